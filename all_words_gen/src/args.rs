@@ -6,6 +6,7 @@ pub(crate) struct AppArgs<'a> {
     pub inflections_db_path: &'a str,
     pub max_stems_to_fetch: i64,
     pub max_batch_size: i64,
+    pub incremental: bool,
 }
 
 impl fmt::Display for AppArgs<'_> {
@@ -13,6 +14,7 @@ impl fmt::Display for AppArgs<'_> {
         writeln!(f, "... inflections_db_path: {}", self.inflections_db_path)?;
         writeln!(f, "... max_stems_to_fetch: {}", self.max_stems_to_fetch)?;
         writeln!(f, "... max_batch_size: {}", self.max_batch_size)?;
+        writeln!(f, "... incremental: {}", self.incremental)?;
         Ok(())
     }
 }
@@ -32,6 +34,7 @@ pub(crate) fn get_args<'a>(args: &'a ArgMatches) -> AppArgs<'a> {
             .expect("mandatory argument")
             .parse::<i64>()
             .expect("Valid default configured already."),
+        incremental: args.is_present("INCREMENTAL"),
     }
 }
 
@@ -43,6 +46,7 @@ pub(crate) fn parse_args<'a>() -> ArgMatches<'a> {
         .arg(create_inflection_db_path_arg())
         .arg(create_max_stems_to_fetch_arg())
         .arg(create_max_batch_size_arg())
+        .arg(create_incremental_arg())
         .get_matches()
 }
 
@@ -62,7 +66,7 @@ fn create_max_stems_to_fetch_arg<'a, 'b>() -> Arg<'a, 'b> {
         .short("s")
         .long("max-stems-to-fetch")
         .value_name("MAX_STEMS_TO_FETCH")
-        .help("The maximum number of stem table entries to consider ordered by rowid.")
+        .help("The maximum number of stem table entries to consider ordered by rowid. Not with --incremental.")
         .required(false)
         .validator(|s| validate_i64(&s))
         .takes_value(true)
@@ -81,6 +85,14 @@ fn create_max_batch_size_arg<'a, 'b>() -> Arg<'a, 'b> {
         .default_value("10")
 }
 
+fn create_incremental_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("INCREMENTAL")
+        .long("incremental")
+        .help("Regenerate only the head words whose _stems row or pattern table changed since the last run.")
+        .required(false)
+        .takes_value(false)
+}
+
 fn validate_file_exists(s: &str) -> Result<(), String> {
     if Path::new(&s).is_file() {
         Ok(())
@@ -0,0 +1,411 @@
+use crate::stem_info::StemInfo;
+use pls_core_extras::sql_access::SqlAccess;
+use rusqlite::ToSql;
+use std::collections::BTreeMap;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+const CELL_SEPARATOR: u8 = 0x1f;
+const ROW_SEPARATOR: u8 = 0x1e;
+
+const CREATE_STATE_TABLES_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS _all_words_version (commit_id TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS _all_words_stem_hashes (stem_id INTEGER PRIMARY KEY, hash TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS _all_words_pattern_hashes (pattern TEXT PRIMARY KEY, hash TEXT NOT NULL);
+"#;
+
+/// Content hashes of the parts of inflections.db that _all_words is generated from.
+#[derive(Debug, Default, PartialEq)]
+pub struct ContentHashes {
+    pub commit_id: String,
+    pub stem_hashes: BTreeMap<i64, String>,
+    pub pattern_hashes: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct StemRecord {
+    pub stem_info: StemInfo,
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    pub stems_to_generate: Vec<StemInfo>,
+    pub stale_stem_ids: Vec<i64>,
+}
+
+pub fn compute_content_hashes(
+    sql_access: &SqlAccess,
+    max_stems_to_fetch: i64,
+) -> Result<(Vec<StemRecord>, ContentHashes), String> {
+    let mut content_hashes = ContentHashes {
+        commit_id: get_commit_id(sql_access),
+        ..Default::default()
+    };
+
+    let rows = sql_access.exec(&format!(
+        "SELECT CAST(rowid AS TEXT), pāli1, stem, pattern, * FROM '_stems' ORDER BY rowid LIMIT {}",
+        max_stems_to_fetch
+    ))?;
+    let mut stem_records = Vec::new();
+    for row in rows.into_iter().flatten() {
+        let row_id = row[0]
+            .parse::<i64>()
+            .expect("rowid must be i64. check for db corruption.");
+        let pattern = match row[2].as_str() {
+            "!" | "-" => None,
+            _ if row[3].is_empty() => None,
+            _ => Some(row[3].to_owned()),
+        };
+
+        content_hashes
+            .stem_hashes
+            .insert(row_id, hash_rows(std::iter::once(&row)));
+        stem_records.push(StemRecord {
+            stem_info: StemInfo {
+                row_id,
+                pali1: row[1].to_owned(),
+            },
+            pattern,
+        });
+    }
+
+    for pattern in stem_records.iter().filter_map(|sr| sr.pattern.as_ref()) {
+        if !content_hashes.pattern_hashes.contains_key(pattern) {
+            let hash = compute_pattern_hash(sql_access, pattern);
+            content_hashes
+                .pattern_hashes
+                .insert(pattern.to_owned(), hash);
+        }
+    }
+
+    Ok((stem_records, content_hashes))
+}
+
+/// Returns None if no content hashes have been recorded yet, i.e. _all_words has never been
+/// generated for this db.
+pub fn load_content_hashes(sql_access: &SqlAccess) -> Result<Option<ContentHashes>, String> {
+    let tables_found = sql_access.exec_scalar::<i32>(
+        r#"SELECT CAST(COUNT(*) AS TEXT) FROM sqlite_master WHERE type = 'table' AND name IN ('_all_words', '_all_words_version', '_all_words_stem_hashes', '_all_words_pattern_hashes')"#,
+    )?;
+    if tables_found != 4 {
+        return Ok(None);
+    }
+
+    // An _all_words from before the folded spellings is regenerated.
+    let folded_columns = sql_access.exec_scalar::<i32>(
+        r#"SELECT CAST(COUNT(*) AS TEXT) FROM pragma_table_info('_all_words') WHERE name = 'inflection_folded'"#,
+    )?;
+    if folded_columns != 1 {
+        return Ok(None);
+    }
+
+    let res = sql_access.exec(
+        r#"
+        SELECT commit_id FROM _all_words_version;
+        SELECT CAST(stem_id AS TEXT), hash FROM _all_words_stem_hashes;
+        SELECT pattern, hash FROM _all_words_pattern_hashes;
+        "#,
+    )?;
+
+    Ok(Some(ContentHashes {
+        commit_id: res[0].first().map(|r| r[0].to_owned()).unwrap_or_default(),
+        stem_hashes: res[1]
+            .iter()
+            .map(|r| {
+                let stem_id = r[0]
+                    .parse::<i64>()
+                    .expect("stem_id must be i64. check for db corruption.");
+                (stem_id, r[1].to_owned())
+            })
+            .collect(),
+        pattern_hashes: res[2]
+            .iter()
+            .map(|r| (r[0].to_owned(), r[1].to_owned()))
+            .collect(),
+    }))
+}
+
+pub fn save_content_hashes(
+    sql_access: &SqlAccess,
+    content_hashes: &ContentHashes,
+) -> Result<(), String> {
+    sql_access.exec(CREATE_STATE_TABLES_SQL)?;
+    sql_access.exec(
+        "DELETE FROM _all_words_version; DELETE FROM _all_words_stem_hashes; DELETE FROM _all_words_pattern_hashes",
+    )?;
+
+    sql_access.exec_bulk_insert(
+        "INSERT INTO _all_words_version (commit_id) VALUES (?1)",
+        vec![vec![&content_hashes.commit_id]],
+    )?;
+    sql_access.exec_bulk_insert(
+        "INSERT INTO _all_words_stem_hashes (stem_id, hash) VALUES (?1, ?2)",
+        content_hashes
+            .stem_hashes
+            .iter()
+            .map(|(id, hash)| vec![id as &dyn ToSql, hash as &dyn ToSql]),
+    )?;
+    sql_access.exec_bulk_insert(
+        "INSERT INTO _all_words_pattern_hashes (pattern, hash) VALUES (?1, ?2)",
+        content_hashes
+            .pattern_hashes
+            .iter()
+            .map(|(pattern, hash)| vec![pattern, hash]),
+    )?;
+
+    Ok(())
+}
+
+/// Forgets the recorded hashes, e.g. while _all_words is rebuilt, so that an --incremental run
+/// after a failed rebuild regenerates everything.
+pub fn clear_content_hashes(sql_access: &SqlAccess) -> Result<(), String> {
+    sql_access.exec(
+        "DROP TABLE IF EXISTS _all_words_version; DROP TABLE IF EXISTS _all_words_stem_hashes; DROP TABLE IF EXISTS _all_words_pattern_hashes",
+    )?;
+
+    Ok(())
+}
+
+/// A stem needs to be regenerated if its own row changed or if the pattern table it inflects by
+/// changed. Stems that are no longer in _stems are stale.
+pub fn find_changes(
+    stem_records: Vec<StemRecord>,
+    current: &ContentHashes,
+    recorded: &ContentHashes,
+) -> ChangeSet {
+    let stale_stem_ids = recorded
+        .stem_hashes
+        .keys()
+        .filter(|id| !current.stem_hashes.contains_key(id))
+        .cloned()
+        .collect();
+
+    let stems_to_generate = stem_records
+        .into_iter()
+        .filter(|sr| {
+            let stem_changed = recorded.stem_hashes.get(&sr.stem_info.row_id)
+                != current.stem_hashes.get(&sr.stem_info.row_id);
+            let pattern_changed = match &sr.pattern {
+                Some(p) => recorded.pattern_hashes.get(p) != current.pattern_hashes.get(p),
+                None => false,
+            };
+            stem_changed || pattern_changed
+        })
+        .map(|sr| sr.stem_info)
+        .collect();
+
+    ChangeSet {
+        stems_to_generate,
+        stale_stem_ids,
+    }
+}
+
+pub fn delete_inflections_for_stems(
+    sql_access: &SqlAccess,
+    stem_ids: &[i64],
+) -> Result<usize, String> {
+    sql_access.exec("CREATE INDEX IF NOT EXISTS _all_words_stem_id ON _all_words (stem_id)")?;
+    sql_access.exec_bulk_insert(
+        "DELETE FROM _all_words WHERE stem_id = ?1",
+        stem_ids.iter().map(|id| vec![id]),
+    )
+}
+
+fn get_commit_id(sql_access: &SqlAccess) -> String {
+    sql_access
+        .exec("SELECT * FROM _version")
+        .ok()
+        .and_then(|res| res.into_iter().flatten().next())
+        .and_then(|row| row.into_iter().next())
+        .unwrap_or_default()
+}
+
+fn compute_pattern_hash(sql_access: &SqlAccess, pattern: &str) -> String {
+    // NOTE: A missing table or _index entry hashes its error message, so the stems using it are
+    // regenerated (and their failures logged) once upstream fixes it.
+    let index_row = sql_access
+        .exec(&format!(
+            "SELECT * FROM '_index' WHERE name = '{}'",
+            pattern.replace("'", "''")
+        ))
+        .unwrap_or_else(|e| vec![vec![vec![e]]]);
+    let table_rows = sql_access
+        .exec(&format!(
+            "SELECT * FROM '{}' ORDER BY rowid",
+            pattern.replace(" ", "_").replace("'", "''")
+        ))
+        .unwrap_or_else(|e| vec![vec![vec![e]]]);
+
+    hash_rows(index_row.iter().chain(table_rows.iter()).flatten())
+}
+
+fn hash_rows<'a>(rows: impl Iterator<Item = &'a Vec<String>>) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut add_byte = |b: u8| {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    };
+
+    for row in rows {
+        for cell in row {
+            cell.bytes().for_each(&mut add_byte);
+            add_byte(CELL_SEPARATOR);
+        }
+        add_byte(ROW_SEPARATOR);
+    }
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pls_core_extras::test_fixtures;
+
+    fn create_test_sql_access() -> SqlAccess {
+        test_fixtures::create_test_sql_access(
+            "INSERT INTO _stems VALUES ('ababa 2', 'abab', 'a masc', 'masc', 'name of a hell')",
+        )
+    }
+
+    fn get_row_ids(cs: &ChangeSet) -> Vec<i64> {
+        cs.stems_to_generate.iter().map(|si| si.row_id).collect()
+    }
+
+    #[test]
+    fn test_hash_rows_is_stable_and_separates_cells() {
+        let row1 = vec!["ab".to_string(), "c".to_string()];
+        let row2 = vec!["a".to_string(), "bc".to_string()];
+        let h1 = hash_rows(std::iter::once(&row1));
+        let h2 = hash_rows(std::iter::once(&row2));
+
+        assert_eq!(h1, hash_rows(std::iter::once(&row1)));
+        assert_ne!(h1, h2);
+        assert_eq!(h1.len(), 16);
+    }
+
+    #[test]
+    fn test_load_content_hashes_before_first_run() {
+        let sa = create_test_sql_access();
+
+        assert_eq!(load_content_hashes(&sa), Ok(None));
+    }
+
+    #[test]
+    fn test_save_and_load_content_hashes_round_trip() {
+        let sa = create_test_sql_access();
+        let (_, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+
+        save_content_hashes(&sa, &current).expect("must save");
+
+        assert_eq!(
+            current.commit_id,
+            "0123456789012345678901234567890123456789"
+        );
+        assert_eq!(current.stem_hashes.len(), 3);
+        assert_eq!(current.pattern_hashes.len(), 1);
+        assert_eq!(load_content_hashes(&sa), Ok(Some(current)));
+    }
+
+    #[test]
+    fn test_load_content_hashes_without_folded_inflections() {
+        let sa = create_test_sql_access();
+        let (_, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        save_content_hashes(&sa, &current).expect("must save");
+
+        sa.exec(
+            r#"
+            DROP TABLE _all_words;
+            CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, stem_id INTEGER NOT NULL);
+            "#,
+        )
+        .expect("must recreate");
+
+        assert_eq!(load_content_hashes(&sa), Ok(None));
+    }
+
+    #[test]
+    fn test_clear_content_hashes() {
+        let sa = create_test_sql_access();
+        let (_, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        save_content_hashes(&sa, &current).expect("must save");
+
+        clear_content_hashes(&sa).expect("must clear");
+
+        assert_eq!(load_content_hashes(&sa), Ok(None));
+    }
+
+    #[test]
+    fn test_find_changes_no_changes() {
+        let sa = create_test_sql_access();
+        let (_, recorded) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        let (stem_records, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+
+        let cs = find_changes(stem_records, &current, &recorded);
+
+        assert!(cs.stems_to_generate.is_empty());
+        assert!(cs.stale_stem_ids.is_empty());
+    }
+
+    #[test]
+    fn test_find_changes_stem_row_changed() {
+        let sa = create_test_sql_access();
+        let (_, recorded) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        sa.exec("UPDATE _stems SET definition = 'not, without' WHERE pāli1 = 'a 1'")
+            .expect("must update");
+        let (stem_records, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+
+        let cs = find_changes(stem_records, &current, &recorded);
+
+        assert_eq!(get_row_ids(&cs), vec![2]);
+        assert!(cs.stale_stem_ids.is_empty());
+    }
+
+    #[test]
+    fn test_find_changes_pattern_table_changed() {
+        let sa = create_test_sql_access();
+        let (_, recorded) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        sa.exec("INSERT INTO a_masc VALUES ('acc', 'masc', 'sg', 'aṃ')")
+            .expect("must insert");
+        let (stem_records, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+
+        let cs = find_changes(stem_records, &current, &recorded);
+
+        assert_eq!(get_row_ids(&cs), vec![1, 3]);
+        assert!(cs.stale_stem_ids.is_empty());
+    }
+
+    #[test]
+    fn test_find_changes_stem_added_and_removed() {
+        let sa = create_test_sql_access();
+        let (_, recorded) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+        sa.exec(
+            "DELETE FROM _stems WHERE pāli1 = 'ababa 1'; INSERT INTO _stems VALUES ('a 2', '-', '', 'ind', 'to')",
+        )
+        .expect("must update");
+        let (stem_records, current) = compute_content_hashes(&sa, i64::MAX).expect("must compute");
+
+        let cs = find_changes(stem_records, &current, &recorded);
+
+        assert_eq!(get_row_ids(&cs), vec![4]);
+        assert_eq!(cs.stale_stem_ids, vec![1]);
+    }
+
+    #[test]
+    fn test_delete_inflections_for_stems() {
+        let sa = create_test_sql_access();
+        sa.exec(
+            "INSERT INTO _all_words (inflection, inflection_folded, stem_id) VALUES ('ababo', 'ababo', 1), ('ababo', 'ababo', 2), ('a', 'a', 3)",
+        )
+        .expect("must insert");
+
+        let ret = delete_inflections_for_stems(&sa, &[1, 3]);
+
+        assert_eq!(ret, Ok(2));
+        assert_eq!(
+            sa.exec("SELECT inflection, CAST(stem_id AS TEXT) FROM _all_words"),
+            Ok(vec![vec![vec!["ababo".to_string(), "2".to_string()]]])
+        );
+    }
+}
@@ -8,10 +8,19 @@ pub struct InflectionInfo {
     pub inflection: String,
 }
 
+/// A head word whose inflections could not be generated.
+#[derive(Serialize, Debug)]
+pub struct GenerationFailure {
+    pub pali1_id: i64,
+    pub pali1: String,
+    pub error: String,
+}
+
 #[derive(Serialize, Debug)]
 pub struct InflectionsBatchInfo {
     pub inflection_infos: Vec<InflectionInfo>,
     pub inflected_forms_fetched: i64,
+    pub failures: Vec<GenerationFailure>,
 }
 
 pub fn create_inflection_infos(
@@ -21,10 +30,21 @@ pub fn create_inflection_infos(
     let mut ibi = InflectionsBatchInfo {
         inflection_infos: vec![],
         inflected_forms_fetched: 0,
+        failures: vec![],
     };
 
     for stem_info in stem_infos {
-        let infs = igen.generate_all_inflections(&stem_info.pali1);
+        let infs = match igen.try_generate_all_inflections(&stem_info.pali1) {
+            Ok(infs) => infs,
+            Err(error) => {
+                ibi.failures.push(GenerationFailure {
+                    pali1_id: stem_info.row_id,
+                    pali1: stem_info.pali1,
+                    error,
+                });
+                continue;
+            }
+        };
         if infs.is_empty() {
             ibi.inflected_forms_fetched += 1;
         }
@@ -72,6 +92,50 @@ mod tests {
         insta::assert_yaml_snapshot!(infl_infos);
     }
 
+    struct FailingGenerator;
+
+    impl InflectionGenerator for FailingGenerator {
+        fn check_inflection_db(&self, _logger: &dyn PlsLogger) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn generate_inflection_table_html(&self, _pali1: &str) -> String {
+            "".to_string()
+        }
+
+        fn generate_all_inflections(&self, pali1: &str) -> Vec<String> {
+            self.try_generate_all_inflections(pali1).unwrap_or_default()
+        }
+
+        fn try_generate_all_inflections(&self, pali1: &str) -> Result<Vec<String>, String> {
+            match pali1 {
+                "dhamma" => Ok(vec!["dhammo".to_string()]),
+                _ => Err("No pattern found".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_inflection_infos_records_failures() {
+        let stems = vec![
+            StemInfo {
+                row_id: 1,
+                pali1: "dhamma".to_string(),
+            },
+            StemInfo {
+                row_id: 2,
+                pali1: "xyz".to_string(),
+            },
+        ];
+
+        let ibi = create_inflection_infos(stems, &FailingGenerator);
+
+        assert_eq!(ibi.inflection_infos.len(), 1);
+        assert_eq!(ibi.failures.len(), 1);
+        assert_eq!(ibi.failures[0].pali1_id, 2);
+        assert_eq!(ibi.failures[0].error, "No pattern found");
+    }
+
     fn create_inflection_generator<'a>(
         db_path: &'a str,
         logger: &'a dyn PlsLogger,
@@ -0,0 +1,191 @@
+use crate::inflection_info::InflectionsBatchInfo;
+use pls_core::alphabet::fold_diacritics;
+use pls_core::inflections::pmd::get_stem_for_indeclinable;
+use pls_core_extras::sql_access::SqlAccess;
+use rusqlite::ToSql;
+
+const INSERT_INFLECTION_SQL: &str =
+    "INSERT INTO _all_words (inflection, inflection_folded, stem_id) VALUES (?1, ?2, ?3)";
+
+const INSERT_HEAD_WORD_SQL: &str =
+    "INSERT INTO _all_words_head_words (stem_id, pali1_folded) VALUES (?1, ?2)";
+
+/// The inflections with their spelling folded for the searches of pls_core, e.g. paññā as panna.
+pub fn insert_inflection_infos(
+    sql_access: &SqlAccess,
+    inflection_infos: &InflectionsBatchInfo,
+) -> Result<usize, String> {
+    let folded: Vec<String> = inflection_infos
+        .inflection_infos
+        .iter()
+        .map(|inf| fold_diacritics(&inf.inflection))
+        .collect();
+
+    sql_access.exec_bulk_insert(
+        INSERT_INFLECTION_SQL,
+        inflection_infos
+            .inflection_infos
+            .iter()
+            .zip(folded.iter())
+            .map(|(inf, folded)| {
+                vec![
+                    &inf.inflection as &dyn ToSql,
+                    folded as &dyn ToSql,
+                    &inf.pali1_id as &dyn ToSql,
+                ]
+            }),
+    )
+}
+
+/// Indexes the forms for the lookups of pls_core, e.g. analyze_word and search_words. Created once
+/// the rows are in.
+pub fn create_all_words_indexes(sql_access: &SqlAccess) -> Result<(), String> {
+    sql_access
+        .exec(
+            r#"
+            CREATE INDEX IF NOT EXISTS _all_words_inflection ON _all_words (inflection);
+            CREATE INDEX IF NOT EXISTS _all_words_inflection_folded ON _all_words (inflection_folded);
+            "#,
+        )
+        .map(|_| ())
+}
+
+/// The folded head words without their homonym numbers, e.g. dhamma for dhammā 2, for the searches
+/// of pls_core. Recreated from all of _stems on every run.
+pub fn create_head_words_table(sql_access: &SqlAccess) -> Result<usize, String> {
+    sql_access.exec(
+        r#"
+        DROP TABLE IF EXISTS _all_words_head_words;
+        CREATE TABLE _all_words_head_words (stem_id INTEGER NOT NULL, pali1_folded TEXT NOT NULL);
+        "#,
+    )?;
+
+    let head_words = sql_access
+        .exec("SELECT CAST(rowid AS TEXT), pāli1 FROM _stems")?
+        .pop()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| {
+            let stem_id = row[0]
+                .parse::<i64>()
+                .expect("stem_id must be i64. check for db corruption.");
+            Ok((
+                stem_id,
+                fold_diacritics(&get_stem_for_indeclinable(&row[1])?),
+            ))
+        })
+        .collect::<Result<Vec<(i64, String)>, String>>()?;
+
+    let n = sql_access.exec_bulk_insert(
+        INSERT_HEAD_WORD_SQL,
+        head_words
+            .iter()
+            .map(|(stem_id, folded)| vec![stem_id as &dyn ToSql, folded as &dyn ToSql]),
+    )?;
+    sql_access.exec(
+        "CREATE INDEX _all_words_head_words_pali1_folded ON _all_words_head_words (pali1_folded)",
+    )?;
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflection_info::InflectionInfo;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_insert_inflection_infos() {
+        let sa = SqlAccess {
+            connection: Connection::open_in_memory().expect("unexpected test setup failure"),
+        };
+        sa.exec("CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, inflection_folded TEXT NOT NULL, stem_id INTEGER NOT NULL)")
+            .expect("unexpected test setup failure");
+        let inflection_infos = InflectionsBatchInfo {
+            inflected_forms_fetched: 10005,
+            failures: vec![],
+            inflection_infos: vec![
+                InflectionInfo {
+                    pali1_id: 102,
+                    inflection: "102-1".to_string(),
+                },
+                InflectionInfo {
+                    pali1_id: 102,
+                    inflection: "ti'".to_string(),
+                },
+                InflectionInfo {
+                    pali1_id: 103,
+                    inflection: "paññā".to_string(),
+                },
+            ],
+        };
+
+        let ret = insert_inflection_infos(&sa, &inflection_infos);
+
+        assert_eq!(ret, Ok(3));
+        assert_eq!(
+            sa.exec(
+                "SELECT inflection, inflection_folded, CAST(stem_id AS TEXT) FROM _all_words ORDER BY inflectionIndex"
+            ),
+            Ok(vec![vec![
+                vec!["102-1".to_string(), "102-1".to_string(), "102".to_string()],
+                vec!["ti'".to_string(), "ti'".to_string(), "102".to_string()],
+                vec!["paññā".to_string(), "panna".to_string(), "103".to_string()],
+            ]])
+        );
+    }
+
+    #[test]
+    fn test_create_all_words_indexes() {
+        let sa = SqlAccess {
+            connection: Connection::open_in_memory().expect("unexpected test setup failure"),
+        };
+        sa.exec("CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, inflection_folded TEXT NOT NULL, stem_id INTEGER NOT NULL)")
+            .expect("unexpected test setup failure");
+
+        assert_eq!(create_all_words_indexes(&sa), Ok(()));
+        assert_eq!(create_all_words_indexes(&sa), Ok(()));
+        assert_eq!(
+            sa.exec(
+                "EXPLAIN QUERY PLAN SELECT stem_id FROM _all_words WHERE inflection = 'dhammo'"
+            )
+            .map(|t| t[0][0][3].contains("USING INDEX _all_words_inflection ")),
+            Ok(true)
+        );
+        assert_eq!(
+            sa.exec(
+                "EXPLAIN QUERY PLAN SELECT stem_id FROM _all_words WHERE inflection_folded = 'dhammo'"
+            )
+            .map(|t| t[0][0][3].contains("USING INDEX _all_words_inflection_folded ")),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_create_head_words_table() {
+        let sa = SqlAccess {
+            connection: Connection::open_in_memory().expect("unexpected test setup failure"),
+        };
+        sa.exec(
+            r#"
+            CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+            INSERT INTO _stems VALUES ('paññā', 'paññ', 'ā fem', 'fem', 'wisdom');
+            INSERT INTO _stems VALUES ('dhammā 2', '!', 'dhamma 1', 'masc', 'natures');
+            "#,
+        )
+        .expect("unexpected test setup failure");
+
+        assert_eq!(create_head_words_table(&sa), Ok(2));
+        assert_eq!(create_head_words_table(&sa), Ok(2));
+        assert_eq!(
+            sa.exec(
+                "SELECT CAST(stem_id AS TEXT), pali1_folded FROM _all_words_head_words ORDER BY stem_id"
+            ),
+            Ok(vec![vec![
+                vec!["1".to_string(), "panna".to_string()],
+                vec!["2".to_string(), "dhamma".to_string()],
+            ]])
+        );
+    }
+}
@@ -1,13 +1,21 @@
-use crate::inflection_info::create_inflection_infos;
-use crate::inflection_sql_queries::create_inflection_sql_queries;
+use crate::content_hashes::{
+    clear_content_hashes, compute_content_hashes, delete_inflections_for_stems, find_changes,
+    load_content_hashes, save_content_hashes,
+};
+use crate::inflection_info::{create_inflection_infos, GenerationFailure};
+use crate::inflection_insertion::{
+    create_all_words_indexes, create_head_words_table, insert_inflection_infos,
+};
+use crate::stem_info::StemInfo;
 use chrono::{Datelike, Utc};
 use pls_core_extras::inflection_generator::PlsInflectionGenerator;
 use pls_core_extras::logger::{ColoredConsoleLogger, PlsLogger};
 use std::borrow::BorrowMut;
 
 mod args;
+mod content_hashes;
 mod inflection_info;
-mod inflection_sql_queries;
+mod inflection_insertion;
 mod stem_info;
 
 fn main() -> Result<(), String> {
@@ -15,6 +23,11 @@ fn main() -> Result<(), String> {
     let args = args::get_args(&arg_matches);
     print_banner();
 
+    // The stems past the limit would not be hashed and so taken as deleted from _stems.
+    if args.incremental && arg_matches.occurrences_of("MAX_STEMS_TO_FETCH") > 0 {
+        return Err("--max-stems-to-fetch cannot be used with --incremental.".to_string());
+    }
+
     let logger = &ColoredConsoleLogger {};
     logger.info("Generating all words with the following parameters:");
     logger.info(&format!(
@@ -26,6 +39,7 @@ fn main() -> Result<(), String> {
         args.max_stems_to_fetch
     ));
     logger.info(&format!("... max_batch_size: {}", args.max_batch_size));
+    logger.info(&format!("... incremental: {}", args.incremental));
     logger.info("");
 
     let igen = &PlsInflectionGenerator::new(
@@ -36,60 +50,153 @@ fn main() -> Result<(), String> {
         logger,
     )?;
 
-    logger.info("(Re)Creating _all_words table...");
-    create_all_words_table(igen)?;
+    logger.info("Computing content hashes of _stems and pattern tables...");
+    let sql_access = &igen.inflection_host.sql_access;
+    let (stem_records, mut current_hashes) =
+        compute_content_hashes(sql_access, args.max_stems_to_fetch)?;
+    let recorded_hashes = if args.incremental {
+        load_content_hashes(sql_access)?
+    } else {
+        None
+    };
 
-    logger.info("Inserting inflections into _all_words...");
-    let mut sii = crate::stem_info::StemInfoIterator::new(
-        &igen.inflection_host.sql_access,
-        args.max_stems_to_fetch,
-        args.max_batch_size,
-    );
-    let mut ibis = sii
-        .borrow_mut()
-        .map(|x| create_inflection_infos(x, igen))
-        .map(create_inflection_sql_queries);
-    let mut inflections_generated = 0;
-    let mut inflected_forms_fetched = 0;
-    let mut n = 0;
-    for ibi in &mut ibis {
-        inflections_generated += ibi.inflection_sql_queries.len() - 2;
-        inflected_forms_fetched += ibi.inflected_forms_fetched;
+    let summary = match recorded_hashes {
+        Some(recorded_hashes) => {
+            logger.info(&format!(
+                "Updating _all_words from version '{}' to '{}'...",
+                recorded_hashes.commit_id, current_hashes.commit_id
+            ));
+            let change_set = find_changes(stem_records, &current_hashes, &recorded_hashes);
+            logger.info(&format!(
+                "... stale head words: {}",
+                change_set.stale_stem_ids.len()
+            ));
+            logger.info(&format!(
+                "... changed head words: {}",
+                change_set.stems_to_generate.len()
+            ));
 
-        let batch_query = ibi.inflection_sql_queries.join(";\n");
-        match &igen.inflection_host.sql_access.exec(&batch_query) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                logger.error(&format!("Insertion into db failed with {}", e));
-                Err(e)
+            let stem_ids: Vec<i64> = change_set
+                .stale_stem_ids
+                .iter()
+                .chain(change_set.stems_to_generate.iter().map(|si| &si.row_id))
+                .cloned()
+                .collect();
+            match delete_inflections_for_stems(sql_access, &stem_ids) {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    logger.error(&format!("Deletion from db failed with {}", e));
+                    Err(e)
+                }
+            }?;
+
+            logger.info("Inserting inflections into _all_words...");
+            let mut batches = change_set
+                .stems_to_generate
+                .chunks(args.max_batch_size.max(1) as usize)
+                .map(|c| c.to_vec());
+            let (inflections_generated, inflected_forms_fetched, failures) =
+                insert_inflections(&mut batches, igen)?;
+
+            GenerationSummary {
+                stems_fetched: change_set.stems_to_generate.len() as i64,
+                batches_fetched: change_set
+                    .stems_to_generate
+                    .chunks(args.max_batch_size.max(1) as usize)
+                    .count() as i64,
+                inflections_generated,
+                inflected_forms_fetched,
+                failures,
+                error: None,
+            }
+        }
+        None => {
+            if args.incremental {
+                logger.info(
+                    "No content hashes recorded yet, or _all_words is from an older version. Regenerating all head words.",
+                );
             }
-        }?;
 
-        n += 1;
-        if n % 1000 == 0 {
-            logger.info(&format!(
-                "... inserted {:05} entries into db. ({}).",
-                n,
-                ibi.inflection_sql_queries[ibi.inflection_sql_queries.len() - 2]
-            ));
+            logger.info("(Re)Creating _all_words table...");
+            create_all_words_table(igen)?;
+            clear_content_hashes(sql_access)?;
+
+            logger.info("Inserting inflections into _all_words...");
+            let mut sii = crate::stem_info::StemInfoIterator::new(
+                sql_access,
+                args.max_stems_to_fetch,
+                args.max_batch_size,
+            );
+            let (inflections_generated, inflected_forms_fetched, failures) =
+                insert_inflections(sii.borrow_mut(), igen)?;
+
+            GenerationSummary {
+                stems_fetched: sii.stems_fetched,
+                batches_fetched: sii.batches_fetched,
+                inflections_generated,
+                inflected_forms_fetched,
+                failures,
+                error: sii.error,
+            }
+        }
+    };
+
+    logger.info("Indexing _all_words...");
+    match create_all_words_indexes(sql_access) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            logger.error(&format!("Indexing _all_words failed with {}", e));
+            Err(e)
+        }
+    }?;
+
+    logger.info("(Re)Creating _all_words_head_words table...");
+    match create_head_words_table(sql_access) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            logger.error(&format!("Creating _all_words_head_words failed with {}", e));
+            Err(e)
+        }
+    }?;
+
+    // Without hashes the next --incremental run regenerates everything, and without the hashes of
+    // the failed head words it retries them.
+    match &summary.error {
+        Some(e) => logger.warning(&format!(
+            "Not recording content hashes as generation stopped early. Error: {}",
+            e
+        )),
+        None => {
+            logger.info("Recording content hashes...");
+            for failure in &summary.failures {
+                current_hashes.stem_hashes.remove(&failure.pali1_id);
+            }
+            save_content_hashes(sql_access, &current_hashes)?;
         }
     }
 
     logger.info("Summary:");
-    logger.info(&format!("... Head words fetched: {}", sii.stems_fetched));
+    logger.info(&format!(
+        "... Head words fetched: {}",
+        summary.stems_fetched
+    ));
     logger.info(&format!(
         "... Head word batches fetched: {}",
-        sii.batches_fetched
+        summary.batches_fetched
     ));
     logger.info(&format!(
         "... Inflection generated: {}",
-        inflections_generated
+        summary.inflections_generated
     ));
     logger.info(&format!(
         "... Inflected forms fetched: {}",
-        inflected_forms_fetched
+        summary.inflected_forms_fetched
+    ));
+    logger.info(&format!(
+        "... Head words failed: {}",
+        summary.failures.len()
     ));
-    logger.info(&format!("... Error: {:?}", sii.error));
+    logger.info(&format!("... Error: {:?}", summary.error));
 
     logger.info("");
     print_table_word_count("_stems", igen);
@@ -98,8 +205,59 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
+struct GenerationSummary {
+    stems_fetched: i64,
+    batches_fetched: i64,
+    inflections_generated: usize,
+    inflected_forms_fetched: i64,
+    failures: Vec<GenerationFailure>,
+    error: Option<String>,
+}
+
+fn insert_inflections(
+    batches: &mut dyn Iterator<Item = Vec<StemInfo>>,
+    igen: &PlsInflectionGenerator,
+) -> Result<(usize, i64, Vec<GenerationFailure>), String> {
+    let logger = igen.inflection_host.logger;
+    let mut ibis = batches.map(|x| create_inflection_infos(x, igen));
+    let mut inflections_generated = 0;
+    let mut inflected_forms_fetched = 0;
+    let mut failures = Vec::new();
+    let mut n = 0;
+    for mut ibi in &mut ibis {
+        inflections_generated += ibi.inflection_infos.len();
+        inflected_forms_fetched += ibi.inflected_forms_fetched;
+        for failure in &ibi.failures {
+            logger.warning(&format!(
+                "Unable to generate inflections for '{}'. Error: {}.",
+                failure.pali1, failure.error
+            ));
+        }
+        failures.append(&mut ibi.failures);
+
+        match insert_inflection_infos(&igen.inflection_host.sql_access, &ibi) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                logger.error(&format!("Insertion into db failed with {}", e));
+                Err(e)
+            }
+        }?;
+
+        n += 1;
+        if n % 1000 == 0 {
+            logger.info(&format!(
+                "... inserted {:05} entries into db. ({:?}).",
+                n,
+                ibi.inflection_infos.last()
+            ));
+        }
+    }
+
+    Ok((inflections_generated, inflected_forms_fetched, failures))
+}
+
 fn create_all_words_table(igen: &PlsInflectionGenerator) -> Result<(), String> {
-    let query = r#"DROP TABLE IF EXISTS _all_words; CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, stem_id INTEGER NOT NULL);"#;
+    let query = r#"DROP TABLE IF EXISTS _all_words; CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, inflection_folded TEXT NOT NULL, stem_id INTEGER NOT NULL);"#;
     match igen.inflection_host.sql_access.exec(query) {
         Ok(_) => Ok(()),
         Err(e) => {
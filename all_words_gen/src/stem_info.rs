@@ -1,7 +1,7 @@
 use pls_core_extras::sql_access::SqlAccess;
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StemInfo {
     pub row_id: i64,
     pub pali1: String,
@@ -69,8 +69,7 @@ impl Iterator for StemInfoIterator<'_> {
         {
             self.batches_fetched += 1;
             let stem_infos: Vec<StemInfo> = curr_batch[0]
-                .to_owned()
-                .into_iter()
+                .iter()
                 .map(|x| StemInfo {
                     row_id: x[0]
                         .parse::<i64>()
@@ -0,0 +1,111 @@
+use clap::{App, Arg, ArgMatches};
+use std::path::Path;
+
+pub(crate) struct AppArgs<'a> {
+    pub inflections_db_path: &'a str,
+    pub xml_paths: Vec<&'a str>,
+    pub include_notes: bool,
+    pub top: usize,
+    pub verbose: bool,
+}
+
+pub(crate) fn get_args<'a>(args: &'a ArgMatches) -> AppArgs<'a> {
+    AppArgs {
+        inflections_db_path: args
+            .value_of("INFLECTION_DB_PATH")
+            .expect("mandatory argument"),
+        xml_paths: args
+            .values_of("XML_PATHS")
+            .expect("mandatory argument")
+            .collect(),
+        include_notes: args.is_present("INCLUDE_NOTES"),
+        top: args
+            .value_of("TOP")
+            .expect("mandatory argument")
+            .parse::<usize>()
+            .expect("Valid default configured already."),
+        verbose: args.is_present("VERBOSE"),
+    }
+}
+
+pub(crate) fn parse_args<'a>() -> ArgMatches<'a> {
+    App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .arg(create_inflection_db_path_arg())
+        .arg(create_xml_paths_arg())
+        .arg(create_include_notes_arg())
+        .arg(create_top_arg())
+        .arg(create_verbose_arg())
+        .get_matches()
+}
+
+fn create_inflection_db_path_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("INFLECTION_DB_PATH")
+        .short("i")
+        .long("inflection-db")
+        .value_name("INFLECTION_DB_PATH")
+        .help("The path to inflections.db with the _all_words table from all_words_gen.")
+        .required(true)
+        .validator(|s| validate_file_exists(&s))
+        .takes_value(true)
+}
+
+fn create_xml_paths_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("XML_PATHS")
+        .value_name("XML_PATHS")
+        .help("The CSCD book .xml files, or directories to search for them, e.g. tipitaka/romn.")
+        .required(true)
+        .multiple(true)
+        .validator(|s| validate_path_exists(&s))
+}
+
+fn create_include_notes_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("INCLUDE_NOTES")
+        .long("include-notes")
+        .help("Count the words of the <note> variant readings too.")
+        .required(false)
+        .takes_value(false)
+}
+
+fn create_top_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("TOP")
+        .short("t")
+        .long("top")
+        .value_name("TOP")
+        .help("The number of entries in each frequency list, 0 for all.")
+        .required(false)
+        .validator(|s| validate_usize(&s))
+        .takes_value(true)
+        .default_value("100")
+}
+
+fn create_verbose_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("VERBOSE")
+        .short("v")
+        .long("verbose")
+        .help("Log progress to stderr.")
+        .required(false)
+        .takes_value(false)
+}
+
+fn validate_file_exists(s: &str) -> Result<(), String> {
+    if Path::new(&s).is_file() {
+        Ok(())
+    } else {
+        Err(format!("'{}' does not exist.", s))
+    }
+}
+
+fn validate_path_exists(s: &str) -> Result<(), String> {
+    if Path::new(&s).exists() {
+        Ok(())
+    } else {
+        Err(format!("'{}' does not exist.", s))
+    }
+}
+
+fn validate_usize(s: &str) -> Result<(), String> {
+    s.parse::<usize>().map(|_| ()).map_err(|e| e.to_string())
+}
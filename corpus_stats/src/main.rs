@@ -0,0 +1,67 @@
+use crate::stats::{compute_book_stats, count_forms, lookup_lemmas, CorpusStats, FormCounts};
+use pls_core_extras::cscd;
+use pls_core_extras::inflection_generator::{get_version_info, PlsInflectionGenerator};
+use pls_core_extras::logger::{ColoredStderrLogger, NullLogger, PlsLogger};
+
+mod args;
+mod stats;
+
+fn main() -> Result<(), String> {
+    let arg_matches = args::parse_args();
+    let args = args::get_args(&arg_matches);
+
+    let logger: &dyn PlsLogger = if args.verbose {
+        &ColoredStderrLogger {}
+    } else {
+        &NullLogger {}
+    };
+
+    let igen = PlsInflectionGenerator::new(
+        "en",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_NAME"),
+        args.inflections_db_path,
+        logger,
+    )?;
+    let commit_id = get_version_info(&igen.inflection_host)?.commit_id;
+
+    let files = cscd::find_xml_files(&args.xml_paths)?;
+    logger.info(&format!("Reading {} book(s)...", files.len()));
+    let mut books = Vec::new();
+    let mut total = FormCounts::default();
+    for file in files {
+        let book = cscd::read_book(&file, args.include_notes)?;
+        let counts = count_forms(&book);
+        logger.info(&format!(
+            "... {}: {} paragraphs, {} words",
+            book.id,
+            counts.paragraphs,
+            counts.forms.values().sum::<usize>()
+        ));
+        total.merge(&counts);
+        books.push((book.id, book.title, counts));
+    }
+
+    logger.info(&format!(
+        "Looking up {} distinct words in _all_words...",
+        total.forms.len()
+    ));
+    let lemmas = lookup_lemmas(
+        total.forms.keys().map(|f| f.as_str()),
+        &igen.inflection_host,
+    )?;
+
+    let corpus_stats = CorpusStats {
+        commit_id,
+        books: books
+            .iter()
+            .map(|(id, title, counts)| compute_book_stats(id, title, counts, &lemmas, args.top))
+            .collect(),
+        total: compute_book_stats("total", "", &total, &lemmas, args.top),
+    };
+
+    let json = serde_json::to_string_pretty(&corpus_stats).map_err(|e| e.to_string())?;
+    println!("{}", json);
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use pls_core::alphabet::sort_key;
+use pls_core::inflections::host::PlsInflectionsHost;
+use pls_core::inflections::pmd::get_stem_for_indeclinable;
+use pls_core::tokenizer::normalize_word;
+use pls_core_extras::cscd::CscdBook;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+const LOOKUP_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Frequency {
+    pub text: String,
+    pub count: usize,
+}
+
+/// The word counts of a book, or of the whole corpus.
+#[derive(Debug, Default)]
+pub struct FormCounts {
+    pub paragraphs: usize,
+    pub verse_lines: usize,
+    pub forms: HashMap<String, usize>,
+}
+
+impl FormCounts {
+    pub fn merge(&mut self, other: &FormCounts) {
+        self.paragraphs += other.paragraphs;
+        self.verse_lines += other.verse_lines;
+        for (form, count) in &other.forms {
+            *self.forms.entry(form.clone()).or_insert(0) += count;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BookStats {
+    pub book: String,
+    pub title: String,
+    pub paragraphs: usize,
+    pub verse_lines: usize,
+    pub words: usize,
+    pub distinct_words: usize,
+    pub covered_words: usize,
+    pub distinct_covered_words: usize,
+    pub word_frequencies: Vec<Frequency>,
+    pub lemma_frequencies: Vec<Frequency>,
+    pub uncovered_words: Vec<Frequency>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CorpusStats {
+    pub commit_id: String,
+    pub books: Vec<BookStats>,
+    pub total: BookStats,
+}
+
+pub fn count_forms(book: &CscdBook) -> FormCounts {
+    let mut counts = FormCounts {
+        paragraphs: book.paragraphs.len(),
+        verse_lines: book.paragraphs.iter().filter(|p| p.is_gatha()).count(),
+        forms: HashMap::new(),
+    };
+    for paragraph in &book.paragraphs {
+        for word in pls_core::tokenizer::words(&paragraph.text) {
+            let form = normalize_word(word.text);
+            if !form.is_empty() {
+                *counts.forms.entry(form).or_insert(0) += 1;
+            }
+        }
+    }
+
+    counts
+}
+
+/// The head words each form is an inflection of, from _all_words and the indeclinables. Forms no
+/// head word generates are left out.
+/// The forms are looked up on the _all_words_inflection index all_words_gen creates.
+pub fn lookup_lemmas<'a>(
+    forms: impl Iterator<Item = &'a str>,
+    host: &dyn PlsInflectionsHost,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut lemmas: HashMap<String, Vec<String>> = HashMap::new();
+    let forms: Vec<&str> = forms.collect();
+    for batch in forms.chunks(LOOKUP_BATCH_SIZE) {
+        let in_list = batch
+            .iter()
+            .map(|f| format!("'{}'", f.replace("'", "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+        let sql = format!(
+            r#"SELECT w.inflection, s.pāli1 FROM _all_words w JOIN _stems s ON s.rowid = w.stem_id WHERE w.inflection IN ({})"#,
+            in_list
+        );
+        for row in host.exec_sql_query(&sql)?.into_iter().flatten() {
+            if let [form, pali1] = row.as_slice() {
+                lemmas.entry(form.clone()).or_default().push(pali1.clone());
+            }
+        }
+    }
+
+    let forms: HashSet<&str> = forms.into_iter().collect();
+    let sql = "SELECT pāli1 FROM _stems WHERE stem IN ('!', '-')";
+    for pali1 in host.exec_sql_query(sql)?.into_iter().flatten().flatten() {
+        let form = normalize_word(&get_stem_for_indeclinable(&pali1)?);
+        if forms.contains(form.as_str()) {
+            lemmas.entry(form).or_default().push(pali1);
+        }
+    }
+
+    for pali1s in lemmas.values_mut() {
+        pali1s.sort_by_cached_key(|p| sort_key(p));
+        pali1s.dedup();
+    }
+
+    Ok(lemmas)
+}
+
+/// A form with several head words counts towards each of them. top limits the frequency lists,
+/// 0 for all.
+pub fn compute_book_stats(
+    book: &str,
+    title: &str,
+    counts: &FormCounts,
+    lemmas: &HashMap<String, Vec<String>>,
+    top: usize,
+) -> BookStats {
+    let mut lemma_counts: HashMap<&str, usize> = HashMap::new();
+    let mut uncovered: HashMap<&str, usize> = HashMap::new();
+    for (form, &count) in &counts.forms {
+        match lemmas.get(form) {
+            Some(pali1s) => {
+                for pali1 in pali1s {
+                    *lemma_counts.entry(pali1).or_insert(0) += count;
+                }
+            }
+            None => {
+                uncovered.insert(form, count);
+            }
+        }
+    }
+
+    let words = counts.forms.values().sum();
+    let uncovered_words: usize = uncovered.values().sum();
+    BookStats {
+        book: book.to_string(),
+        title: title.to_string(),
+        paragraphs: counts.paragraphs,
+        verse_lines: counts.verse_lines,
+        words,
+        distinct_words: counts.forms.len(),
+        covered_words: words - uncovered_words,
+        distinct_covered_words: counts.forms.len() - uncovered.len(),
+        word_frequencies: to_frequencies(counts.forms.iter().map(|(f, &c)| (f.as_str(), c)), top),
+        lemma_frequencies: to_frequencies(lemma_counts.into_iter(), top),
+        uncovered_words: to_frequencies(uncovered.into_iter(), top),
+    }
+}
+
+/// Most frequent first, ties in alphabet order.
+fn to_frequencies<'a>(
+    counts: impl Iterator<Item = (&'a str, usize)>,
+    top: usize,
+) -> Vec<Frequency> {
+    let mut frequencies: Vec<(usize, Vec<u32>, &str)> =
+        counts.map(|(t, c)| (c, sort_key(t), t)).collect();
+    frequencies.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    if top > 0 {
+        frequencies.truncate(top);
+    }
+
+    frequencies
+        .into_iter()
+        .map(|(count, _, text)| Frequency {
+            text: text.to_string(),
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pls_core_extras::cscd::parse_book;
+    use pls_core_extras::logger::NullLogger;
+    use pls_core_extras::test_fixtures::create_test_host;
+
+    const DB_SQL: &str = r#"
+        INSERT INTO _stems VALUES ('dhamma 1', 'dhamm', 'a masc', 'masc', 'nature');
+        INSERT INTO _stems VALUES ('dhamma 2', 'dhamm', 'a nt', 'nt', 'teaching');
+        INSERT INTO _stems VALUES ('bhikkhu', 'bhikkh', 'u masc', 'masc', 'monk');
+        INSERT INTO _stems VALUES ('ca', '-', '', 'ind', 'and');
+        INSERT INTO _stems VALUES ('pi 1', '-', '', 'ind', 'also');
+        INSERT INTO _all_words (inflection, inflection_folded, stem_id) VALUES
+            ('dhammo', 'dhammo', 3), ('dhammaṃ', 'dhammam', 3), ('dhammaṃ', 'dhammam', 4),
+            ('bhikkhu', 'bhikkhu', 5), ('bhikkhave', 'bhikkhave', 5);
+    "#;
+
+    const BOOK_XML: &str = r#"<body>
+<head rend="book">Dhammapotthako</head>
+<p rend="bodytext" n="1"><hi rend="paranum">1</hi><hi rend="dot">.</hi> Bhikkhave, dhammaṃ ca desemi.</p>
+<p rend="gatha1">Dhammo pi ca bhikkhu’pi dhammaṃ;</p>
+<p rend="gathalast">Ajjhattaṃ dhammaṃ ca.</p>
+</body>"#;
+
+    #[test]
+    fn lookup_lemmas_test() {
+        let logger = NullLogger {};
+        let host = create_test_host(DB_SQL, &logger);
+
+        let lemmas = lookup_lemmas(
+            vec!["dhammaṃ", "ca", "pi", "bhikkhave", "desemi", "x'y"].into_iter(),
+            &host,
+        )
+        .unwrap();
+
+        let mut lemmas: Vec<_> = lemmas.into_iter().collect();
+        lemmas.sort();
+        insta::assert_yaml_snapshot!(lemmas);
+    }
+
+    #[test]
+    fn compute_book_stats_test() {
+        let logger = NullLogger {};
+        let host = create_test_host(DB_SQL, &logger);
+        let book = parse_book("s0101m.mul", BOOK_XML, false).unwrap();
+        let counts = count_forms(&book);
+        let lemmas = lookup_lemmas(counts.forms.keys().map(|f| f.as_str()), &host).unwrap();
+
+        let stats = compute_book_stats(&book.id, &book.title, &counts, &lemmas, 0);
+
+        insta::assert_yaml_snapshot!(stats);
+    }
+
+    #[test]
+    fn compute_book_stats_top_test() {
+        let counts = FormCounts {
+            paragraphs: 1,
+            verse_lines: 0,
+            forms: vec![("b", 2), ("a", 2), ("c", 5), ("ṃ", 1)]
+                .into_iter()
+                .map(|(f, c)| (f.to_string(), c))
+                .collect(),
+        };
+
+        let stats = compute_book_stats("b", "", &counts, &HashMap::new(), 3);
+
+        assert_eq!(
+            stats
+                .uncovered_words
+                .iter()
+                .map(|f| (f.text.as_str(), f.count))
+                .collect::<Vec<_>>(),
+            vec![("c", 5), ("a", 2), ("b", 2)]
+        );
+        assert_eq!(stats.words, 10);
+        assert_eq!(stats.covered_words, 0);
+    }
+}
@@ -28,3 +28,16 @@ export const execSql = (sql) => {
         throw e
     }
 }
+
+export const execSqlAsync = async (sql) => {
+    if (window.__pali_language_services_execSqlAsync) {
+        try {
+            return JSON.stringify(await window.__pali_language_services_execSqlAsync(sql))
+        } catch (e) {
+            console.error('pali-language-services-dal.execSqlAsync', e)
+            throw e
+        }
+    }
+
+    return execSql(sql)
+}
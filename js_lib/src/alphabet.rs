@@ -0,0 +1,239 @@
+use crate::error::{ErrorKind, PlsError};
+use crate::types::{to_js, JsStringArray};
+use pls_core::alphabet::{Character, CharacterTokenizer, PALI_ALPHABET_ROMAN};
+use pls_core::ipa::{transcribe, Profile};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "(number | string)[]")]
+    pub type JsTokens;
+
+    #[wasm_bindgen(typescript_type = "TextToken[]")]
+    pub type JsTextTokens;
+
+    #[wasm_bindgen(typescript_type = "LintDiagnostic[]")]
+    pub type JsLintDiagnostics;
+
+    #[wasm_bindgen(typescript_type = "ParsedNumeral")]
+    pub type JsParsedNumeral;
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TEXT_TOKEN_TYPES: &'static str = r#"
+export type TextTokenKind =
+    | "Word"
+    | "Elided"
+    | "Number"
+    | "Peyyala"
+    | "Punctuation"
+    | "Whitespace"
+    | "Other";
+
+/** Offsets are into the text, end exclusive. Use the char offsets with Array.from(text). */
+export interface TextToken {
+    kind: TextTokenKind;
+    text: string;
+    byte_start: number;
+    byte_end: number;
+    char_start: number;
+    char_end: number;
+}
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LINT_TYPES: &'static str = r#"
+export type LintRule =
+    | "non-pali-letter"
+    | "illegal-cluster"
+    | "cluster-too-long"
+    | "illegal-onset"
+    | "consonant-ending"
+    | "niggahita-before-vowel"
+    | "misplaced-niggahita";
+
+/** text is the offending letters of word. Offsets are into the linted text, end exclusive. */
+export interface LintDiagnostic {
+    rule: LintRule;
+    severity: "warning" | "error";
+    message: string;
+    word: string;
+    text: string;
+    byte_start: number;
+    byte_end: number;
+    char_start: number;
+    char_end: number;
+}
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_NUMERAL_TYPES: &'static str = r#"
+export interface ParsedNumeral {
+    value: number;
+    kind: "cardinal" | "ordinal";
+}
+"#;
+
+#[wasm_bindgen(js_name = stringCompare)]
+pub fn string_compare(str1: &str, str2: &str) -> isize {
+    pls_core::alphabet::string_compare(str1, str2)
+}
+
+#[wasm_bindgen(js_name = stringLength)]
+pub fn string_length(str1: &str) -> usize {
+    pls_core::alphabet::string_length(str1)
+}
+
+/// The letters in alphabet order. A letter's index is its PaliAlphabet value.
+#[wasm_bindgen(js_name = paliAlphabetRoman)]
+pub fn pali_alphabet_roman() -> JsStringArray {
+    PALI_ALPHABET_ROMAN
+        .iter()
+        .map(|&l| JsValue::from_str(l))
+        .collect::<js_sys::Array>()
+        .unchecked_into()
+}
+
+/// Splits the string into letters, as their PaliAlphabet index, and other characters, as is.
+#[wasm_bindgen]
+pub fn tokenize(str1: &str) -> JsTokens {
+    CharacterTokenizer::new(str1.chars())
+        .map(|c| match c {
+            Character::Pali(c) => JsValue::from(c as u32),
+            Character::Other(c) => JsValue::from(c.to_string()),
+        })
+        .collect::<js_sys::Array>()
+        .unchecked_into()
+}
+
+/// Lower case without the diacritics of the letters, e.g. "sangha" for "Saṅgha", to match
+/// searchWords on the client.
+#[wasm_bindgen(js_name = foldDiacritics)]
+pub fn fold_diacritics(str1: &str) -> String {
+    pls_core::alphabet::fold_diacritics(str1)
+}
+
+/// Splits running text into words, numbers, punctuation and whitespace with their offsets.
+#[wasm_bindgen(js_name = tokenizeText)]
+pub fn tokenize_text(text: &str) -> Result<JsTextTokens, JsValue> {
+    to_js(&pls_core::tokenizer::tokenize(text))
+        .map_err(|e| PlsError::new(ErrorKind::GenerationFailed, e).into())
+}
+
+/// Checks the words of the text against Pāli phonotactics, e.g. before publishing it.
+#[wasm_bindgen(js_name = lintText)]
+pub fn lint_text(text: &str) -> Result<JsLintDiagnostics, JsValue> {
+    to_js(&pls_core::phonotactics::lint_text(text))
+        .map_err(|e| PlsError::new(ErrorKind::GenerationFailed, e).into())
+}
+
+/// Transcribes the words of the text to IPA as a recitation tradition says them: "reconstructed",
+/// "sri-lankan", "burmese" or "thai". Syllables are separated by "." if syllableBoundaries is set.
+#[wasm_bindgen(js_name = transcribeIpa)]
+pub fn transcribe_ipa(
+    text: &str,
+    profile: &str,
+    syllable_boundaries: Option<bool>,
+) -> Result<String, JsValue> {
+    let profile = profile
+        .parse::<Profile>()
+        .map_err(|e| PlsError::new(ErrorKind::InvalidOptions, e))?;
+
+    Ok(transcribe(
+        text,
+        profile,
+        syllable_boundaries.unwrap_or(false),
+    ))
+}
+
+/// Spells the number, 1 to 999999999, as a Pāli cardinal, e.g. "sahassa dvisata paññāsa", or
+/// ordinal.
+#[wasm_bindgen(js_name = spellNumeral)]
+pub fn spell_numeral(n: u32, ordinal: Option<bool>) -> Result<String, JsValue> {
+    let spelling = if ordinal.unwrap_or(false) {
+        pls_core::numerals::ordinal(n as u64)
+    } else {
+        pls_core::numerals::cardinal(n as u64)
+    };
+
+    spelling.map_err(|e| PlsError::new(ErrorKind::InvalidOptions, e).into())
+}
+
+/// Reads a Pāli numeral phrase, e.g. "pañca satāni" as 500.
+#[wasm_bindgen(js_name = parseNumeral)]
+pub fn parse_numeral(phrase: &str) -> Result<JsParsedNumeral, JsValue> {
+    let parsed = pls_core::numerals::parse_numeral(phrase)
+        .map_err(|e| PlsError::new(ErrorKind::InvalidOptions, e))?;
+    to_js(&parsed).map_err(|e| PlsError::new(ErrorKind::GenerationFailed, e).into())
+}
+
+/// An Array.prototype.sort comparator, e.g. words.sort(compare). Use sortWords for large arrays.
+#[wasm_bindgen]
+pub fn compare(str1: &str, str2: &str) -> i32 {
+    pls_core::alphabet::string_compare(str1, str2) as i32
+}
+
+/// A key that sorts with plain string comparison (<, localeCompare is not the same) as the string
+/// does with compare, e.g. for IndexedDB indices.
+#[wasm_bindgen(js_name = sortKey)]
+pub fn sort_key(str1: &str) -> String {
+    pls_core::alphabet::sort_key(str1)
+        .iter()
+        .map(|k| format!("{:06x}", k))
+        .collect()
+}
+
+/// Returns the words sorted, in one call rather than one per comparison.
+#[wasm_bindgen(js_name = sortWords)]
+pub fn sort_words(words: JsStringArray) -> Result<JsStringArray, JsValue> {
+    let mut words = from_js_strings(&words)?;
+    pls_core::alphabet::sort_strings(&mut words);
+
+    Ok(words
+        .into_iter()
+        .map(JsValue::from)
+        .collect::<js_sys::Array>()
+        .unchecked_into())
+}
+
+/// Returns the indices of the words in sorted order, to sort records by one of their fields.
+#[wasm_bindgen(js_name = sortIndices)]
+pub fn sort_indices(words: JsStringArray) -> Result<Vec<u32>, JsValue> {
+    let words = from_js_strings(&words)?;
+    let mut indices: Vec<u32> = (0..words.len() as u32).collect();
+    indices.sort_by_cached_key(|&i| pls_core::alphabet::sort_key(&words[i as usize]));
+
+    Ok(indices)
+}
+
+fn from_js_strings(words: &JsStringArray) -> Result<Vec<String>, PlsError> {
+    words
+        .unchecked_ref::<js_sys::Array>()
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            w.as_string().ok_or_else(|| {
+                PlsError::new(
+                    ErrorKind::InvalidOptions,
+                    format!("Expected an array of strings, the item at {} is not.", i),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("a", "b" => true)]
+    #[test_case("x", "a" => true)]
+    #[test_case("pāli 1", "pālicca" => true)]
+    #[test_case("ṃ", "a" => false; "niggahita last")]
+    #[test_case("buddh", "buddho" => true)]
+    fn sort_key_orders_as_compare(str1: &str, str2: &str) -> bool {
+        sort_key(str1) < sort_key(str2)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::{ErrorKind, PlsError};
+use js_sys::{Function, Promise, Reflect};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+pub(crate) const DEFAULT_DATA_ACCESS: &str = "default";
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DATA_ACCESS_TYPES: &'static str = r#"
+/**
+ * How the library reaches inflections.db. The queries resolve to the rows of each ';' separated
+ * statement, i.e. string[][][], or that as JSON.
+ */
+export interface DataAccess {
+    /** Used by the sync functions, and by the async ones when execSqlAsync is not set. */
+    execSql?: (sql: string) => string | string[][][];
+    execSqlAsync?: (sql: string) => Promise<string | string[][][]>;
+    /** Converts roman to the script of the locale. Defaults to leaving the text as is. */
+    transliterate?: (s: string) => string;
+}
+"#;
+
+#[wasm_bindgen(raw_module = "./pali_language_services_dal.js")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = transliterate)]
+    fn dal_transliterate(sql: &str) -> Result<String, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = execSql)]
+    fn dal_exec_sql(sql: &str) -> Result<String, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = execSqlAsync)]
+    fn dal_exec_sql_async(sql: &str) -> Result<Promise, JsValue>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "DataAccess")]
+    pub type JsDataAccess;
+}
+
+thread_local! {
+    static DATA_ACCESSES: RefCell<HashMap<String, DataAccess>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Clone)]
+pub(crate) enum DataAccess {
+    /// pali_language_services_dal.js, which reads the window.__pali_language_services_* globals.
+    Dal,
+    Callbacks {
+        exec_sql: Option<Function>,
+        exec_sql_async: Option<Function>,
+        transliterate: Option<Function>,
+    },
+}
+
+/// Registers the callbacks the library queries inflections.db and transliterates through, so it
+/// runs without a window, e.g. in Node, Deno or a worker. The functions that take no data access
+/// name use the one registered as "default", or the window globals if there is none.
+#[wasm_bindgen(js_name = registerDataAccess)]
+pub fn register_data_access(
+    data_access: JsDataAccess,
+    name: Option<String>,
+) -> Result<(), JsValue> {
+    let data_access = DataAccess::from_js(&data_access)?;
+    let name = name.unwrap_or_else(|| DEFAULT_DATA_ACCESS.to_string());
+    DATA_ACCESSES.with(|das| das.borrow_mut().insert(name, data_access));
+
+    Ok(())
+}
+
+/// Returns whether a data access was registered under the name.
+#[wasm_bindgen(js_name = unregisterDataAccess)]
+pub fn unregister_data_access(name: Option<String>) -> bool {
+    let name = name.unwrap_or_else(|| DEFAULT_DATA_ACCESS.to_string());
+    DATA_ACCESSES.with(|das| das.borrow_mut().remove(&name).is_some())
+}
+
+impl DataAccess {
+    pub(crate) fn registered(name: &str) -> Option<DataAccess> {
+        DATA_ACCESSES.with(|das| das.borrow().get(name).cloned())
+    }
+
+    fn from_js(data_access: &JsValue) -> Result<DataAccess, PlsError> {
+        if !data_access.is_object() {
+            return Err(invalid_data_access(
+                "The data access must be an object.".to_string(),
+            ));
+        }
+
+        let exec_sql = get_function(data_access, "execSql")?;
+        let exec_sql_async = get_function(data_access, "execSqlAsync")?;
+        if exec_sql.is_none() && exec_sql_async.is_none() {
+            return Err(invalid_data_access(
+                "The data access needs execSql or execSqlAsync.".to_string(),
+            ));
+        }
+
+        Ok(DataAccess::Callbacks {
+            exec_sql,
+            exec_sql_async,
+            transliterate: get_function(data_access, "transliterate")?,
+        })
+    }
+
+    pub(crate) fn transliterate(&self, s: &str) -> Result<String, String> {
+        match self {
+            DataAccess::Dal => dal_transliterate(s).map_err(exception_to_string),
+            DataAccess::Callbacks {
+                transliterate: Some(transliterate),
+                ..
+            } => transliterate
+                .call1(&JsValue::NULL, &s.into())
+                .map_err(exception_to_string)?
+                .as_string()
+                .ok_or_else(|| "transliterate did not return a string.".to_string()),
+            DataAccess::Callbacks { .. } => Ok(s.to_string()),
+        }
+    }
+
+    pub(crate) fn exec_sql(&self, sql: &str) -> Result<String, String> {
+        match self {
+            DataAccess::Dal => dal_exec_sql(sql).map_err(exception_to_string),
+            DataAccess::Callbacks {
+                exec_sql: Some(exec_sql),
+                ..
+            } => {
+                let result = exec_sql
+                    .call1(&JsValue::NULL, &sql.into())
+                    .map_err(exception_to_string)?;
+                rows_to_json(result, "execSql")
+            }
+            DataAccess::Callbacks { .. } => Err(
+                "The data access has no execSql, only the async functions can use it.".to_string(),
+            ),
+        }
+    }
+
+    pub(crate) async fn exec_sql_async(&self, sql: &str) -> Result<String, String> {
+        let promise = match self {
+            DataAccess::Dal => dal_exec_sql_async(sql).map_err(exception_to_string)?,
+            DataAccess::Callbacks {
+                exec_sql_async: Some(exec_sql_async),
+                ..
+            } => {
+                let result = exec_sql_async
+                    .call1(&JsValue::NULL, &sql.into())
+                    .map_err(exception_to_string)?;
+                Promise::resolve(&result)
+            }
+            DataAccess::Callbacks { .. } => return self.exec_sql(sql),
+        };
+
+        let result = JsFuture::from(promise).await.map_err(exception_to_string)?;
+        rows_to_json(result, "execSqlAsync")
+    }
+}
+
+fn get_function(data_access: &JsValue, name: &str) -> Result<Option<Function>, PlsError> {
+    let value = Reflect::get(data_access, &name.into()).map_err(|e| {
+        invalid_data_access(format!(
+            "Unable to read {}: {}",
+            name,
+            exception_to_string(e)
+        ))
+    })?;
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+
+    value
+        .dyn_into::<Function>()
+        .map(Some)
+        .map_err(|_| invalid_data_access(format!("{} must be a function.", name)))
+}
+
+/// The callbacks may return the rows themselves rather than their JSON.
+fn rows_to_json(result: JsValue, name: &str) -> Result<String, String> {
+    if let Some(json) = result.as_string() {
+        return Ok(json);
+    }
+
+    js_sys::JSON::stringify(&result)
+        .map(String::from)
+        .map_err(|_| format!("{} did not return rows or their JSON.", name))
+}
+
+fn invalid_data_access(message: String) -> PlsError {
+    PlsError::new(ErrorKind::InvalidOptions, message)
+}
+
+/// The callbacks may throw strings or Error objects.
+pub(crate) fn exception_to_string(e: JsValue) -> String {
+    match e.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => e
+            .as_string()
+            .unwrap_or_else(|| "No exception string!".to_string()),
+    }
+}
@@ -0,0 +1,98 @@
+use pls_core::inflections::pmd::Pali1MetadataError;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_ERROR_TYPES: &'static str = r#"
+export type PlsErrorKind = "WordNotFound" | "InvalidOptions" | "DataAccessFailed" | "GenerationFailed";
+
+/** What the exported functions throw, or reject with for the async ones. */
+export interface PlsError extends Error {
+    kind: PlsErrorKind;
+    pali1?: string;
+}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ErrorKind {
+    /// The word is not in inflections.db.
+    WordNotFound,
+    InvalidOptions,
+    /// inflections.db could not be read, e.g. a failing query or a pattern missing from _index.
+    DataAccessFailed,
+    /// The word is known but its inflections could not be generated, e.g. a failing query.
+    GenerationFailed,
+}
+
+impl ErrorKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::WordNotFound => "WordNotFound",
+            ErrorKind::InvalidOptions => "InvalidOptions",
+            ErrorKind::DataAccessFailed => "DataAccessFailed",
+            ErrorKind::GenerationFailed => "GenerationFailed",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct PlsError {
+    pub(crate) kind: ErrorKind,
+    pub(crate) message: String,
+    pub(crate) pali1: Option<String>,
+}
+
+impl PlsError {
+    pub(crate) fn new(kind: ErrorKind, message: String) -> PlsError {
+        PlsError {
+            kind,
+            message,
+            pali1: None,
+        }
+    }
+
+    pub(crate) fn for_word(kind: ErrorKind, pali1: &str, message: String) -> PlsError {
+        PlsError {
+            kind,
+            message,
+            pali1: Some(pali1.to_string()),
+        }
+    }
+
+    /// WordNotFound only when the word is missing from the db, else DataAccessFailed.
+    pub(crate) fn for_metadata(pali1: &str, e: Pali1MetadataError) -> PlsError {
+        match e {
+            Pali1MetadataError::NotFound(msg) => {
+                PlsError::for_word(ErrorKind::WordNotFound, pali1, msg)
+            }
+            Pali1MetadataError::Failed(msg) => {
+                PlsError::for_word(ErrorKind::DataAccessFailed, pali1, msg)
+            }
+        }
+    }
+}
+
+/// An Error object, so the exception has a stack, with kind and pali1 set on it.
+impl From<PlsError> for JsValue {
+    fn from(e: PlsError) -> JsValue {
+        let error = js_sys::Error::new(&e.message);
+        error.set_name("PlsError");
+        let _ = js_sys::Reflect::set(&error, &"kind".into(), &e.kind.as_str().into());
+        if let Some(pali1) = &e.pali1 {
+            let _ = js_sys::Reflect::set(&error, &"pali1".into(), &pali1.into());
+        }
+
+        error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(Pali1MetadataError::NotFound("Word 'x' not found in db.".to_string()) => ErrorKind::WordNotFound; "not found")]
+    #[test_case(Pali1MetadataError::Failed("no such table: _stems".to_string()) => ErrorKind::DataAccessFailed; "failed")]
+    fn for_metadata_tests(e: Pali1MetadataError) -> ErrorKind {
+        PlsError::for_metadata("x", e).kind
+    }
+}
@@ -1,60 +1,330 @@
-use pls_core::inflections::host::PlsInflectionsHost;
+use crate::data_access::{DataAccess, DEFAULT_DATA_ACCESS};
+use crate::error::{ErrorKind, PlsError};
+use crate::types::{
+    to_js, InflectionTableOptions, JsAbbreviations, JsInflectionTable, JsInflectionTableData,
+    JsInflectionTableOptions, JsInflectionTablePromise, JsPali1Metadata, JsSearchResults,
+    JsStringArray, JsStringArrayPromise, TableFormat,
+};
+use pls_core::inflections::host::{PlsInflectionsHost, PlsInflectionsHostAsync};
+use pls_core::inflections::pmd::{find_pali1_metadata, find_pali1_metadata_async, Pali1Metadata};
+use std::future::Future;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
-#[wasm_bindgen(raw_module = "./pali_language_services_dal.js")]
+mod alphabet;
+mod data_access;
+mod error;
+mod types;
+
+pub use crate::data_access::{register_data_access, unregister_data_access};
+
+const DEFAULT_MAX_SEARCH_RESULTS: usize = 50;
+
+#[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(catch, js_name = transliterate)]
-    fn transliterate(sql: &str) -> Result<String, JsValue>;
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(msg: &str);
+}
 
-    #[wasm_bindgen(catch, js_name = execSql)]
-    fn exec_sql(sql: &str) -> Result<String, JsValue>;
+#[wasm_bindgen(start)]
+pub fn init() {
+    console_error_panic_hook::set_once();
 }
 
-#[wasm_bindgen(js_name = stringCompare)]
-pub fn string_compare(str1: &str, str2: &str) -> isize {
-    pls_core::alphabet::string_compare(str1, str2)
+struct Host<'a> {
+    locale: &'a str,
+    version: &'a str,
+    url: &'a str,
+    data_access: &'a DataAccess,
 }
 
-#[wasm_bindgen(js_name = stringLength)]
-pub fn string_length(str1: &str) -> usize {
-    pls_core::alphabet::string_length(str1)
+impl<'a> PlsInflectionsHost for Host<'a> {
+    fn get_locale(&self) -> &str {
+        self.locale
+    }
+
+    fn get_version(&self) -> &str {
+        self.version
+    }
+
+    fn get_url(&self) -> &str {
+        self.url
+    }
+
+    fn transliterate(&self, s: &str) -> Result<String, String> {
+        self.data_access.transliterate(s)
+    }
+
+    fn exec_sql_query_core(&self, sql: &str) -> Result<String, String> {
+        self.data_access.exec_sql(sql)
+    }
+
+    fn log_warning(&self, msg: &str) {
+        console_warn(&format!("pali-language-services: {}", msg));
+    }
 }
 
-struct Host<'a> {
+/// Same as Host but the queries go through execSqlAsync, so they can run in a Web Worker.
+struct AsyncHost<'a> {
     locale: &'a str,
     version: &'a str,
     url: &'a str,
+    data_access: &'a DataAccess,
 }
 
-impl<'a> PlsInflectionsHost<'a> for Host<'a> {
-    fn get_locale(&self) -> &'a str {
+impl<'a> PlsInflectionsHostAsync for AsyncHost<'a> {
+    fn get_locale(&self) -> &str {
         self.locale
     }
 
-    fn get_version(&self) -> &'a str {
+    fn get_version(&self) -> &str {
         self.version
     }
 
-    fn get_url(&self) -> &'a str {
+    fn get_url(&self) -> &str {
         self.url
     }
 
     fn transliterate(&self, s: &str) -> Result<String, String> {
-        transliterate(s).map_err(|e| {
-            e.as_string()
-                .unwrap_or_else(|| "No exception string!".to_string())
+        self.data_access.transliterate(s)
+    }
+
+    fn exec_sql_query_core_async<'b>(
+        &'b self,
+        sql: &'b str,
+    ) -> impl Future<Output = Result<String, String>> + 'b {
+        self.data_access.exec_sql_async(sql)
+    }
+
+    fn log_warning(&self, msg: &str) {
+        console_warn(&format!("pali-language-services: {}", msg));
+    }
+}
+
+/// The inflection API over one registered data access, e.g. one per inflections.db.
+#[wasm_bindgen(js_name = PaliLanguageServices)]
+#[derive(Clone)]
+pub struct Services {
+    url: String,
+    version: String,
+    data_access: DataAccess,
+}
+
+#[wasm_bindgen(js_class = PaliLanguageServices)]
+impl Services {
+    /// Uses the data access registered under the name, "default" if none is given.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        host_url: String,
+        host_version: String,
+        data_access: Option<String>,
+    ) -> Result<Services, JsValue> {
+        let name = data_access.unwrap_or_else(|| DEFAULT_DATA_ACCESS.to_string());
+        let data_access = DataAccess::registered(&name).ok_or_else(|| {
+            PlsError::new(
+                ErrorKind::InvalidOptions,
+                format!("No data access is registered as '{}'.", name),
+            )
+        })?;
+
+        Ok(Services {
+            url: host_url,
+            version: host_version,
+            data_access,
         })
     }
 
-    fn exec_sql_query_core(&self, sql: &str) -> Result<String, String> {
-        exec_sql(sql).map_err(|e| {
-            e.as_string()
-                .unwrap_or_else(|| "No exception string!".to_string())
+    /// Renders the table as html, or returns its data for options.format = "json".
+    #[wasm_bindgen(js_name = generateInflectionTable)]
+    pub fn generate_inflection_table(
+        &self,
+        pali1: &str,
+        options: JsInflectionTableOptions,
+    ) -> Result<JsInflectionTable, JsValue> {
+        let options = table_options(&options)?;
+        let host = self.host(&options.locale);
+
+        find_word(pali1, &host)?;
+        let table = match options.format {
+            TableFormat::Html => {
+                pls_core::inflections::generate_inflection_table(pali1, options.details, &host)
+                    .map(|html| JsValue::from_str(&html).unchecked_into())
+            }
+            TableFormat::Json => {
+                pls_core::inflections::generate_inflection_table_data(pali1, &host)
+                    .and_then(|data| to_js(&data))
+            }
+        };
+
+        table.map_err(|e| generation_failed(pali1, e).into())
+    }
+
+    #[wasm_bindgen(js_name = generateInflectionTableData)]
+    pub fn generate_inflection_table_data(
+        &self,
+        pali1: &str,
+        locale: &str,
+    ) -> Result<JsInflectionTableData, JsValue> {
+        let host = self.host(locale);
+
+        find_word(pali1, &host)?;
+        pls_core::inflections::generate_inflection_table_data(pali1, &host)
+            .and_then(|data| to_js(&data))
+            .map_err(|e| generation_failed(pali1, e).into())
+    }
+
+    #[wasm_bindgen(js_name = generateAllInflections)]
+    pub fn generate_all_inflections(
+        &self,
+        pali1: &str,
+        locale: &str,
+    ) -> Result<JsStringArray, JsValue> {
+        let host = self.host(locale);
+
+        find_word(pali1, &host)?;
+        pls_core::inflections::generate_all_inflections(pali1, &host)
+            .and_then(|inflections| to_js(&inflections))
+            .map_err(|e| generation_failed(pali1, e).into())
+    }
+
+    #[wasm_bindgen(js_name = getPali1Metadata)]
+    pub fn get_pali1_metadata(
+        &self,
+        pali1: &str,
+        locale: &str,
+    ) -> Result<JsPali1Metadata, JsValue> {
+        let pm = find_word(pali1, &self.host(locale))?;
+        to_js(&pm).map_err(|e| generation_failed(pali1, e).into())
+    }
+
+    /// Maps abbreviations and their descriptions to the abbreviation in the locale.
+    #[wasm_bindgen(js_name = getAbbreviations)]
+    pub fn get_abbreviations(&self, locale: &str) -> Result<JsAbbreviations, JsValue> {
+        pls_core::inflections::get_abbreviations_for_locale(&self.host(locale))
+            .and_then(|abbreviations| to_js(&abbreviations))
+            .map_err(|e| PlsError::new(ErrorKind::GenerationFailed, e).into())
+    }
+
+    /// Head words and inflected forms that match the query ignoring diacritics, e.g. paññā for
+    /// "panna", exact matches first. Needs _all_words.
+    #[wasm_bindgen(js_name = searchWords)]
+    pub fn search_words(
+        &self,
+        query: &str,
+        max_results: Option<usize>,
+    ) -> Result<JsSearchResults, JsValue> {
+        pls_core::inflections::search_words(
+            query,
+            max_results.unwrap_or(DEFAULT_MAX_SEARCH_RESULTS),
+            &self.host("en"),
+        )
+        .and_then(|results| to_js(&results))
+        .map_err(|e| PlsError::new(ErrorKind::GenerationFailed, e).into())
+    }
+
+    /// Same as generateInflectionTable, with the queries through execSqlAsync.
+    #[wasm_bindgen(js_name = generateInflectionTableAsync)]
+    pub fn generate_inflection_table_async(
+        &self,
+        pali1: String,
+        options: JsInflectionTableOptions,
+    ) -> JsInflectionTablePromise {
+        let services = self.clone();
+        let options = table_options(&options);
+        wasm_bindgen_futures::future_to_promise(async move {
+            services
+                .inflection_table_async(&pali1, &options?)
+                .await
+                .map_err(JsValue::from)
         })
+        .unchecked_into()
     }
 
-    fn log_warning(&self, _msg: &str) {
-        // TODO: Send this to console.warn.
+    #[wasm_bindgen(js_name = generateAllInflectionsAsync)]
+    pub fn generate_all_inflections_async(
+        &self,
+        pali1: String,
+        locale: String,
+    ) -> JsStringArrayPromise {
+        let services = self.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            services
+                .all_inflections_async(&pali1, &locale)
+                .await
+                .map(JsValue::from)
+                .map_err(JsValue::from)
+        })
+        .unchecked_into()
+    }
+}
+
+impl Services {
+    /// For the free functions, which predate registered data access.
+    fn default_for(host_url: &str, host_version: &str) -> Services {
+        Services {
+            url: host_url.to_string(),
+            version: host_version.to_string(),
+            data_access: DataAccess::registered(DEFAULT_DATA_ACCESS).unwrap_or(DataAccess::Dal),
+        }
+    }
+
+    fn host<'a>(&'a self, locale: &'a str) -> Host<'a> {
+        Host {
+            locale,
+            version: &self.version,
+            url: &self.url,
+            data_access: &self.data_access,
+        }
+    }
+
+    fn async_host<'a>(&'a self, locale: &'a str) -> AsyncHost<'a> {
+        AsyncHost {
+            locale,
+            version: &self.version,
+            url: &self.url,
+            data_access: &self.data_access,
+        }
+    }
+
+    async fn inflection_table_async(
+        &self,
+        pali1: &str,
+        options: &InflectionTableOptions,
+    ) -> Result<JsValue, PlsError> {
+        let host = self.async_host(&options.locale);
+
+        find_word_async(pali1, &host).await?;
+        let table = match options.format {
+            TableFormat::Html => pls_core::inflections::generate_inflection_table_async(
+                pali1,
+                options.details,
+                &host,
+            )
+            .await
+            .map(|html| JsValue::from_str(&html)),
+            TableFormat::Json => {
+                pls_core::inflections::generate_inflection_table_data_async(pali1, &host)
+                    .await
+                    .and_then(|data| to_js(&data))
+            }
+        };
+
+        table.map_err(|e| generation_failed(pali1, e))
+    }
+
+    async fn all_inflections_async(
+        &self,
+        pali1: &str,
+        locale: &str,
+    ) -> Result<js_sys::Array, PlsError> {
+        let host = self.async_host(locale);
+
+        find_word_async(pali1, &host).await?;
+        let inflections = pls_core::inflections::generate_all_inflections_async(pali1, &host)
+            .await
+            .map_err(|e| generation_failed(pali1, e))?;
+
+        Ok(inflections.into_iter().map(JsValue::from).collect())
     }
 }
 
@@ -64,15 +334,120 @@ pub fn generate_inflection_table(
     host_url: &str,
     host_version: &str,
     locale: &str,
-) -> String {
-    pls_core::inflections::generate_inflection_table(
-        pali1,
-        true,
-        &Host {
-            locale,
-            url: host_url,
-            version: host_version,
-        },
-    )
-    .unwrap()
+) -> Result<String, JsValue> {
+    let services = Services::default_for(host_url, host_version);
+    let host = services.host(locale);
+
+    find_word(pali1, &host)?;
+    pls_core::inflections::generate_inflection_table(pali1, true, &host)
+        .map_err(|e| generation_failed(pali1, e).into())
+}
+
+/// Renders the table as html, or returns its data for options.format = "json".
+#[wasm_bindgen(js_name = generateInflectionTableWithOptions)]
+pub fn generate_inflection_table_with_options(
+    pali1: &str,
+    host_url: &str,
+    host_version: &str,
+    options: JsInflectionTableOptions,
+) -> Result<JsInflectionTable, JsValue> {
+    Services::default_for(host_url, host_version).generate_inflection_table(pali1, options)
+}
+
+#[wasm_bindgen(js_name = generateInflectionTableData)]
+pub fn generate_inflection_table_data(
+    pali1: &str,
+    host_url: &str,
+    host_version: &str,
+    locale: &str,
+) -> Result<JsInflectionTableData, JsValue> {
+    Services::default_for(host_url, host_version).generate_inflection_table_data(pali1, locale)
+}
+
+#[wasm_bindgen(js_name = generateAllInflections)]
+pub fn generate_all_inflections(
+    pali1: &str,
+    host_url: &str,
+    host_version: &str,
+    locale: &str,
+) -> Result<JsStringArray, JsValue> {
+    Services::default_for(host_url, host_version).generate_all_inflections(pali1, locale)
+}
+
+#[wasm_bindgen(js_name = getPali1Metadata)]
+pub fn get_pali1_metadata(
+    pali1: &str,
+    host_url: &str,
+    host_version: &str,
+    locale: &str,
+) -> Result<JsPali1Metadata, JsValue> {
+    Services::default_for(host_url, host_version).get_pali1_metadata(pali1, locale)
+}
+
+/// Maps abbreviations and their descriptions to the abbreviation in the locale.
+#[wasm_bindgen(js_name = getAbbreviations)]
+pub fn get_abbreviations(
+    host_url: &str,
+    host_version: &str,
+    locale: &str,
+) -> Result<JsAbbreviations, JsValue> {
+    Services::default_for(host_url, host_version).get_abbreviations(locale)
+}
+
+/// Head words and inflected forms that match the query ignoring diacritics, exact matches first.
+#[wasm_bindgen(js_name = searchWords)]
+pub fn search_words(
+    query: &str,
+    host_url: &str,
+    host_version: &str,
+    max_results: Option<usize>,
+) -> Result<JsSearchResults, JsValue> {
+    Services::default_for(host_url, host_version).search_words(query, max_results)
+}
+
+/// Same as generateInflectionTableWithOptions, with the queries through execSqlAsync.
+#[wasm_bindgen(js_name = generateInflectionTableAsync)]
+pub fn generate_inflection_table_async(
+    pali1: String,
+    host_url: String,
+    host_version: String,
+    options: JsInflectionTableOptions,
+) -> JsInflectionTablePromise {
+    Services::default_for(&host_url, &host_version).generate_inflection_table_async(pali1, options)
+}
+
+#[wasm_bindgen(js_name = generateAllInflectionsAsync)]
+pub async fn generate_all_inflections_async(
+    pali1: String,
+    host_url: String,
+    host_version: String,
+    locale: String,
+) -> Result<js_sys::Array, JsValue> {
+    Services::default_for(&host_url, &host_version)
+        .all_inflections_async(&pali1, &locale)
+        .await
+        .map_err(JsValue::from)
+}
+
+fn table_options(options: &JsInflectionTableOptions) -> Result<InflectionTableOptions, PlsError> {
+    InflectionTableOptions::from_js(options)
+        .map_err(|e| PlsError::new(ErrorKind::InvalidOptions, e))
+}
+
+/// Distinguishes a word missing from inflections.db from a failure to inflect it.
+fn find_word(pali1: &str, host: &dyn PlsInflectionsHost) -> Result<Pali1Metadata, PlsError> {
+    find_pali1_metadata(pali1, host).map_err(|e| PlsError::for_metadata(pali1, e))
+}
+
+async fn find_word_async(
+    pali1: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Pali1Metadata, PlsError> {
+    find_pali1_metadata_async(pali1, host)
+        .await
+        .map_err(|e| PlsError::for_metadata(pali1, e))
+}
+
+fn generation_failed(pali1: &str, e: String) -> PlsError {
+    PlsError::for_word(ErrorKind::GenerationFailed, pali1, e)
 }
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type InflectionClass =
+    | "Conjugation"
+    | "Declension"
+    | "DeclensionPron1st"
+    | "DeclensionPron2nd"
+    | "DeclensionPronDual";
+
+export type WordType =
+    | { InflectedForm: { stems: string } }
+    | { Indeclinable: { stem: string } }
+    | { Irregular: { pattern: string; inflection_class: InflectionClass } }
+    | { Declinable: { stem: string; pattern: string; inflection_class: InflectionClass } };
+
+export interface Pali1Metadata {
+    pali1: string;
+    word_type: WordType;
+    pos: string;
+    meaning: string;
+    like: string;
+    long_name: string;
+}
+
+export interface InflectionCell {
+    parameters: Record<string, string>;
+    inflections: string[];
+}
+
+export interface InflectionTableData {
+    metadata: Pali1Metadata;
+    cells: InflectionCell[];
+}
+
+/** A word that matches a search ignoring diacritics, with the head words it comes from. */
+export interface SearchResult {
+    word: string;
+    pali1s: string[];
+    exact: boolean;
+}
+
+export interface InflectionTableOptions {
+    /** Include the word details and feedback links. Defaults to true. */
+    details?: boolean;
+    /** Defaults to "en". */
+    locale?: string;
+    /** "html" renders the table, "json" returns InflectionTableData. Defaults to "html". */
+    format?: "html" | "json";
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Pali1Metadata")]
+    pub type JsPali1Metadata;
+
+    #[wasm_bindgen(typescript_type = "InflectionTableData")]
+    pub type JsInflectionTableData;
+
+    #[wasm_bindgen(typescript_type = "Record<string, string>")]
+    pub type JsAbbreviations;
+
+    #[wasm_bindgen(typescript_type = "string[]")]
+    pub type JsStringArray;
+
+    #[wasm_bindgen(typescript_type = "InflectionTableOptions | undefined")]
+    pub type JsInflectionTableOptions;
+
+    #[wasm_bindgen(typescript_type = "string | InflectionTableData")]
+    pub type JsInflectionTable;
+
+    #[wasm_bindgen(typescript_type = "SearchResult[]")]
+    pub type JsSearchResults;
+
+    #[wasm_bindgen(typescript_type = "Promise<string | InflectionTableData>")]
+    pub type JsInflectionTablePromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<string[]>")]
+    pub type JsStringArrayPromise;
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TableFormat {
+    Html,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct InflectionTableOptions {
+    pub(crate) details: bool,
+    pub(crate) locale: String,
+    pub(crate) format: TableFormat,
+}
+
+impl Default for InflectionTableOptions {
+    fn default() -> Self {
+        InflectionTableOptions {
+            details: true,
+            locale: "en".to_string(),
+            format: TableFormat::Html,
+        }
+    }
+}
+
+impl InflectionTableOptions {
+    pub(crate) fn from_js(options: &JsInflectionTableOptions) -> Result<Self, String> {
+        if options.is_undefined() || options.is_null() {
+            return Ok(InflectionTableOptions::default());
+        }
+
+        let json = js_sys::JSON::stringify(options)
+            .map_err(|_| "Options must be serializable to JSON.".to_string())?;
+        Self::from_json(&String::from(json))
+    }
+
+    pub(crate) fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid options. Error: {}.", e))
+    }
+}
+
+/// Converts through JSON so the object has the same shape as the pls_server responses.
+pub(crate) fn to_js<T: Serialize, U: JsCast>(value: &T) -> Result<U, String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    js_sys::JSON::parse(&json)
+        .map(|v| v.unchecked_into())
+        .map_err(|_| "Unable to convert result to a JS object.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("{}" => Ok(InflectionTableOptions::default()))]
+    #[test_case(r#"{"details":false,"locale":"si","format":"json"}"# => Ok(InflectionTableOptions {
+        details: false,
+        locale: "si".to_string(),
+        format: TableFormat::Json,
+    }))]
+    #[test_case(r#"{"locale":"si"}"# => Ok(InflectionTableOptions {
+        locale: "si".to_string(),
+        ..InflectionTableOptions::default()
+    }))]
+    #[test_case(r#"{"format":"xml"}"# => Err("Invalid options. Error: unknown variant `xml`, expected `html` or `json` at line 1 column 15.".to_string()))]
+    fn from_json_tests(json: &str) -> Result<InflectionTableOptions, String> {
+        InflectionTableOptions::from_json(json)
+    }
+}
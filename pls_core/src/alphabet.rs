@@ -62,6 +62,19 @@ pub const PALI_ALPHABET_ROMAN: &[&str] = &[
     "ṃ", // nigahita - 40-40
 ];
 
+/// PALI_ALPHABET_ROMAN without the diacritics, letter for letter, so folding keeps the number of
+/// characters.
+pub const PALI_ALPHABET_FOLDED: &[&str] = &[
+    "a", "a", "i", "i", "u", "u", "e", "o", // vowels - 0-7
+    "k", "kh", "g", "gh", "n", // guttural - 8-12
+    "c", "ch", "j", "jh", "n", // palatal - 13-17
+    "t", "th", "d", "dh", "n", // retroflex cerebral - 18-22
+    "t", "th", "d", "dh", "n", // dental - 23-27
+    "p", "ph", "b", "bh", "m", // labial - 28-32
+    "y", "r", "l", "v", "s", "h", "l", // semi-vowel - 33-39
+    "m", // nigahita - 40-40
+];
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Character {
     Other(char),
@@ -111,6 +124,80 @@ pub fn string_length(str1: &str) -> usize {
     chars1.count()
 }
 
+/// Keys that compare as the strings do in string_compare: characters outside the alphabet by code
+/// point, then the letters in alphabet order.
+pub fn sort_key(str1: &str) -> Vec<u32> {
+    CharacterTokenizer::new(str1.chars())
+        .map(|c| match c {
+            Character::Other(c) => c as u32,
+            Character::Pali(c) => SORT_KEY_PALI_BASE + c as u32,
+        })
+        .collect()
+}
+
+/// Lower case without the diacritics of the letters, e.g. "Saṅgha" and "sangha" both fold to
+/// "sangha". ṁ is read as ṃ and combining marks are dropped.
+pub fn fold_diacritics(str1: &str) -> String {
+    let lower: String = str1
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| if c == 'ṁ' { 'ṃ' } else { c })
+        .collect();
+
+    let mut folded = String::with_capacity(lower.len());
+    for c in CharacterTokenizer::new(lower.chars()) {
+        match c {
+            Character::Pali(c) => folded.push_str(PALI_ALPHABET_FOLDED[c as usize]),
+            Character::Other(c) => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+/// A letter with its char offsets into the string it comes from, end exclusive.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Letter {
+    pub character: Character,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// The letters of a word with their offsets. Case is ignored and ṁ is read as ṃ.
+pub(crate) fn letters_with_offsets(str1: &str) -> Vec<Letter> {
+    let lower: String = str1
+        .chars()
+        .map(|c| match c.to_lowercase().next().unwrap_or(c) {
+            'ṁ' => 'ṃ',
+            c => c,
+        })
+        .collect();
+
+    let mut char_start = 0;
+    CharacterTokenizer::new(lower.chars())
+        .map(|character| {
+            let len = match character {
+                Character::Pali(c) => PALI_ALPHABET_ROMAN[c as usize].chars().count(),
+                Character::Other(_) => 1,
+            };
+            char_start += len;
+            Letter {
+                character,
+                char_start: char_start - len,
+                char_end: char_start,
+            }
+        })
+        .collect()
+}
+
+const SORT_KEY_PALI_BASE: u32 = char::MAX as u32 + 1;
+
+/// Sorts as string_compare does, tokenizing each string once rather than per comparison.
+pub fn sort_strings<T: AsRef<str>>(strs: &mut [T]) {
+    strs.sort_by_cached_key(|s| sort_key(s.as_ref()));
+}
+
 impl<'a> CharacterTokenizer<'a> {
     pub fn new(source: Chars<'a>) -> CharacterTokenizer<'a> {
         CharacterTokenizer {
@@ -327,7 +414,41 @@ mod tests {
         string_length(str1)
     }
 
+    #[test]
+    fn sort_strings_sorts_in_alphabet_order() {
+        let mut strs = vec!["pālicca", "x", "bhagavā", "pāli 1", "buddho", "ā", "a"];
+
+        sort_strings(&mut strs);
+
+        assert_eq!(
+            strs,
+            vec!["x", "a", "ā", "pāli 1", "pālicca", "buddho", "bhagavā"]
+        );
+    }
+
+    #[test_case("paññā" => "panna")]
+    #[test_case("Saṅgha" => "sangha")]
+    #[test_case("aṭṭhakathā" => "atthakatha")]
+    #[test_case("saṁyutta" => "samyutta"; "m dot above")]
+    #[test_case("pan\u{303}n\u{303}a\u{304}" => "panna"; "combining marks")]
+    #[test_case("ḷ 1, x" => "l 1, x")]
+    fn fold_diacritics_tests(str1: &str) -> String {
+        fold_diacritics(str1)
+    }
+
     proptest! {
+        #[test]
+        fn fold_diacritics_keeps_length(str1 in "[a-zāīūṅñṭḍṇḷṃ 1x]{0,8}") {
+            prop_assert_eq!(fold_diacritics(&str1).chars().count(), str1.chars().count());
+        }
+
+        #[test]
+        fn sort_key_orders_as_string_compare(str1 in "[a-zāīūṅñṭḍṇḷṃ 1x]{0,8}", str2 in "[a-zāīūṅñṭḍṇḷṃ 1x]{0,8}") {
+            let cmp_key = sort_key(&str1).cmp(&sort_key(&str2)) as isize;
+
+            assert_eq!(cmp_key, string_compare(&str1, &str2));
+        }
+
         #[test]
         fn string_compare_all(i1 in 0usize..PALI_ALPHABET_ROMAN.len(), i2 in 0usize..PALI_ALPHABET_ROMAN.len()) {
             let cmp_str = string_compare(PALI_ALPHABET_ROMAN[i1], PALI_ALPHABET_ROMAN[i2]);
@@ -0,0 +1,275 @@
+use crate::alphabet::{fold_diacritics, sort_key};
+use crate::tokenizer::{normalize_word, tokenize, words, TokenKind};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A spelling of a folded word and the number of times the corpus has it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alternative {
+    pub word: String,
+    pub count: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RestoredWord {
+    pub original: String,
+    pub restored: String,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    /// The spellings the word can have, most likely first. Empty for unknown words, and more
+    /// than one when the word is ambiguous.
+    pub alternatives: Vec<Alternative>,
+}
+
+impl RestoredWord {
+    pub fn is_ambiguous(&self) -> bool {
+        self.alternatives.len() > 1
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Restoration {
+    pub text: String,
+    pub words: Vec<RestoredWord>,
+}
+
+/// Restores the diacritics of words typed without them, e.g. "panna" to paññā, picking the
+/// spelling the corpus has most often.
+pub struct DiacriticRestorer {
+    spellings: HashMap<String, Vec<Alternative>>,
+}
+
+/// The lower case letters of the alphabet with diacritics, by their letter and combining mark.
+const COMPOSITIONS: &[(char, char, char)] = &[
+    ('a', '\u{304}', 'ā'),
+    ('i', '\u{304}', 'ī'),
+    ('u', '\u{304}', 'ū'),
+    ('m', '\u{323}', 'ṃ'),
+    ('t', '\u{323}', 'ṭ'),
+    ('d', '\u{323}', 'ḍ'),
+    ('n', '\u{323}', 'ṇ'),
+    ('l', '\u{323}', 'ḷ'),
+    ('n', '\u{307}', 'ṅ'),
+    ('m', '\u{307}', 'ṁ'),
+    ('n', '\u{303}', 'ñ'),
+];
+
+/// The word in NFC as far as the alphabet goes, e.g. a followed by a combining macron as ā, so
+/// that its spelling has a char per letter.
+fn compose_letters(word: &str) -> String {
+    let mut composed = String::with_capacity(word.len());
+    let mut pending: Option<char> = None;
+    for c in word.chars() {
+        let letter = pending.and_then(|p| {
+            COMPOSITIONS
+                .iter()
+                .find(|(base, mark, _)| *base == p && *mark == c)
+                .map(|(_, _, letter)| *letter)
+        });
+        if letter.is_none() {
+            composed.extend(pending);
+        }
+        pending = Some(letter.unwrap_or(c));
+    }
+    composed.extend(pending);
+
+    composed
+}
+
+/// Adds the words of the text to the counts, e.g. to build the frequencies of a corpus.
+pub fn count_words(text: &str, counts: &mut HashMap<String, u64>) {
+    for word in words(text) {
+        let word = normalize_word(word.text);
+        if !word.is_empty() {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+}
+
+impl DiacriticRestorer {
+    /// The inventory is the words known to be spelled right, e.g. _all_words. Words of the corpus
+    /// that are not in it are spellings too.
+    pub fn new<I, S>(inventory: I, frequencies: &HashMap<String, u64>) -> DiacriticRestorer
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for (word, count) in frequencies {
+            *counts.entry(compose_letters(word)).or_insert(0) += count;
+        }
+        for word in inventory {
+            counts
+                .entry(compose_letters(&normalize_word(word.as_ref())))
+                .or_insert(0);
+        }
+
+        let mut spellings: HashMap<String, Vec<Alternative>> = HashMap::new();
+        for (word, count) in counts {
+            if word.is_empty() {
+                continue;
+            }
+            spellings
+                .entry(fold_diacritics(&word))
+                .or_default()
+                .push(Alternative { word, count });
+        }
+        for alternatives in spellings.values_mut() {
+            alternatives.sort_by_cached_key(|a| (std::cmp::Reverse(a.count), sort_key(&a.word)));
+        }
+
+        DiacriticRestorer { spellings }
+    }
+
+    /// The spellings of the word, most likely first. Ties go to the spelling with fewer
+    /// diacritics, as it sorts first.
+    pub fn spellings(&self, word: &str) -> &[Alternative] {
+        self.spellings
+            .get(&fold_diacritics(&normalize_word(word)))
+            .map_or(&[], |a| a.as_slice())
+    }
+
+    /// Restores each word of the text, keeping everything else as is. Words that already have
+    /// diacritics and unknown words are left alone.
+    pub fn restore(&self, text: &str) -> Restoration {
+        let mut restored_text = String::with_capacity(text.len());
+        let mut restored_words = Vec::new();
+        for token in tokenize(text) {
+            if !matches!(token.kind, TokenKind::Word | TokenKind::Elided) {
+                restored_text.push_str(token.text);
+                continue;
+            }
+
+            let has_diacritics = fold_diacritics(token.text) != token.text.to_lowercase();
+            let alternatives = if has_diacritics {
+                Vec::new()
+            } else {
+                self.spellings(token.text).to_vec()
+            };
+            let restored = match alternatives.first() {
+                Some(a) => apply_spelling(token.text, &a.word),
+                None => token.text.to_string(),
+            };
+
+            restored_text.push_str(&restored);
+            restored_words.push(RestoredWord {
+                original: token.text.to_string(),
+                restored,
+                byte_start: token.byte_start,
+                byte_end: token.byte_end,
+                alternatives,
+            });
+        }
+
+        Restoration {
+            text: restored_text,
+            words: restored_words,
+        }
+    }
+}
+
+/// The spelling with the apostrophes and the capital of the original.
+fn apply_spelling(original: &str, spelling: &str) -> String {
+    let mut restored = String::with_capacity(spelling.len() + 3);
+    let mut spelling_chars = spelling.chars();
+    let mut capitalize = original.chars().next().is_some_and(char::is_uppercase);
+    for c in original.chars() {
+        if matches!(c, '\'' | '’') {
+            restored.push(c);
+            continue;
+        }
+        // The spelling has a letter for every letter of the original, as its letters are composed
+        // and folding keeps the lengths of composed letters.
+        if let Some(s) = spelling_chars.next() {
+            if capitalize {
+                restored.extend(s.to_uppercase());
+                capitalize = false;
+            } else {
+                restored.push(s);
+            }
+        }
+    }
+    restored.extend(spelling_chars);
+
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn create_restorer() -> DiacriticRestorer {
+        let mut frequencies = HashMap::new();
+        count_words(
+            "saññā saññā sannā sammā sammā sammā samma. Ñāṇa ñāṇa nānā’ti paññā ca",
+            &mut frequencies,
+        );
+
+        DiacriticRestorer::new(
+            vec!["sanna", "saññā", "nānā", "ñāṇa", "nāna", "ti", "saṃsāra"],
+            &frequencies,
+        )
+    }
+
+    #[test_case("sanna" => "saññā:2 sannā:1 sanna:0")]
+    #[test_case("Samma" => "sammā:3 samma:1")]
+    #[test_case("nana" => "ñāṇa:2 nānā:1 nāna:0")]
+    #[test_case("samsara" => "saṃsāra:0")]
+    #[test_case("buddho" => "")]
+    fn spellings_tests(word: &str) -> String {
+        create_restorer()
+            .spellings(word)
+            .iter()
+            .map(|a| format!("{}:{}", a.word, a.count))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test_case("Sanna ca panna, nana’ti." => "Saññā ca paññā, ñāṇa’ti.")]
+    #[test_case("Samsara – samma" => "Saṃsāra – sammā")]
+    #[test_case("saṃsara buddho 12" => "saṃsara buddho 12"; "diacritics and unknown words kept")]
+    fn restore_text_tests(text: &str) -> String {
+        create_restorer().restore(text).text
+    }
+
+    #[test]
+    fn restore_words_test() {
+        let restoration = create_restorer().restore("Nana buddho");
+
+        insta::assert_yaml_snapshot!(restoration);
+        assert!(restoration.words[0].is_ambiguous());
+        assert!(!restoration.words[1].is_ambiguous());
+    }
+
+    #[test]
+    fn decomposed_inventory_words_are_composed() {
+        let mut frequencies = HashMap::new();
+        count_words("paññā", &mut frequencies);
+        let restorer = DiacriticRestorer::new(vec!["pan\u{303}n\u{303}a\u{304}"], &frequencies);
+
+        assert_eq!(restorer.restore("Panna'").text, "Paññā'");
+        assert_eq!(
+            restorer.spellings("panna"),
+            &[Alternative {
+                word: "paññā".to_string(),
+                count: 1
+            }]
+        );
+    }
+
+    #[test_case("pan\u{303}n\u{303}a\u{304}" => "paññā")]
+    #[test_case("san\u{307}gha" => "saṅgha")]
+    #[test_case("a\u{304}\u{304}" => "ā\u{304}"; "one mark per letter")]
+    #[test_case("\u{304}a" => "\u{304}a"; "leading mark")]
+    fn compose_letters_tests(word: &str) -> String {
+        compose_letters(word)
+    }
+
+    #[test_case("Sanna", "saññā" => "Saññā")]
+    #[test_case("’ti", "ti" => "’ti")]
+    #[test_case("samma'", "sammā" => "sammā'")]
+    fn apply_spelling_tests(original: &str, spelling: &str) -> String {
+        apply_spelling(original, spelling)
+    }
+}
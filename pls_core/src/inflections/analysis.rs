@@ -0,0 +1,159 @@
+use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::pmd::{get_stem_for_indeclinable, Pali1Metadata};
+use crate::inflections::table_data::generate_inflection_table_data;
+use crate::spelling::SpellingSuggester;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A head word an inflected word can come from, along with the table cells it occurs in.
+#[derive(Debug, Serialize)]
+pub struct WordAnalysis {
+    pub metadata: Pali1Metadata,
+    pub parameters: Vec<BTreeMap<String, String>>,
+}
+
+/// Looks up the head words that generate the given word. This needs the _all_words table that
+/// all_words_gen adds to inflections.db.
+pub fn analyze_word(
+    word: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<WordAnalysis>, String> {
+    let word = word.trim();
+    let mut analyses = Vec::new();
+    for pali1 in get_pali1s_for_word(word, host)? {
+        let table_data = generate_inflection_table_data(&pali1, host)?;
+        let parameters = table_data
+            .cells
+            .into_iter()
+            .filter(|c| c.inflections.iter().any(|i| i == word))
+            .map(|c| c.parameters)
+            .collect();
+
+        analyses.push(WordAnalysis {
+            metadata: table_data.metadata,
+            parameters,
+        });
+    }
+
+    Ok(analyses)
+}
+
+/// A correction for a misspelled word, with the head words that generate it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SpellingSuggestion {
+    pub word: String,
+    pub cost: u32,
+    pub pali1s: Vec<String>,
+}
+
+/// A suggester over the words of load_word_inventory. Load it once, it holds every inflected
+/// form.
+pub fn create_spelling_suggester(
+    host: &dyn PlsInflectionsHost,
+) -> Result<SpellingSuggester, String> {
+    Ok(SpellingSuggester::new(load_word_inventory(host)?))
+}
+
+/// The words in _all_words and the indeclinables, sorted and without duplicates.
+pub fn load_word_inventory(host: &dyn PlsInflectionsHost) -> Result<Vec<String>, String> {
+    let inflections = host
+        .exec_sql_query("SELECT DISTINCT inflection FROM _all_words")?
+        .into_iter()
+        .flatten()
+        .flatten();
+    let indeclinables = host
+        .exec_sql_query("SELECT pāli1 FROM _stems WHERE stem IN ('!', '-')")?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|pali1| get_stem_for_indeclinable(&pali1))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut words: Vec<String> = inflections.chain(indeclinables).collect();
+    words.sort();
+    words.dedup();
+    Ok(words)
+}
+
+/// The suggester's suggestions for the word, each with its head words.
+pub fn suggest_spellings(
+    word: &str,
+    suggester: &SpellingSuggester,
+    max_cost: u32,
+    max_results: usize,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<SpellingSuggestion>, String> {
+    suggester
+        .suggest(&word.trim().to_lowercase(), max_cost, max_results)
+        .into_iter()
+        .map(|s| {
+            Ok(SpellingSuggestion {
+                pali1s: get_pali1s_for_word(&s.word, host)?,
+                word: s.word,
+                cost: s.cost,
+            })
+        })
+        .collect()
+}
+
+fn get_pali1s_for_word(word: &str, host: &dyn PlsInflectionsHost) -> Result<Vec<String>, String> {
+    let sql = format!(
+        r#"
+        SELECT s.pāli1 FROM _all_words w JOIN _stems s ON s.rowid = w.stem_id WHERE w.inflection = '{0}'
+        UNION
+        SELECT pāli1 FROM _stems WHERE (pāli1 = '{0}' OR pāli1 LIKE '{0} %') AND stem IN ('!', '-')
+        "#,
+        word.replace("'", "''")
+    );
+
+    Ok(host
+        .exec_sql_query(&sql)?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflections::test_host;
+
+    const WORDS_DB: &str = r#"
+        CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+        CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, stem_id INTEGER NOT NULL);
+        INSERT INTO _stems VALUES
+            ('dhamma 1', 'dhamm', 'a masc', 'masc', 'nature'),
+            ('kamma 1', 'kamm', 'a nt', 'nt', 'action'),
+            ('ca', '-', '', 'ind', 'and'),
+            ('pi 1', '-', '', 'ind', 'also'),
+            ('dhammā 2', '!', 'dhamma 1', 'masc', 'natures');
+        INSERT INTO _all_words (inflection, stem_id) VALUES
+            ('dhammo', 1), ('dhammā', 1), ('dhammaṃ', 1), ('kammaṃ', 2), ('dhammaṃ', 2);
+    "#;
+
+    #[test]
+    fn suggest_spellings_test() {
+        let host = test_host::DbHost::new(WORDS_DB);
+        let suggester = create_spelling_suggester(&host).unwrap();
+
+        let suggestions = suggest_spellings(" Dhamā", &suggester, 20, 2, &host).unwrap();
+
+        assert_eq!(suggester.len(), 6);
+        assert_eq!(
+            suggestions,
+            vec![
+                SpellingSuggestion {
+                    word: "dhammā".to_string(),
+                    cost: 2,
+                    pali1s: vec!["dhamma 1".to_string(), "dhammā 2".to_string()],
+                },
+                SpellingSuggestion {
+                    word: "dhammo".to_string(),
+                    cost: 12,
+                    pali1s: vec!["dhamma 1".to_string()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::inflections::host::{block_on, PlsInflectionsHost, PlsInflectionsHostAsync, SyncHost};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// The transliterations kept per host before they are dropped wholesale.
+const TRANSLITERATION_CACHE_CAPACITY: usize = 100_000;
+
+lazy_static! {
+    static ref HOST_CACHES: Mutex<HashMap<String, HostCache>> = Mutex::new(HashMap::new());
+}
+
+/// What is cached for one host, i.e. one inflections.db. Query results are the same in every
+/// locale; the rest is per locale.
+#[derive(Default)]
+struct HostCache {
+    db_version: Option<String>,
+    queries: HashMap<String, Vec<Vec<Vec<String>>>>,
+    abbreviations: HashMap<String, HashMap<String, String>>,
+    transliterations: HashMap<(String, String), String>,
+    tick: u64,
+    tables: HashMap<String, (u64, String)>,
+}
+
+/// Drops everything cached for every host.
+pub fn clear() {
+    lock().clear();
+}
+
+/// Drops everything cached for the host if its inflections.db is not the one the cache was filled
+/// from. Returns whether it did.
+pub fn invalidate_if_db_version_changed(host: &dyn PlsInflectionsHost) -> Result<bool, String> {
+    block_on(invalidate_if_db_version_changed_async(&SyncHost(host)))
+}
+
+pub async fn invalidate_if_db_version_changed_async(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<bool, String> {
+    let cache_id = match host.get_cache_id() {
+        Some(id) => id,
+        None => return Ok(false),
+    };
+
+    let db_version = host
+        .exec_sql_query_async("select commit_id from _version")
+        .await?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .next();
+
+    let mut caches = lock();
+    let hc = caches.entry(cache_id.to_string()).or_default();
+    if hc.db_version.is_none() || hc.db_version == db_version {
+        hc.db_version = db_version;
+        return Ok(false);
+    }
+
+    host.log_debug(&format!(
+        "inflections.db '{}' changed from {:?} to {:?}, dropped its cached lookups.",
+        cache_id, hc.db_version, db_version
+    ));
+    *hc = HostCache {
+        db_version,
+        ..HostCache::default()
+    };
+    Ok(true)
+}
+
+/// For queries whose results are the same for every word, e.g. the parameter values and _index.
+pub(crate) async fn exec_sql_query(
+    sql: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Vec<Vec<Vec<String>>>, String> {
+    let cache_id = match host.get_cache_id() {
+        Some(id) => id,
+        None => return host.exec_sql_query_async(sql).await,
+    };
+
+    if let Some(result) = lock()
+        .get(cache_id)
+        .and_then(|hc| hc.queries.get(sql).cloned())
+    {
+        return Ok(result);
+    }
+
+    let result = host.exec_sql_query_async(sql).await?;
+    lock()
+        .entry(cache_id.to_string())
+        .or_default()
+        .queries
+        .insert(sql.to_string(), result.clone());
+
+    Ok(result)
+}
+
+pub(crate) fn transliterate(
+    s: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<String, String> {
+    let cache_id = match host.get_cache_id() {
+        Some(id) => id,
+        None => return host.transliterate(s),
+    };
+
+    let key = (host.get_locale().to_string(), s.to_string());
+    if let Some(t) = lock()
+        .get(cache_id)
+        .and_then(|hc| hc.transliterations.get(&key).cloned())
+    {
+        return Ok(t);
+    }
+
+    let t = host.transliterate(s)?;
+    let mut caches = lock();
+    let transliterations = &mut caches
+        .entry(cache_id.to_string())
+        .or_default()
+        .transliterations;
+    if transliterations.len() >= TRANSLITERATION_CACHE_CAPACITY {
+        transliterations.clear();
+    }
+    transliterations.insert(key, t.clone());
+
+    Ok(t)
+}
+
+pub(crate) fn get_abbreviations(
+    host: &impl PlsInflectionsHostAsync,
+) -> Option<HashMap<String, String>> {
+    lock()
+        .get(host.get_cache_id()?)?
+        .abbreviations
+        .get(host.get_locale())
+        .cloned()
+}
+
+pub(crate) fn insert_abbreviations(
+    host: &impl PlsInflectionsHostAsync,
+    abbreviations: &HashMap<String, String>,
+) {
+    if let Some(cache_id) = host.get_cache_id() {
+        lock()
+            .entry(cache_id.to_string())
+            .or_default()
+            .abbreviations
+            .insert(host.get_locale().to_string(), abbreviations.clone());
+    }
+}
+
+pub(crate) fn get_table(key: &str, host: &impl PlsInflectionsHostAsync) -> Option<String> {
+    let mut caches = lock();
+    let hc = caches.get_mut(host.get_cache_id()?)?;
+    if host.get_table_cache_capacity() == 0 {
+        hc.tables.clear();
+        return None;
+    }
+    hc.tick += 1;
+    let tick = hc.tick;
+    hc.tables
+        .get_mut(&get_table_key(key, host))
+        .map(|(last_used, table)| {
+            *last_used = tick;
+            table.clone()
+        })
+}
+
+pub(crate) fn insert_table(key: &str, table: &str, host: &impl PlsInflectionsHostAsync) {
+    let capacity = host.get_table_cache_capacity();
+    let cache_id = match host.get_cache_id() {
+        Some(id) if capacity > 0 => id,
+        _ => return,
+    };
+
+    let key = get_table_key(key, host);
+    let mut caches = lock();
+    let hc = caches.entry(cache_id.to_string()).or_default();
+    // The host may have lowered its capacity since the tables were inserted.
+    while hc.tables.len() >= capacity && !hc.tables.contains_key(&key) {
+        let lru_key = hc
+            .tables
+            .iter()
+            .min_by_key(|(_, (last_used, _))| *last_used)
+            .map(|(k, _)| k.clone());
+        match lru_key {
+            Some(lru_key) => hc.tables.remove(&lru_key),
+            None => break,
+        };
+    }
+
+    hc.tick += 1;
+    let tick = hc.tick;
+    hc.tables.insert(key, (tick, table.to_string()));
+}
+
+/// The tables carry the host's url and version, so a host upgraded in place does not serve the
+/// tables of its previous version.
+fn get_table_key(key: &str, host: &impl PlsInflectionsHostAsync) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        host.get_url(),
+        host.get_version(),
+        host.get_locale(),
+        key
+    )
+}
+
+fn lock() -> MutexGuard<'static, HashMap<String, HostCache>> {
+    // Entries are inserted whole, so the map is consistent even if a holder of the lock panicked.
+    HOST_CACHES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflections::test_host::DbHost;
+
+    const CACHE_DB: &str = r#"
+        CREATE TABLE _version (commit_id TEXT, date TEXT, repository TEXT);
+        CREATE TABLE _case_values (name TEXT);
+        INSERT INTO _version VALUES ('c1', '2021-01-01', 'test');
+        INSERT INTO _case_values VALUES ('nom'), ('acc');
+    "#;
+
+    fn create_host(cache_id: Option<&str>) -> DbHost<'_> {
+        let mut host = DbHost::new(CACHE_DB);
+        host.cache_id = cache_id;
+        host.psuedo_transliterate = true;
+        host
+    }
+
+    #[test]
+    fn queries_and_transliterations_are_cached_per_host() {
+        let host = create_host(Some("queries_and_transliterations_are_cached_per_host"));
+        let sync_host = SyncHost(&host);
+
+        for _ in 0..3 {
+            assert_eq!(
+                block_on(exec_sql_query("select * from _case_values", &sync_host)),
+                Ok(vec![vec![vec!["nom".to_string()], vec!["acc".to_string()]]])
+            );
+            assert_eq!(
+                transliterate("dhamma", &sync_host),
+                Ok("^dhamma$".to_string())
+            );
+        }
+
+        assert_eq!(host.queries.get(), 1);
+        assert_eq!(host.transliterations.get(), 1);
+    }
+
+    #[test]
+    fn hosts_without_cache_id_are_not_cached() {
+        let host = create_host(None);
+        let sync_host = SyncHost(&host);
+
+        for _ in 0..3 {
+            let _ = block_on(exec_sql_query("select * from _case_values", &sync_host));
+            let _ = transliterate("dhamma", &sync_host);
+        }
+
+        assert_eq!(host.queries.get(), 3);
+        assert_eq!(host.transliterations.get(), 3);
+    }
+
+    #[test]
+    fn abbreviations_are_cached_per_locale() {
+        let mut host = create_host(Some("abbreviations_are_cached_per_locale"));
+        let abbreviations: HashMap<String, String> = vec![("pl".to_string(), "බහු".to_string())]
+            .into_iter()
+            .collect();
+
+        host.locale = "si";
+        insert_abbreviations(&SyncHost(&host), &abbreviations);
+        let si_abbreviations = get_abbreviations(&SyncHost(&host));
+        host.locale = "en";
+        let en_abbreviations = get_abbreviations(&SyncHost(&host));
+
+        assert_eq!(si_abbreviations, Some(abbreviations));
+        assert_eq!(en_abbreviations, None);
+    }
+
+    #[test]
+    fn tables_are_least_recently_used() {
+        let mut host = create_host(Some("tables_are_least_recently_used"));
+        host.table_cache_capacity = 2;
+
+        insert_table("a 1", "<a/>", &SyncHost(&host));
+        insert_table("b 1", "<b/>", &SyncHost(&host));
+        let a = get_table("a 1", &SyncHost(&host));
+        insert_table("c 1", "<c/>", &SyncHost(&host));
+
+        assert_eq!(a, Some("<a/>".to_string()));
+        assert_eq!(get_table("b 1", &SyncHost(&host)), None);
+        assert_eq!(get_table("c 1", &SyncHost(&host)), Some("<c/>".to_string()));
+        host.table_cache_capacity = 0;
+        assert_eq!(get_table("c 1", &SyncHost(&host)), None);
+    }
+
+    #[test]
+    fn tables_are_not_cached_without_capacity() {
+        let host = create_host(Some("tables_are_not_cached_without_capacity"));
+
+        insert_table("a 1", "<a/>", &SyncHost(&host));
+
+        assert_eq!(get_table("a 1", &SyncHost(&host)), None);
+    }
+
+    #[test]
+    fn lowered_capacity_drops_least_recently_used_tables() {
+        let mut host = create_host(Some("lowered_capacity_drops_least_recently_used_tables"));
+        host.table_cache_capacity = 3;
+        insert_table("a 1", "<a/>", &SyncHost(&host));
+        insert_table("b 1", "<b/>", &SyncHost(&host));
+        insert_table("c 1", "<c/>", &SyncHost(&host));
+
+        host.table_cache_capacity = 1;
+        insert_table("d 1", "<d/>", &SyncHost(&host));
+
+        assert_eq!(get_table("c 1", &SyncHost(&host)), None);
+        assert_eq!(get_table("d 1", &SyncHost(&host)), Some("<d/>".to_string()));
+    }
+
+    #[test]
+    fn table_keys_are_per_url_version_and_locale() {
+        let mut host = create_host(Some("table_keys_are_per_url_version_and_locale"));
+        let key = get_table_key("a 1", &SyncHost(&host));
+
+        host.version = "v0.2";
+        let version_key = get_table_key("a 1", &SyncHost(&host));
+        host.url = "other test case";
+        let url_key = get_table_key("a 1", &SyncHost(&host));
+        host.locale = "si";
+        let locale_key = get_table_key("a 1", &SyncHost(&host));
+
+        assert_eq!(key, "test case|v0.1|en|a 1");
+        assert_eq!(version_key, "test case|v0.2|en|a 1");
+        assert_eq!(url_key, "other test case|v0.2|en|a 1");
+        assert_eq!(locale_key, "other test case|v0.2|si|a 1");
+    }
+
+    #[test]
+    fn db_version_change_invalidates_cache() {
+        let host = create_host(Some("db_version_change_invalidates_cache"));
+        let sync_host = SyncHost(&host);
+
+        assert_eq!(invalidate_if_db_version_changed(&host), Ok(false));
+        let _ = block_on(exec_sql_query("select * from _case_values", &sync_host));
+        assert_eq!(invalidate_if_db_version_changed(&host), Ok(false));
+        let _ = block_on(exec_sql_query("select * from _case_values", &sync_host));
+        assert_eq!(host.queries.get(), 3);
+
+        host.conn
+            .execute_batch("UPDATE _version SET commit_id = 'c2'")
+            .unwrap();
+        assert_eq!(invalidate_if_db_version_changed(&host), Ok(true));
+        let _ = block_on(exec_sql_query("select * from _case_values", &sync_host));
+        assert_eq!(host.queries.get(), 5);
+    }
+}
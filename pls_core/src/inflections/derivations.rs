@@ -0,0 +1,418 @@
+use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::pmd::{get_pali1_metadata, InflectionClass, WordType};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum VerbDerivationKind {
+    PresentParticiple,
+    PastParticiple,
+    Gerundive,
+    Absolutive,
+    Infinitive,
+    Causative,
+    Passive,
+}
+
+impl VerbDerivationKind {
+    pub const ALL: [VerbDerivationKind; 7] = [
+        VerbDerivationKind::PresentParticiple,
+        VerbDerivationKind::PastParticiple,
+        VerbDerivationKind::Gerundive,
+        VerbDerivationKind::Absolutive,
+        VerbDerivationKind::Infinitive,
+        VerbDerivationKind::Causative,
+        VerbDerivationKind::Passive,
+    ];
+
+    /// The _abbreviations key used to label the derivation.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            VerbDerivationKind::PresentParticiple => "prp",
+            VerbDerivationKind::PastParticiple => "pp",
+            VerbDerivationKind::Gerundive => "ptp",
+            VerbDerivationKind::Absolutive => "abs",
+            VerbDerivationKind::Infinitive => "inf",
+            VerbDerivationKind::Causative => "caus",
+            VerbDerivationKind::Passive => "pass",
+        }
+    }
+}
+
+/// A form derived from a verb, e.g. the present participle 'bhavant' of 'bhavati'. Declinable and
+/// conjugable forms carry the pattern that inflects them, provided inflections.db has it.
+#[derive(Debug, Serialize)]
+pub struct VerbDerivation {
+    pub kind: VerbDerivationKind,
+    pub word: String,
+    pub stem: String,
+    pub pattern: Option<String>,
+    pub inflection_class: Option<InflectionClass>,
+    /// Whether inflections.db has the word as a head word or an inflected form. The rest are only
+    /// formed by the rules and may not occur, e.g. gacchita where the texts have gata.
+    pub attested: bool,
+}
+
+/// How the regular derivations are formed for each class of present stem.
+struct DerivationRules {
+    present_ending: &'static str,
+    /// Joins the root to -tvā, -tuṃ and -tabba, e.g. bhav-i-tvā, ābādh-e-tvā.
+    connecting_vowel: &'static str,
+    participle_endings: &'static [&'static str],
+    /// The -ita, -iya and -īyati forms hold for the root only in the ati and eti classes, e.g.
+    /// not for pāpuṇoti or jānāti.
+    past_participle_endings: &'static [&'static str],
+    root_absolutive_endings: &'static [&'static str],
+    causative_endings: &'static [&'static str],
+    passive_endings: &'static [&'static str],
+}
+
+const DERIVATION_RULES: &[DerivationRules] = &[
+    DerivationRules {
+        present_ending: "ati",
+        connecting_vowel: "i",
+        participle_endings: &["ant", "amāna"],
+        past_participle_endings: &["ita"],
+        root_absolutive_endings: &["iya"],
+        causative_endings: &["eti", "āpeti"],
+        passive_endings: &["īyati"],
+    },
+    DerivationRules {
+        present_ending: "eti",
+        connecting_vowel: "e",
+        participle_endings: &["ent", "ayamāna"],
+        past_participle_endings: &["ita"],
+        root_absolutive_endings: &["iya"],
+        causative_endings: &["āpeti"],
+        passive_endings: &["īyati"],
+    },
+    DerivationRules {
+        present_ending: "oti",
+        connecting_vowel: "i",
+        participle_endings: &["ant", "amāna"],
+        past_participle_endings: &[],
+        root_absolutive_endings: &[],
+        causative_endings: &["āpeti"],
+        passive_endings: &[],
+    },
+    DerivationRules {
+        present_ending: "āti",
+        connecting_vowel: "i",
+        participle_endings: &["ant", "amāna"],
+        past_participle_endings: &[],
+        root_absolutive_endings: &[],
+        causative_endings: &["āpeti"],
+        passive_endings: &[],
+    },
+];
+
+/// Forms the regular participles, gerundives, absolutives, infinitives, causatives and passives
+/// of a verb in the present tense. Words that are not regular present tense verbs have none.
+pub fn generate_verb_derivations(
+    pali1: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<VerbDerivation>, String> {
+    let pm = get_pali1_metadata(pali1, host)?;
+    let (stem, pattern) = match &pm.word_type {
+        WordType::Declinable {
+            stem,
+            pattern,
+            inflection_class: InflectionClass::Conjugation,
+        } => (stem, pattern),
+        _ => return Ok(vec![]),
+    };
+
+    let words = derive_words(stem, pattern);
+    let inflection_classes = get_pattern_inflection_classes(&words, host)?;
+    let attested_words = get_attested_words(&words, host)?;
+    let derivations = words
+        .into_iter()
+        .map(|(kind, word)| {
+            let attested = attested_words.contains(&word);
+            let found = get_candidate_pattern(kind, &word).and_then(|(ending, pattern)| {
+                let inflection_class = inflection_classes.get(&pattern)?;
+                Some((ending, pattern, inflection_class))
+            });
+
+            match found {
+                Some((ending, pattern, inflection_class)) => VerbDerivation {
+                    kind,
+                    stem: word[..word.len() - ending.len()].to_string(),
+                    word,
+                    pattern: Some(pattern),
+                    inflection_class: InflectionClass::from_str(inflection_class).ok(),
+                    attested,
+                },
+                None => VerbDerivation {
+                    kind,
+                    stem: word.clone(),
+                    word,
+                    pattern: None,
+                    inflection_class: None,
+                    attested,
+                },
+            }
+        })
+        .collect();
+
+    Ok(derivations)
+}
+
+fn derive_words(stem: &str, pattern: &str) -> Vec<(VerbDerivationKind, String)> {
+    let rules = match pattern
+        .strip_suffix(" pr")
+        .and_then(|e| DERIVATION_RULES.iter().find(|r| r.present_ending == e))
+    {
+        Some(rules) => rules,
+        None => return vec![],
+    };
+
+    let cv = rules.connecting_vowel;
+    let regular = |suffix: &str| format!("{}{}", stem, suffix);
+    let mut words = Vec::new();
+    let mut add = |kind, word: String| words.push((kind, word));
+
+    for e in rules.participle_endings {
+        add(VerbDerivationKind::PresentParticiple, regular(e));
+    }
+    for e in rules.past_participle_endings {
+        add(VerbDerivationKind::PastParticiple, regular(e));
+    }
+    add(
+        VerbDerivationKind::Gerundive,
+        regular(&format!("{}tabba", cv)),
+    );
+    add(VerbDerivationKind::Gerundive, regular("anīya"));
+    add(
+        VerbDerivationKind::Absolutive,
+        regular(&format!("{}tvā", cv)),
+    );
+    add(
+        VerbDerivationKind::Absolutive,
+        regular(&format!("{}tvāna", cv)),
+    );
+    for e in rules.root_absolutive_endings {
+        add(VerbDerivationKind::Absolutive, regular(e));
+    }
+    add(
+        VerbDerivationKind::Infinitive,
+        regular(&format!("{}tuṃ", cv)),
+    );
+    for e in rules.causative_endings {
+        let word = match *e {
+            "eti" => format!("{}{}", lengthen_root_vowel(stem), e),
+            _ => regular(e),
+        };
+        add(VerbDerivationKind::Causative, word);
+    }
+    for e in rules.passive_endings {
+        add(VerbDerivationKind::Passive, get_passive_word(stem, e));
+    }
+
+    words
+}
+
+/// The causative in -eti lengthens the a of a light root syllable, e.g. bhav-eti to bhāveti,
+/// pac-eti to pāceti. Heavy syllables stay, e.g. gaccheti.
+fn lengthen_root_vowel(stem: &str) -> String {
+    let chars: Vec<char> = stem.chars().collect();
+    let is_aspirate = |i: usize| chars[i] == 'h' && i > 0 && "kgcjṭḍtdpb".contains(chars[i - 1]);
+    let consonant_len = match chars.len() {
+        0 => return stem.to_string(),
+        len if is_aspirate(len - 1) => 2,
+        _ => 1,
+    };
+
+    match chars.len().checked_sub(consonant_len + 1) {
+        Some(i) if chars[i] == 'a' && !"aāiīuūeoṃ".contains(chars[chars.len() - 1]) => {
+            let mut lengthened = chars;
+            lengthened[i] = 'ā';
+            lengthened.into_iter().collect()
+        }
+        _ => stem.to_string(),
+    }
+}
+
+/// The passive in -īyati takes the root without its guṇa, e.g. bhav to bhūyati, jay to jīyati.
+fn get_passive_word(stem: &str, ending: &str) -> String {
+    let ya_ending = ending.strip_prefix('ī').unwrap_or(ending);
+    match (stem.strip_suffix("av"), stem.strip_suffix("ay")) {
+        (Some(root), _) if !root.is_empty() => format!("{}ū{}", root, ya_ending),
+        (_, Some(root)) if !root.is_empty() => format!("{}ī{}", root, ya_ending),
+        _ => format!("{}{}", stem, ending),
+    }
+}
+
+/// The words inflections.db has, as an inflected form in _all_words or as a head word in _stems
+/// with or without its homonym number.
+fn get_attested_words(
+    words: &[(VerbDerivationKind, String)],
+    host: &dyn PlsInflectionsHost,
+) -> Result<HashSet<String>, String> {
+    if words.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let mut quoted: Vec<String> = words
+        .iter()
+        .map(|(_, word)| format!("'{}'", word.replace("'", "''")))
+        .collect();
+    quoted.sort();
+    quoted.dedup();
+    let sql = format!(
+        r#"
+        SELECT inflection FROM _all_words WHERE inflection IN ({0})
+        UNION
+        SELECT rtrim(pāli1, ' 0123456789') AS word FROM _stems WHERE word IN ({0})
+        "#,
+        quoted.join(", ")
+    );
+
+    Ok(host
+        .exec_sql_query(&sql)?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect())
+}
+
+/// The ending and the name of the pattern that would inflect the word. Nominal derivations
+/// inflect like adjectives and verbal ones like present tense verbs, with patterns named after
+/// the ending they replace, e.g. 'ant adj' inflects bhav-ant. Absolutives and infinitives are
+/// indeclinable.
+fn get_candidate_pattern(kind: VerbDerivationKind, word: &str) -> Option<(&'static str, String)> {
+    let (endings, class): (&[&'static str], &str) = match kind {
+        VerbDerivationKind::Absolutive | VerbDerivationKind::Infinitive => return None,
+        VerbDerivationKind::Causative | VerbDerivationKind::Passive => (&["eti", "ati"], "pr"),
+        _ => (&["ant", "ent", "a"], "adj"),
+    };
+
+    endings
+        .iter()
+        .find(|e| word.ends_with(*e))
+        .map(|e| (*e, format!("{} {}", e, class)))
+}
+
+fn get_pattern_inflection_classes(
+    words: &[(VerbDerivationKind, String)],
+    host: &dyn PlsInflectionsHost,
+) -> Result<HashMap<String, String>, String> {
+    let mut patterns: Vec<String> = words
+        .iter()
+        .filter_map(|(kind, word)| get_candidate_pattern(*kind, word))
+        .map(|(_, pattern)| format!("'{}'", pattern))
+        .collect();
+    patterns.sort();
+    patterns.dedup();
+    if patterns.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let sql = format!(
+        "select name, inflection_class from _index where name in ({})",
+        patterns.join(", ")
+    );
+    let inflection_classes = host
+        .exec_sql_query(&sql)?
+        .into_iter()
+        .flatten()
+        .filter_map(|mut row| {
+            let inflection_class = row.pop()?;
+            let name = row.pop()?;
+            Some((name, inflection_class))
+        })
+        .collect();
+
+    Ok(inflection_classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflections::test_host;
+    use test_case::test_case;
+
+    #[test_case("bhav", "ati pr" => vec![
+        "prp bhavant", "prp bhavamāna", "pp bhavita", "ptp bhavitabba", "ptp bhavanīya",
+        "abs bhavitvā", "abs bhavitvāna", "abs bhaviya", "inf bhavituṃ", "caus bhāveti",
+        "caus bhavāpeti", "pass bhūyati"
+    ]; "ati")]
+    #[test_case("gacch", "ati pr" => vec![
+        "prp gacchant", "prp gacchamāna", "pp gacchita", "ptp gacchitabba", "ptp gacchanīya",
+        "abs gacchitvā", "abs gacchitvāna", "abs gacchiya", "inf gacchituṃ", "caus gaccheti",
+        "caus gacchāpeti", "pass gacchīyati"
+    ]; "ati heavy root")]
+    #[test_case("ābādh", "eti pr" => vec![
+        "prp ābādhent", "prp ābādhayamāna", "pp ābādhita", "ptp ābādhetabba", "ptp ābādhanīya",
+        "abs ābādhetvā", "abs ābādhetvāna", "abs ābādhiya", "inf ābādhetuṃ", "caus ābādhāpeti",
+        "pass ābādhīyati"
+    ]; "eti")]
+    #[test_case("pāpuṇ", "oti pr" => vec![
+        "prp pāpuṇant", "prp pāpuṇamāna", "ptp pāpuṇitabba", "ptp pāpuṇanīya", "abs pāpuṇitvā",
+        "abs pāpuṇitvāna", "inf pāpuṇituṃ", "caus pāpuṇāpeti"
+    ]; "oti")]
+    #[test_case("jān", "āti pr" => vec![
+        "prp jānant", "prp jānamāna", "ptp jānitabba", "ptp jānanīya", "abs jānitvā",
+        "abs jānitvāna", "inf jānituṃ", "caus jānāpeti"
+    ]; "āti")]
+    #[test_case("ahos", "i aor" => Vec::<String>::new(); "not present tense")]
+    #[test_case("dad", "āti fut" => Vec::<String>::new(); "not present tense 2")]
+    fn derive_words_tests(stem: &str, pattern: &str) -> Vec<String> {
+        derive_words(stem, pattern)
+            .into_iter()
+            .map(|(k, w)| format!("{} {}", k.abbreviation(), w))
+            .collect()
+    }
+
+    #[test_case("bhav" => "bhāv")]
+    #[test_case("pac" => "pāc")]
+    #[test_case("labh" => "lābh")]
+    #[test_case("gacch" => "gacch"; "heavy syllable")]
+    #[test_case("pucch" => "pucch"; "not a")]
+    #[test_case("" => ""; "empty")]
+    fn lengthen_root_vowel_tests(stem: &str) -> String {
+        lengthen_root_vowel(stem)
+    }
+
+    #[test_case("bhav" => "bhūyati")]
+    #[test_case("jay" => "jīyati")]
+    #[test_case("ābādh" => "ābādhīyati")]
+    #[test_case("av" => "avīyati"; "no root")]
+    fn get_passive_word_tests(stem: &str) -> String {
+        get_passive_word(stem, "īyati")
+    }
+
+    #[test]
+    fn get_attested_words_test() {
+        let host = test_host::DbHost::new(
+            r#"
+            CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+            CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, stem_id INTEGER NOT NULL);
+            INSERT INTO _stems VALUES ('bhavitvā 1', '-', '', 'abs', 'having been');
+            INSERT INTO _all_words (inflection, stem_id) VALUES ('bhāveti', 1), ('bhavanto', 1);
+            "#,
+        );
+        let words = derive_words("bhav", "ati pr");
+
+        let mut attested: Vec<String> = get_attested_words(&words, &host)
+            .unwrap()
+            .into_iter()
+            .collect();
+        attested.sort();
+
+        assert_eq!(attested, vec!["bhavitvā", "bhāveti"]);
+    }
+
+    #[test_case(VerbDerivationKind::PresentParticiple, "bhavant" => Some(("ant", "ant adj".to_string())))]
+    #[test_case(VerbDerivationKind::PresentParticiple, "bhavamāna" => Some(("a", "a adj".to_string())))]
+    #[test_case(VerbDerivationKind::Causative, "bhāveti" => Some(("eti", "eti pr".to_string())))]
+    #[test_case(VerbDerivationKind::Passive, "bhūyati" => Some(("ati", "ati pr".to_string())))]
+    #[test_case(VerbDerivationKind::Infinitive, "bhavituṃ" => None)]
+    fn get_candidate_pattern_tests(
+        kind: VerbDerivationKind,
+        word: &str,
+    ) -> Option<(&'static str, String)> {
+        get_candidate_pattern(kind, word)
+    }
+}
@@ -1,5 +1,7 @@
 use crate::inflections;
-use crate::inflections::{get_table_name_from_pattern, localise_abbrev, PlsInflectionsHost};
+use crate::inflections::cache;
+use crate::inflections::host::PlsInflectionsHostAsync;
+use crate::inflections::{get_table_name_from_pattern, localisation, localise_abbrev};
 use serde::Serialize;
 use std::collections::HashMap;
 use tera::{Context, Tera};
@@ -32,23 +34,21 @@ struct TemplateViewModel<'a> {
     abbrev_map: HashMap<String, String>,
 }
 
-pub fn create_html_body(
+pub async fn create_html_body(
     pattern: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<String, String> {
     let table_name = &get_table_name_from_pattern(pattern);
-    let tense_view_models = create_tense_view_models(table_name, stem, host)?;
-    let abbrev_map = inflections::get_abbreviations_for_locale(host)?;
+    let tense_view_models = create_tense_view_models(table_name, stem, host).await?;
+    let abbrev_map = localisation::get_abbreviations_for_locale_async(host).await?;
     let vm = TemplateViewModel {
         stem,
         view_models: tense_view_models,
         abbrev_map,
     };
     let context = Context::from_serialize(&vm).map_err(|e| e.to_string())?;
-    TEMPLATES
-        .render("conjugation", &context)
-        .map_err(|e| e.to_string())
+    localisation::render(&TEMPLATES, "conjugation", &context, host)
 }
 
 struct ParameterValues {
@@ -58,7 +58,9 @@ struct ParameterValues {
     pub n_values: Vec<String>,
 }
 
-fn query_parameter_values(host: &dyn PlsInflectionsHost) -> Result<ParameterValues, String> {
+async fn query_parameter_values(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<ParameterValues, String> {
     let sql = r#"
         select * from _tense_values where name <> "";
         select * from _person_values where name <> "";
@@ -66,21 +68,21 @@ fn query_parameter_values(host: &dyn PlsInflectionsHost) -> Result<ParameterValu
         select * from _number_values where name <> "" and name <> "dual";
     "#;
 
-    let values = host.exec_sql_query(sql)?;
+    let values = cache::exec_sql_query(sql, host).await?;
     Ok(ParameterValues {
-        t_values: values[0].to_owned().into_iter().flatten().collect(),
-        p_values: values[1].to_owned().into_iter().flatten().collect(),
-        ar_values: values[2].to_owned().into_iter().flatten().collect(),
-        n_values: values[3].to_owned().into_iter().flatten().collect(),
+        t_values: values[0].iter().flatten().cloned().collect(),
+        p_values: values[1].iter().flatten().cloned().collect(),
+        ar_values: values[2].iter().flatten().cloned().collect(),
+        n_values: values[3].iter().flatten().cloned().collect(),
     })
 }
 
-fn create_tense_view_models(
+async fn create_tense_view_models(
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<TenseViewModel>, String> {
-    let pvs = query_parameter_values(host)?;
+    let pvs = query_parameter_values(host).await?;
 
     let mut view_models: Vec<TenseViewModel> = Vec::new();
     for t in &pvs.t_values {
@@ -90,7 +92,9 @@ fn create_tense_view_models(
                 table_name, t
             ),
             host,
-        )? {
+        )
+        .await?
+        {
             continue;
         }
 
@@ -99,7 +103,7 @@ fn create_tense_view_models(
             ar_values_exist.push(!inflections::query_has_no_results(
                 &format!(r#"select cast(count(*) as text) from '{}' where tense = "{}" and actreflx = "{}""#, table_name, t, ar),
                 host,
-            )?);
+            ).await?);
         }
 
         let mut inflections_list: Vec<Vec<String>> = Vec::new();
@@ -110,7 +114,7 @@ fn create_tense_view_models(
                         r#"SELECT inflections FROM '{}' WHERE tense = '{}' AND person = '{}' AND actreflx = '{}' AND "number" = '{}'"#,
                         table_name, t, p, ar, n,
                     );
-                    let inflections = inflections::get_inflections(stem, &sql, host);
+                    let inflections = inflections::get_inflections(stem, &sql, host).await;
                     inflections_list.push(inflections);
                 }
             }
@@ -1,5 +1,7 @@
 use crate::inflections;
-use crate::inflections::{get_table_name_from_pattern, localise_abbrev, PlsInflectionsHost};
+use crate::inflections::cache;
+use crate::inflections::host::PlsInflectionsHostAsync;
+use crate::inflections::{get_table_name_from_pattern, localisation, localise_abbrev};
 use serde::Serialize;
 use std::collections::HashMap;
 use tera::{Context, Tera};
@@ -34,15 +36,16 @@ struct TemplateViewModel<'a> {
     abbrev_map: HashMap<String, String>,
 }
 
-pub fn create_html_body(
+pub async fn create_html_body(
     pattern: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<String, String> {
     let table_name = &get_table_name_from_pattern(pattern);
-    let (view_models, g_values_exist) = create_case_view_models(table_name, stem, host)?;
-    let abbrev_map = inflections::get_abbreviations_for_locale(host)?;
-    let in_comps_inflections = create_template_view_model_for_in_comps(table_name, stem, host);
+    let (view_models, g_values_exist) = create_case_view_models(table_name, stem, host).await?;
+    let abbrev_map = localisation::get_abbreviations_for_locale_async(host).await?;
+    let in_comps_inflections =
+        create_template_view_model_for_in_comps(table_name, stem, host).await;
 
     let template_view_model = TemplateViewModel {
         pattern,
@@ -54,9 +57,7 @@ pub fn create_html_body(
     };
 
     let context = Context::from_serialize(&template_view_model).map_err(|e| e.to_string())?;
-    TEMPLATES
-        .render("declension", &context)
-        .map_err(|e| e.to_string())
+    localisation::render(&TEMPLATES, "declension", &context, host)
 }
 
 struct ParameterValues {
@@ -65,37 +66,42 @@ struct ParameterValues {
     pub n_values: Vec<String>,
 }
 
-fn query_parameter_values(host: &dyn PlsInflectionsHost) -> Result<ParameterValues, String> {
+async fn query_parameter_values(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<ParameterValues, String> {
     let sql = r#"
         select * from _case_values where name <> "";
         select * from _gender_values where name <> "";
         select * from _number_values where name <> "" and name <> "dual";
     "#;
 
-    let values = host.exec_sql_query(sql)?;
+    let values = cache::exec_sql_query(sql, host).await?;
     Ok(ParameterValues {
-        c_values: values[0].to_owned().into_iter().flatten().collect(),
-        g_values: values[1].to_owned().into_iter().flatten().collect(),
-        n_values: values[2].to_owned().into_iter().flatten().collect(),
+        c_values: values[0].iter().flatten().cloned().collect(),
+        g_values: values[1].iter().flatten().cloned().collect(),
+        n_values: values[2].iter().flatten().cloned().collect(),
     })
 }
 
-fn create_case_view_models(
+async fn create_case_view_models(
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<(Vec<CaseViewModel>, Vec<bool>), String> {
-    let pvs = query_parameter_values(host)?;
+    let pvs = query_parameter_values(host).await?;
 
     let mut g_values_exist: Vec<bool> = Vec::new();
     for g in &pvs.g_values {
-        g_values_exist.push(!inflections::query_has_no_results(
-            &format!(
-                r#"select cast(count(*) as text) from '{}' where gender = "{}""#,
-                table_name, g
-            ),
-            host,
-        )?);
+        g_values_exist.push(
+            !inflections::query_has_no_results(
+                &format!(
+                    r#"select cast(count(*) as text) from '{}' where gender = "{}""#,
+                    table_name, g
+                ),
+                host,
+            )
+            .await?,
+        );
     }
 
     let mut view_models: Vec<CaseViewModel> = Vec::new();
@@ -107,7 +113,7 @@ fn create_case_view_models(
                     r#"SELECT inflections FROM '{}' WHERE "case" = '{}' AND gender = '{}' AND "number" = '{}'"#,
                     table_name, c, g, n
                 );
-                let inflections = inflections::get_inflections(stem, &sql, host);
+                let inflections = inflections::get_inflections(stem, &sql, host).await;
                 inflections_list.push(inflections);
             }
         }
@@ -122,15 +128,15 @@ fn create_case_view_models(
     Ok((view_models, g_values_exist))
 }
 
-fn create_template_view_model_for_in_comps(
+async fn create_template_view_model_for_in_comps(
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Vec<String> {
     let sql = format!(
         r#"SELECT inflections FROM '{}' WHERE "case" = '' AND gender = '' AND "number" = ''"#,
         table_name
     );
 
-    inflections::get_inflections(stem, &sql, host)
+    inflections::get_inflections(stem, &sql, host).await
 }
@@ -1,5 +1,7 @@
 use crate::inflections;
-use crate::inflections::{get_table_name_from_pattern, localise_abbrev, PlsInflectionsHost};
+use crate::inflections::cache;
+use crate::inflections::host::PlsInflectionsHostAsync;
+use crate::inflections::{get_table_name_from_pattern, localisation, localise_abbrev};
 use serde::Serialize;
 use std::collections::HashMap;
 use tera::{Context, Tera};
@@ -33,15 +35,16 @@ struct TemplateViewModel<'a> {
     abbrev_map: HashMap<String, String>,
 }
 
-pub fn create_html_body(
+pub async fn create_html_body(
     pattern: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<String, String> {
     let table_name = &get_table_name_from_pattern(pattern);
-    let view_models = create_case_view_models(table_name, stem, host)?;
-    let in_comps_inflections = create_template_view_model_for_in_comps(table_name, stem, host);
-    let abbrev_map = inflections::get_abbreviations_for_locale(host)?;
+    let view_models = create_case_view_models(table_name, stem, host).await?;
+    let in_comps_inflections =
+        create_template_view_model_for_in_comps(table_name, stem, host).await;
+    let abbrev_map = localisation::get_abbreviations_for_locale_async(host).await?;
 
     let vm = TemplateViewModel {
         pattern,
@@ -52,25 +55,23 @@ pub fn create_html_body(
     };
 
     let context = Context::from_serialize(&vm).map_err(|e| e.to_string())?;
-    TEMPLATES
-        .render("declension_pron_dual", &context)
-        .map_err(|e| e.to_string())
+    localisation::render(&TEMPLATES, "declension_pron_dual", &context, host)
 }
 
-fn create_case_view_models(
+async fn create_case_view_models(
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<CaseViewModel>, String> {
     let sql = r#"select * from _case_values where name <> "" and name <> "voc""#;
-    let values = host.exec_sql_query(sql)?;
+    let values = cache::exec_sql_query(sql, host).await?;
     let mut view_models: Vec<CaseViewModel> = Vec::new();
     for case in values[0].iter().flatten() {
         let sql = format!(
             r#"SELECT inflections FROM '{}' WHERE "case" = '{}' AND special_pron_class = 'dual' AND "number" = 'sg'"#,
             table_name, case
         );
-        let inflections = inflections::get_inflections(stem, &sql, host);
+        let inflections = inflections::get_inflections(stem, &sql, host).await;
 
         let view_model = CaseViewModel {
             name: case.to_owned(),
@@ -82,15 +83,15 @@ fn create_case_view_models(
     Ok(view_models)
 }
 
-fn create_template_view_model_for_in_comps(
+async fn create_template_view_model_for_in_comps(
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Vec<String> {
     let sql = format!(
         r#"SELECT inflections FROM '{}' WHERE "case" = '' AND special_pron_class = '' AND "number" = ''"#,
         table_name
     );
 
-    inflections::get_inflections(stem, &sql, host)
+    inflections::get_inflections(stem, &sql, host).await
 }
@@ -1,5 +1,7 @@
 use crate::inflections;
-use crate::inflections::{get_table_name_from_pattern, localise_abbrev, PlsInflectionsHost};
+use crate::inflections::cache;
+use crate::inflections::host::PlsInflectionsHostAsync;
+use crate::inflections::{get_table_name_from_pattern, localisation, localise_abbrev};
 use serde::Serialize;
 use std::collections::HashMap;
 use tera::{Context, Tera};
@@ -34,16 +36,16 @@ struct TemplateViewModel<'a> {
     abbrev_map: HashMap<String, String>,
 }
 
-pub fn create_html_body(
+pub async fn create_html_body(
     pron_type: &str,
     pattern: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<String, String> {
     let table_name = &get_table_name_from_pattern(pattern);
-    let view_models = create_case_view_models(pron_type, table_name, stem, host)?;
+    let view_models = create_case_view_models(pron_type, table_name, stem, host).await?;
     let in_comps_inflections = Vec::new();
-    let abbrev_map = inflections::get_abbreviations_for_locale(host)?;
+    let abbrev_map = localisation::get_abbreviations_for_locale_async(host).await?;
 
     let template_view_model = TemplateViewModel {
         pattern,
@@ -55,22 +57,20 @@ pub fn create_html_body(
     };
 
     let context = Context::from_serialize(&template_view_model).map_err(|e| e.to_string())?;
-    TEMPLATES
-        .render("declension_pron_x", &context)
-        .map_err(|e| e.to_string())
+    localisation::render(&TEMPLATES, "declension_pron_x", &context, host)
 }
 
-fn create_case_view_models(
+async fn create_case_view_models(
     pron_type: &str,
     table_name: &str,
     stem: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<CaseViewModel>, String> {
     let sql = r#"
         select * from _case_values where name <> "" and name <> "voc";
         select * from _number_values where name <> "" and name <> "dual";
     "#;
-    let values = host.exec_sql_query(sql)?;
+    let values = cache::exec_sql_query(sql, host).await?;
     let mut view_models: Vec<CaseViewModel> = Vec::new();
     for case in values[0].iter().flatten() {
         let mut inflections_list: Vec<Vec<String>> = Vec::new();
@@ -79,7 +79,7 @@ fn create_case_view_models(
                 r#"SELECT inflections FROM '{}' WHERE "case" = '{}' AND special_pron_class = '{}' AND "number" = '{}'"#,
                 table_name, case, pron_type, number
             );
-            let inflections = inflections::get_inflections(stem, &sql, host);
+            let inflections = inflections::get_inflections(stem, &sql, host).await;
             inflections_list.push(inflections);
         }
 
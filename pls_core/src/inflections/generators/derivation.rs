@@ -0,0 +1,98 @@
+use crate::inflections::derivations::VerbDerivation;
+use crate::inflections::host::PlsInflectionsHostAsync;
+use crate::inflections::{localisation, localise_abbrev};
+use serde::Serialize;
+use std::collections::HashMap;
+use tera::{Context, Tera};
+
+lazy_static! {
+    static ref TEMPLATES: Tera = {
+        let mut tera = Tera::default();
+        tera.register_filter("localise_abbrev", localise_abbrev);
+        tera.register_filter("localise", localise_abbrev);
+        tera.add_raw_templates(vec![(
+            "derivation",
+            include_str!("templates/derivation.html"),
+        )])
+        .expect("Unexpected failure adding template");
+        tera.autoescape_on(vec!["html"]);
+        tera
+    };
+}
+
+#[derive(Serialize)]
+struct DerivationViewModel {
+    name: &'static str,
+    words: Vec<WordViewModel>,
+}
+
+#[derive(Serialize)]
+struct WordViewModel {
+    word: String,
+    attested: bool,
+}
+
+#[derive(Serialize)]
+struct TableViewModel {
+    word: String,
+    pattern: String,
+    body: String,
+}
+
+#[derive(Serialize)]
+struct TemplateViewModel {
+    view_models: Vec<DerivationViewModel>,
+    tables: Vec<TableViewModel>,
+    has_unattested: bool,
+    abbrev_map: HashMap<String, String>,
+    messages: HashMap<String, String>,
+}
+
+/// Lists the derived forms by kind, marking those inflections.db does not have, followed by the
+/// inflection table of each form that has a pattern.
+pub async fn create_html_body(
+    derivations: &[VerbDerivation],
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<String, String> {
+    let mut view_models: Vec<DerivationViewModel> = Vec::new();
+    let mut tables: Vec<TableViewModel> = Vec::new();
+    for d in derivations {
+        let word = host.transliterate(&d.word)?;
+        let word_vm = WordViewModel {
+            word: word.clone(),
+            attested: d.attested,
+        };
+        match view_models.last_mut() {
+            Some(vm) if vm.name == d.kind.abbreviation() => vm.words.push(word_vm),
+            _ => view_models.push(DerivationViewModel {
+                name: d.kind.abbreviation(),
+                words: vec![word_vm],
+            }),
+        }
+
+        if let (Some(pattern), Some(inflection_class)) = (&d.pattern, &d.inflection_class) {
+            let (body, _) = super::create_html_body_for_inflection_class(
+                &d.stem,
+                pattern,
+                inflection_class,
+                host,
+            )
+            .await?;
+            tables.push(TableViewModel {
+                word,
+                pattern: pattern.to_owned(),
+                body,
+            });
+        }
+    }
+
+    let vm = TemplateViewModel {
+        view_models,
+        tables,
+        has_unattested: derivations.iter().any(|d| !d.attested),
+        abbrev_map: localisation::get_abbreviations_for_locale_async(host).await?,
+        messages: localisation::get_messages_for_locale(host),
+    };
+    let context = Context::from_serialize(&vm).map_err(|e| e.to_string())?;
+    localisation::render(&TEMPLATES, "derivation", &context, host)
+}
@@ -1,11 +1,13 @@
+use crate::inflections::host::PlsInflectionsHostAsync;
 use crate::inflections::pmd::{Pali1Metadata, WordType};
-use crate::inflections::{localise_abbrev, PlsInflectionsHost};
+use crate::inflections::{localisation, localise_abbrev};
 use tera::{Context, Tera};
 
 lazy_static! {
     static ref TEMPLATES: Tera = {
         let mut tera = Tera::default();
         tera.register_filter("localise_abbrev", localise_abbrev);
+        tera.register_filter("localise", localise_abbrev);
         tera.add_raw_templates(vec![(
             "indeclinable",
             include_str!("templates/indeclinable.html"),
@@ -16,9 +18,9 @@ lazy_static! {
     };
 }
 
-pub fn create_html_body(
+pub async fn create_html_body(
     pm: &Pali1Metadata,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
     with_details: bool,
 ) -> Result<(String, bool), String> {
     let mut context = Context::new();
@@ -36,9 +38,8 @@ pub fn create_html_body(
     context.insert("meaning", &pm.meaning);
     context.insert("pos", &pm.pos);
     context.insert("with_details", &with_details);
-    let body = TEMPLATES
-        .render("indeclinable", &context)
-        .map_err(|e| e.to_string())?;
+    context.insert("messages", &localisation::get_messages_for_locale(host));
+    let body = localisation::render(&TEMPLATES, "indeclinable", &context, host)?;
 
     Ok((body, false))
 }
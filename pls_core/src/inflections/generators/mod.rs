@@ -1,52 +1,140 @@
+use crate::inflections::derivations::VerbDerivation;
+use crate::inflections::host::PlsInflectionsHostAsync;
 use crate::inflections::pmd::{InflectionClass, Pali1Metadata, WordType};
-use crate::inflections::PlsInflectionsHost;
+use regex::{Error, Regex};
 
 mod conjugation;
 mod declension;
 mod declension_pron_dual;
 mod declension_pron_x;
+mod derivation;
 mod indeclinable;
 
-pub fn create_html_body(
+lazy_static! {
+    static ref ABBREVIATION_KEY_CRACKER: Result<Regex, Error> =
+        Regex::new(r#""([^"]+)"\s*\|\s*localise_abbrev"#);
+}
+
+const TEMPLATE_SOURCES: &[&str] = &[
+    include_str!("templates/conjugation.html"),
+    include_str!("templates/declension.html"),
+    include_str!("templates/declension_pron_dual.html"),
+    include_str!("templates/declension_pron_x.html"),
+    include_str!("templates/derivation.html"),
+    include_str!("templates/indeclinable.html"),
+];
+
+pub async fn create_html_body(
     pm: &Pali1Metadata,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
     with_details: bool,
 ) -> Result<(String, bool), String> {
     match &pm.word_type {
         WordType::InflectedForm { stems: _ } | WordType::Indeclinable { stem: _ } => {
-            indeclinable::create_html_body(pm, host, with_details)
+            indeclinable::create_html_body(pm, host, with_details).await
         }
         WordType::Irregular {
             pattern,
             inflection_class,
-        } => create_html_body_for_inflection_class("", pattern, inflection_class, host),
+        } => create_html_body_for_inflection_class("", pattern, inflection_class, host).await,
         WordType::Declinable {
             stem,
             pattern,
             inflection_class,
-        } => create_html_body_for_inflection_class(stem, pattern, inflection_class, host),
+        } => create_html_body_for_inflection_class(stem, pattern, inflection_class, host).await,
     }
 }
 
-fn create_html_body_for_inflection_class(
+pub async fn create_derivation_html_body(
+    derivations: &[VerbDerivation],
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<(String, bool), String> {
+    Ok((
+        derivation::create_html_body(derivations, host).await?,
+        !derivations.is_empty(),
+    ))
+}
+
+async fn create_html_body_for_inflection_class(
     stem: &str,
     pattern: &str,
     inflection_class: &InflectionClass,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<(String, bool), String> {
     let body = match inflection_class {
-        InflectionClass::Conjugation => conjugation::create_html_body(pattern, stem, host),
-        InflectionClass::Declension => declension::create_html_body(pattern, stem, host),
+        InflectionClass::Conjugation => conjugation::create_html_body(pattern, stem, host).await,
+        InflectionClass::Declension => declension::create_html_body(pattern, stem, host).await,
         InflectionClass::DeclensionPron1st => {
-            declension_pron_x::create_html_body("1st", pattern, stem, host)
+            declension_pron_x::create_html_body("1st", pattern, stem, host).await
         }
         InflectionClass::DeclensionPron2nd => {
-            declension_pron_x::create_html_body("2nd", pattern, stem, host)
+            declension_pron_x::create_html_body("2nd", pattern, stem, host).await
         }
         InflectionClass::DeclensionPronDual => {
-            declension_pron_dual::create_html_body(pattern, stem, host)
+            declension_pron_dual::create_html_body(pattern, stem, host).await
         }
     };
 
     Ok((body?, true))
 }
+
+pub fn get_pattern_table_columns(inflection_class: &InflectionClass) -> &'static [&'static str] {
+    match inflection_class {
+        InflectionClass::Conjugation => &["tense", "person", "actreflx", "number", "inflections"],
+        InflectionClass::Declension => &["case", "gender", "number", "inflections"],
+        InflectionClass::DeclensionPron1st
+        | InflectionClass::DeclensionPron2nd
+        | InflectionClass::DeclensionPronDual => {
+            &["case", "special_pron_class", "number", "inflections"]
+        }
+    }
+}
+
+/// The abbreviation keys passed as literals to the localise_abbrev filter in the templates.
+pub fn get_abbreviations_used_in_templates() -> Result<Vec<String>, String> {
+    let regex = ABBREVIATION_KEY_CRACKER
+        .as_ref()
+        .map_err(|e| e.to_string())?;
+    let mut abbrevs: Vec<String> = TEMPLATE_SOURCES
+        .iter()
+        .flat_map(|t| regex.captures_iter(t))
+        .map(|c| c[1].to_string())
+        .collect();
+    abbrevs.sort();
+    abbrevs.dedup();
+
+    Ok(abbrevs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_abbreviations_used_in_templates_test() {
+        let abbrevs = get_abbreviations_used_in_templates();
+
+        assert_eq!(
+            abbrevs,
+            Ok(vec![
+                "1st",
+                "2nd",
+                "3rd",
+                "active",
+                "dual",
+                "feminine",
+                "in comps",
+                "masculine",
+                "neuter",
+                "pl",
+                "plural",
+                "reflexive",
+                "sg",
+                "singular"
+            ]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect())
+        );
+    }
+}
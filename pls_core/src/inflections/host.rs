@@ -1,14 +1,213 @@
-pub trait PlsInflectionsHost<'a> {
-    fn get_locale(&self) -> &'a str;
-    fn get_version(&self) -> &'a str;
-    fn get_url(&self) -> &'a str;
-    fn transliterate(&self, s: &str) -> Result<String, String>;
-    fn exec_sql_query_core(&self, sql: &str) -> Result<String, String>;
-    fn exec_sql_query(&self, sql: &str) -> Result<Vec<Vec<Vec<String>>>, String> {
-        let result_str = self.exec_sql_query_core(sql)?;
-        let result: Vec<Vec<Vec<String>>> =
-            serde_json::from_str(&result_str).map_err(|e| e.to_string())?;
-        Ok(result)
-    }
-    fn log_warning(&self, msg: &str);
-}
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+pub trait PlsInflectionsHost {
+    fn get_locale(&self) -> &str;
+    fn get_version(&self) -> &str;
+    fn get_url(&self) -> &str;
+    /// Identifies the inflections.db behind the host, e.g. its path. Hosts that have one share the
+    /// lookups cached by pls_core; the rest are not cached.
+    fn get_cache_id(&self) -> Option<&str> {
+        None
+    }
+    /// How many rendered tables pls_core keeps for the host's cache id, least recently used
+    /// dropped first. 0 keeps none.
+    fn get_table_cache_capacity(&self) -> usize {
+        0
+    }
+    fn transliterate(&self, s: &str) -> Result<String, String>;
+    fn exec_sql_query_core(&self, sql: &str) -> Result<String, String>;
+    fn exec_sql_query(&self, sql: &str) -> Result<Vec<Vec<Vec<String>>>, String> {
+        let result_str = self.exec_sql_query_core(sql)?;
+        parse_sql_query_result(&result_str)
+    }
+    fn log_warning(&self, msg: &str);
+    /// Diagnostics for troubleshooting, e.g. cache invalidation. Dropped unless the host logs them.
+    fn log_debug(&self, _msg: &str) {}
+}
+
+/// A boxed query future, for hosts whose futures cannot be named, e.g. those of JS promises.
+pub type HostFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Same as HostFuture but Send, for hosts whose queries can complete on another thread.
+pub type SendHostFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Same as PlsInflectionsHost but the queries complete asynchronously, e.g. in a Web Worker or on
+/// an async executor. The futures of the generation functions are Send when the host is Sync and
+/// its query futures are Send, so they can be spawned on a thread pool; they are not otherwise,
+/// e.g. over JS promises.
+pub trait PlsInflectionsHostAsync {
+    fn get_locale(&self) -> &str;
+    fn get_version(&self) -> &str;
+    fn get_url(&self) -> &str;
+    fn get_cache_id(&self) -> Option<&str> {
+        None
+    }
+    fn get_table_cache_capacity(&self) -> usize {
+        0
+    }
+    fn transliterate(&self, s: &str) -> Result<String, String>;
+    fn exec_sql_query_core_async<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> impl Future<Output = Result<String, String>> + 'a;
+    fn exec_sql_query_async<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> impl Future<Output = Result<Vec<Vec<Vec<String>>>, String>> + 'a {
+        async move {
+            let result_str = self.exec_sql_query_core_async(sql).await?;
+            parse_sql_query_result(&result_str)
+        }
+    }
+    fn log_warning(&self, msg: &str);
+    fn log_debug(&self, _msg: &str) {}
+}
+
+/// Adapts a PlsInflectionsHost to PlsInflectionsHostAsync. Its queries are ready when first polled.
+/// It is Sync when the host is, e.g. SyncHost(&host) for a host shared by the threads of a pool.
+pub struct SyncHost<'a, H: ?Sized = dyn PlsInflectionsHost>(pub &'a H);
+
+impl<H: PlsInflectionsHost + ?Sized> PlsInflectionsHostAsync for SyncHost<'_, H> {
+    fn get_locale(&self) -> &str {
+        self.0.get_locale()
+    }
+
+    fn get_version(&self) -> &str {
+        self.0.get_version()
+    }
+
+    fn get_url(&self) -> &str {
+        self.0.get_url()
+    }
+
+    fn get_cache_id(&self) -> Option<&str> {
+        self.0.get_cache_id()
+    }
+
+    fn get_table_cache_capacity(&self) -> usize {
+        self.0.get_table_cache_capacity()
+    }
+
+    fn transliterate(&self, s: &str) -> Result<String, String> {
+        self.0.transliterate(s)
+    }
+
+    fn exec_sql_query_core_async<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> impl Future<Output = Result<String, String>> + 'a {
+        let result = self.0.exec_sql_query_core(sql);
+        async move { result }
+    }
+
+    fn log_warning(&self, msg: &str) {
+        self.0.log_warning(msg)
+    }
+
+    fn log_debug(&self, msg: &str) {
+        self.0.log_debug(msg)
+    }
+}
+
+/// Runs the future to completion on the current thread. The sync API uses it over SyncHost, whose
+/// futures never wait, so it does not block on anything but the queries themselves.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+fn parse_sql_query_result(result_str: &str) -> Result<Vec<Vec<Vec<String>>>, String> {
+    serde_json::from_str(result_str).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pending on the first poll, ready with the value on the second.
+    struct YieldOnce(Option<u32>, bool);
+
+    impl Future for YieldOnce {
+        type Output = u32;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+            if self.1 {
+                Poll::Ready(self.0.take().expect("polled after completion"))
+            } else {
+                self.1 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn block_on_waits_for_pending_futures() {
+        let output =
+            block_on(async { YieldOnce(Some(1), false).await + YieldOnce(Some(2), false).await });
+
+        assert_eq!(output, 3);
+    }
+
+    #[test]
+    fn exec_sql_query_async_parses_result() {
+        struct JsonHost;
+
+        impl PlsInflectionsHost for JsonHost {
+            fn get_locale(&self) -> &str {
+                "en"
+            }
+
+            fn get_version(&self) -> &str {
+                "v0.1"
+            }
+
+            fn get_url(&self) -> &str {
+                "test case"
+            }
+
+            fn transliterate(&self, s: &str) -> Result<String, String> {
+                Ok(s.to_string())
+            }
+
+            fn exec_sql_query_core(&self, sql: &str) -> Result<String, String> {
+                match sql {
+                    "ok" => Ok(r#"[[["a","b"]]]"#.to_string()),
+                    _ => Ok("not json".to_string()),
+                }
+            }
+
+            fn log_warning(&self, _msg: &str) {}
+        }
+
+        let host = SyncHost(&JsonHost);
+
+        assert_eq!(
+            block_on(host.exec_sql_query_async("ok")),
+            Ok(vec![vec![vec!["a".to_string(), "b".to_string()]]])
+        );
+        assert_eq!(
+            block_on(host.exec_sql_query_async("bad")),
+            Err("expected ident at line 1 column 2".to_string())
+        );
+    }
+}
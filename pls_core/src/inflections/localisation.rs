@@ -0,0 +1,257 @@
+use crate::inflections::cache;
+use crate::inflections::host::{block_on, PlsInflectionsHost, PlsInflectionsHostAsync, SyncHost};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tera::{Context, Tera, Value};
+
+/// The locale of the name column of _abbreviations and the last resort of every fallback chain.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Marks every localised abbreviation, e.g. ^pl$, to spot strings that escape localisation.
+pub const PSEUDO_LOCALE: &str = "xx";
+
+const CATALOGUE_SOURCES: &[(&str, &str)] = &[
+    ("en", include_str!("locales/en.json")),
+    ("my", include_str!("locales/my.json")),
+    ("si", include_str!("locales/si.json")),
+    ("th", include_str!("locales/th.json")),
+];
+
+lazy_static! {
+    static ref CATALOGUES: HashMap<&'static str, HashMap<String, String>> = CATALOGUE_SOURCES
+        .iter()
+        .map(|(locale, source)| {
+            let catalogue = serde_json::from_str(source)
+                .unwrap_or_else(|e| panic!("Invalid message catalogue '{}': {}", locale, e));
+            (*locale, catalogue)
+        })
+        .collect();
+}
+
+thread_local! {
+    /// Keys the filters could not find during the current render.
+    static MISSING_KEYS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The locales to try in order, most specific first, e.g. si-LK → si → en.
+pub fn get_locale_fallback_chain(locale: &str) -> Vec<String> {
+    let mut chain: Vec<String> = Vec::new();
+    let mut locale = locale.trim();
+    while !locale.is_empty() {
+        chain.push(locale.to_string());
+        locale = match locale.rfind(['-', '_']) {
+            Some(i) => &locale[..i],
+            None => "",
+        };
+    }
+
+    if !chain.iter().any(|l| l == DEFAULT_LOCALE) {
+        chain.push(DEFAULT_LOCALE.to_string());
+    }
+
+    chain
+}
+
+/// The locales inflections.db has abbreviations for: the default and pseudo locales plus one per
+/// extra column of _abbreviations.
+pub fn get_available_locales(host: &dyn PlsInflectionsHost) -> Result<Vec<String>, String> {
+    block_on(get_available_locales_async(&SyncHost(host)))
+}
+
+pub async fn get_available_locales_async(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Vec<String>, String> {
+    let columns = cache::exec_sql_query("PRAGMA table_info(_abbreviations)", host).await?;
+    let mut locales = vec![DEFAULT_LOCALE.to_string(), PSEUDO_LOCALE.to_string()];
+    locales.extend(
+        columns
+            .into_iter()
+            .flatten()
+            .filter_map(|mut c| {
+                if c.len() > 1 {
+                    Some(c.swap_remove(1))
+                } else {
+                    None
+                }
+            })
+            .filter(|c| c != "name" && c != "description"),
+    );
+
+    Ok(locales)
+}
+
+/// The fallback chain of the host locale, restricted to the locales inflections.db has.
+pub fn get_abbreviation_locale_chain(host: &dyn PlsInflectionsHost) -> Result<Vec<String>, String> {
+    block_on(get_abbreviation_locale_chain_async(&SyncHost(host)))
+}
+
+pub async fn get_abbreviation_locale_chain_async(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Vec<String>, String> {
+    let available = get_available_locales_async(host).await?;
+    let chain: Vec<String> = get_locale_fallback_chain(host.get_locale())
+        .into_iter()
+        .filter(|l| available.contains(l))
+        .collect();
+
+    if chain[0] != host.get_locale() {
+        host.log_warning(&format!(
+            "Locale '{}' not found in inflections.db. Falling back to '{}'.",
+            host.get_locale(),
+            chain[0]
+        ));
+    }
+
+    Ok(chain)
+}
+
+/// Maps abbreviations and their descriptions to the abbreviation in the host locale. Cells that are
+/// empty for a locale fall back along its chain.
+pub fn get_abbreviations_for_locale(
+    host: &dyn PlsInflectionsHost,
+) -> Result<HashMap<String, String>, String> {
+    block_on(get_abbreviations_for_locale_async(&SyncHost(host)))
+}
+
+pub async fn get_abbreviations_for_locale_async(
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<HashMap<String, String>, String> {
+    if let Some(abbrev_map) = cache::get_abbreviations(host) {
+        return Ok(abbrev_map);
+    }
+
+    let chain = get_abbreviation_locale_chain_async(host).await?;
+    let localised_name = if chain[0] == PSEUDO_LOCALE {
+        "'^' || name || '$'".to_string()
+    } else {
+        let mut columns: Vec<String> = chain
+            .iter()
+            .take_while(|l| *l != DEFAULT_LOCALE)
+            .map(|l| format!(r#"nullif("{}", '')"#, l))
+            .collect();
+        if columns.is_empty() {
+            "name".to_string()
+        } else {
+            columns.push("name".to_string());
+            format!("coalesce({})", columns.join(", "))
+        }
+    };
+
+    let sql = format!(
+        "select name, description, {} from _abbreviations",
+        localised_name
+    );
+    let res = host.exec_sql_query_async(&sql).await?;
+    let mut abbrev_map = HashMap::new();
+    for i in res[0].iter() {
+        abbrev_map.insert(i[0].clone(), i[2].clone());
+        abbrev_map.insert(i[1].clone(), i[2].clone());
+    }
+    cache::insert_abbreviations(host, &abbrev_map);
+    Ok(abbrev_map)
+}
+
+/// The template strings in the host locale. Messages missing from a catalogue fall back along the
+/// locale chain.
+pub fn get_messages_for_locale(host: &impl PlsInflectionsHostAsync) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    for locale in get_locale_fallback_chain(host.get_locale()).iter().rev() {
+        if let Some(catalogue) = CATALOGUES.get(locale.as_str()) {
+            messages.extend(catalogue.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    messages
+}
+
+/// Tera filter that looks the value up in the map passed as hmap, e.g. abbreviations or messages.
+/// A missing key renders as itself and is reported by the render that used the filter.
+pub fn localise_abbrev(value: &Value, arg: &HashMap<String, Value>) -> tera::Result<Value> {
+    let key = value
+        .as_str()
+        .ok_or_else(|| "Error while converting value to str.".to_string())?;
+    let localised = &arg["hmap"][key];
+    if localised.is_null() {
+        MISSING_KEYS.with(|mk| mk.borrow_mut().push(key.to_string()));
+        return Ok(value.clone());
+    }
+
+    Ok(serde_json::value::to_value(localised)?)
+}
+
+/// Renders the template and logs any keys the localisation filters could not find.
+pub fn render(
+    tera: &Tera,
+    template_name: &str,
+    context: &Context,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<String, String> {
+    let outer_missing_keys = MISSING_KEYS.with(|mk| mk.replace(Vec::new()));
+    let html = tera
+        .render(template_name, context)
+        .map_err(|e| e.to_string());
+    let mut missing_keys = MISSING_KEYS.with(|mk| mk.replace(outer_missing_keys));
+
+    missing_keys.sort();
+    missing_keys.dedup();
+    for key in missing_keys {
+        host.log_warning(&format!(
+            "Localisation not found for '{}' in locale '{}' while rendering '{}'.",
+            key,
+            host.get_locale(),
+            template_name
+        ));
+    }
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("si" => vec!["si", "en"])]
+    #[test_case("si-LK" => vec!["si-LK", "si", "en"])]
+    #[test_case("zh_Hant_TW" => vec!["zh_Hant_TW", "zh_Hant", "zh", "en"])]
+    #[test_case("en" => vec!["en"])]
+    #[test_case("xx" => vec!["xx", "en"])]
+    #[test_case("" => vec!["en"])]
+    fn get_locale_fallback_chain_tests(locale: &str) -> Vec<String> {
+        get_locale_fallback_chain(locale)
+    }
+
+    #[test]
+    fn catalogues_have_the_same_keys() {
+        let mut en_keys: Vec<&String> = CATALOGUES[DEFAULT_LOCALE].keys().collect();
+        en_keys.sort();
+
+        for (locale, catalogue) in CATALOGUES.iter() {
+            let mut keys: Vec<&String> = catalogue.keys().collect();
+            keys.sort();
+            assert_eq!(keys, en_keys, "catalogue '{}'", locale);
+        }
+    }
+
+    #[test]
+    fn localise_abbrev_falls_back_to_key_and_reports_it() {
+        let mut tera = Tera::default();
+        tera.register_filter("localise_abbrev", localise_abbrev);
+        tera.add_raw_template(
+            "t",
+            r#"{{ "pl" | localise_abbrev(hmap = m) }} {{ "sg" | localise_abbrev(hmap = m) }}"#,
+        )
+        .expect("Unexpected failure adding template");
+        let mut context = Context::new();
+        context.insert(
+            "m",
+            &[("pl", "බහු")].iter().cloned().collect::<HashMap<_, _>>(),
+        );
+
+        let html = tera.render("t", &context);
+        let missing_keys = MISSING_KEYS.with(|mk| mk.replace(Vec::new()));
+
+        assert_eq!(html.ok(), Some("බහු sg".to_string()));
+        assert_eq!(missing_keys, vec!["sg"]);
+    }
+}
@@ -1,20 +1,46 @@
+mod analysis;
+pub mod cache;
+mod derivations;
 mod generators;
 pub mod host;
-mod pmd;
+pub mod localisation;
+mod numeral_declension;
+pub mod pmd;
+mod search;
+mod table_data;
 
 use crate::alphabet::string_compare;
-use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::host::{block_on, PlsInflectionsHost, PlsInflectionsHostAsync, SyncHost};
 use crate::inflections::pmd::{
-    get_feedback_url_for_inflection_class, get_pali1_metadata, InflectionClass, Pali1Metadata,
-    WordType,
+    get_feedback_url_for_inflection_class, get_pali1_metadata, get_pali1_metadata_async,
+    InflectionClass, Pali1Metadata, WordType,
 };
 use serde::Serialize;
 use std::collections::HashMap;
-use tera::{Context, Tera, Value};
+use tera::{Context, Tera};
+
+pub use crate::inflections::analysis::{
+    analyze_word, create_spelling_suggester, load_word_inventory, suggest_spellings,
+    SpellingSuggestion, WordAnalysis,
+};
+pub use crate::inflections::derivations::{
+    generate_verb_derivations, VerbDerivation, VerbDerivationKind,
+};
+pub use crate::inflections::generators::get_pattern_table_columns;
+pub use crate::inflections::localisation::{
+    get_abbreviations_for_locale, get_abbreviations_for_locale_async, localise_abbrev,
+};
+pub use crate::inflections::numeral_declension::inflect_numeral;
+pub use crate::inflections::search::{search_words, SearchResult};
+pub use crate::inflections::table_data::{
+    generate_inflection_table_data, generate_inflection_table_data_async, InflectionCell,
+    InflectionTableData,
+};
 
 lazy_static! {
     static ref TEMPLATES: Tera = {
         let mut tera = Tera::default();
+        tera.register_filter("localise", localise_abbrev);
         tera.add_raw_templates(vec![("output", include_str!("templates/output.html"))])
             .expect("Unexpected failure adding template");
         tera.autoescape_on(vec!["html"]);
@@ -26,9 +52,45 @@ pub fn generate_inflection_table(
     pali1: &str,
     with_details: bool,
     host: &dyn PlsInflectionsHost,
+) -> Result<String, String> {
+    block_on(generate_inflection_table_async(
+        pali1,
+        with_details,
+        &SyncHost(host),
+    ))
+}
+
+pub async fn generate_inflection_table_async(
+    pali1: &str,
+    with_details: bool,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<String, String> {
+    let table_key = format!("{}|{}", with_details, pali1);
+    if let Some(table) = cache::get_table(&table_key, host) {
+        return Ok(table);
+    }
+
+    let pm = get_pali1_metadata_async(pali1, host).await?;
+    let (body, has_inflection_table) =
+        generators::create_html_body(&pm, host, with_details).await?;
+
+    let table = generate_output(&pm, pali1, with_details, &body, has_inflection_table, host)?;
+    cache::insert_table(&table_key, &table, host);
+    Ok(table)
+}
+
+/// The participles, gerundives, absolutives, infinitives, causatives and passives of a verb, with
+/// the inflection tables of those that inflect.
+pub fn generate_verb_derivation_table(
+    pali1: &str,
+    with_details: bool,
+    host: &dyn PlsInflectionsHost,
 ) -> Result<String, String> {
     let pm = get_pali1_metadata(pali1, host)?;
-    let (body, has_inflection_table) = generators::create_html_body(&pm, host, with_details)?;
+    let derivations = generate_verb_derivations(pali1, host)?;
+    let host = &SyncHost(host);
+    let (body, has_inflection_table) =
+        block_on(generators::create_derivation_html_body(&derivations, host))?;
 
     generate_output(&pm, pali1, with_details, &body, has_inflection_table, host)
 }
@@ -37,7 +99,14 @@ pub fn generate_all_inflections(
     pali1: &str,
     host: &dyn PlsInflectionsHost,
 ) -> Result<Vec<String>, String> {
-    let pm = get_pali1_metadata(pali1, host)?;
+    block_on(generate_all_inflections_async(pali1, &SyncHost(host)))
+}
+
+pub async fn generate_all_inflections_async(
+    pali1: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Vec<String>, String> {
+    let pm = get_pali1_metadata_async(pali1, host).await?;
 
     let inflected_words = match pm.word_type {
         WordType::InflectedForm { stems: _ } => vec![],
@@ -47,7 +116,7 @@ pub fn generate_all_inflections(
             inflection_class: _,
         } => {
             let table_name = get_table_name_from_pattern(&pattern);
-            get_all_inflections_for_irregulars(&table_name, host)?
+            get_all_inflections_for_irregulars(&table_name, host).await?
         }
         WordType::Declinable {
             stem,
@@ -55,7 +124,7 @@ pub fn generate_all_inflections(
             inflection_class: _,
         } => {
             let table_name = get_table_name_from_pattern(&pattern);
-            get_all_inflections_for_regulars(&stem, &table_name, host)?
+            get_all_inflections_for_regulars(&stem, &table_name, host).await?
         }
     };
 
@@ -79,6 +148,7 @@ struct OutputViewModel<'a> {
     pub feedback_form_url: &'a str,
     pub host_url: &'a str,
     pub host_version: &'a str,
+    pub messages: HashMap<String, String>,
 }
 
 fn generate_output(
@@ -87,7 +157,7 @@ fn generate_output(
     with_details: bool,
     body: &str,
     has_inflection_table: bool,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<String, String> {
     let feedback_form_url = match &pm.word_type {
         WordType::Irregular {
@@ -127,26 +197,27 @@ fn generate_output(
         has_inflection_table,
         host_url: host.get_url(),
         host_version: host.get_version(),
+        messages: localisation::get_messages_for_locale(host),
     };
 
     let context = Context::from_serialize(&vm).map_err(|e| e.to_string())?;
-    TEMPLATES
-        .render("output", &context)
-        .map_err(|e| e.to_string())
+    localisation::render(&TEMPLATES, "output", &context, host)
 }
 
-fn get_inflection_suffixes_for_pattern(
+async fn get_inflection_suffixes_for_pattern(
     pattern: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<Vec<Vec<String>>>, String> {
-    host.exec_sql_query(&format!("Select * from {}", pattern))
+    host.exec_sql_query_async(&format!("Select * from {}", pattern))
+        .await
 }
 
-fn get_all_inflections_for_irregulars(
+async fn get_all_inflections_for_irregulars(
     pattern: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<String>, String> {
-    let suffixes: Vec<Vec<String>> = get_inflection_suffixes_for_pattern(pattern, host)?
+    let suffixes: Vec<Vec<String>> = get_inflection_suffixes_for_pattern(pattern, host)
+        .await?
         .pop()
         .ok_or_else(|| format!("No pattern found for {}", pattern))?;
     let mut inflections: Vec<String> = Vec::new();
@@ -162,13 +233,14 @@ fn get_all_inflections_for_irregulars(
     Ok(inflections)
 }
 
-fn get_all_inflections_for_regulars(
+async fn get_all_inflections_for_regulars(
     stem: &str,
     pattern: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<Vec<String>, String> {
     let mut inflections: Vec<String> = Vec::new();
-    let suffixes: Vec<Vec<String>> = get_inflection_suffixes_for_pattern(pattern, host)?
+    let suffixes: Vec<Vec<String>> = get_inflection_suffixes_for_pattern(pattern, host)
+        .await?
         .pop()
         .ok_or_else(|| format!("No pattern found for {}", pattern))?;
     for mut suffix_row in suffixes {
@@ -183,33 +255,24 @@ fn get_all_inflections_for_regulars(
     Ok(inflections)
 }
 
-pub fn localise_abbrev(value: &Value, arg: &HashMap<String, Value>) -> tera::Result<Value> {
-    let localised_abbrev = &arg["hmap"][value
-        .as_str()
-        .ok_or_else(|| "Error while converting value to str.".to_string())?];
-    if localised_abbrev.is_null() {
-        let error_string = format!("Error: abbreviation not found for {}", value);
-        println!("{}", error_string);
-        return Err(tera::Error::msg(error_string));
-    }
-    Ok(serde_json::value::to_value(localised_abbrev)?)
-}
-
 fn join_and_transliterate_if_not_empty(
     stem: &str,
     suffix: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> String {
     if suffix.is_empty() {
         "".to_string()
     } else {
-        host.transliterate(&format!("{}{}", stem, suffix))
-            .unwrap_or_else(|e| e)
+        cache::transliterate(&format!("{}{}", stem, suffix), host).unwrap_or_else(|e| e)
     }
 }
 
-fn get_inflections(stem: &str, sql: &str, host: &dyn PlsInflectionsHost) -> Vec<String> {
-    let res = match host.exec_sql_query(sql) {
+async fn get_inflections(
+    stem: &str,
+    sql: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Vec<String> {
+    let res = match host.exec_sql_query_async(sql).await {
         Ok(x) => {
             if x.len() == 1 && x[0].len() == 1 && x[0][0].len() == 1 {
                 x[0][0][0].to_string()
@@ -228,38 +291,43 @@ fn get_inflections(stem: &str, sql: &str, host: &dyn PlsInflectionsHost) -> Vec<
     inflections
 }
 
-fn query_has_no_results(query: &str, host: &dyn PlsInflectionsHost) -> Result<bool, String> {
-    let count = &host.exec_sql_query(query)?[0][0][0];
+async fn query_has_no_results(
+    query: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<bool, String> {
+    let count = &host.exec_sql_query_async(query).await?[0][0][0];
     Ok(count.eq("0"))
 }
 
-pub fn get_abbreviations_for_locale(
+/// All abbreviation keys the templates localise: the literals in the templates plus the case and
+/// tense names that are used as row and table headers, and the verb derivation kinds.
+pub fn get_abbreviations_used_in_templates(
     host: &dyn PlsInflectionsHost,
-) -> Result<HashMap<String, String>, String> {
-    let locale = host.get_locale();
-    let sql = if locale == "xx" {
-        "select name, description, '^' || name || '$' from _abbreviations".to_string()
-    } else if locale == "en" {
-        "select name, description, name from _abbreviations".to_string()
-    } else {
-        format!(
-            r#"select name, description, {} from _abbreviations"#,
-            locale
-        )
-    };
-    let res = host.exec_sql_query(&sql)?;
-    let mut abbrev_map = HashMap::new();
-    for i in res[0].iter() {
-        abbrev_map.insert(i[0].clone(), i[2].clone());
-        abbrev_map.insert(i[1].clone(), i[2].clone());
-    }
-    Ok(abbrev_map)
+) -> Result<Vec<String>, String> {
+    let sql = r#"
+        select * from _case_values where name <> "";
+        select * from _tense_values where name <> "";
+    "#;
+
+    let mut abbrevs = generators::get_abbreviations_used_in_templates()?;
+    abbrevs.extend(host.exec_sql_query(sql)?.into_iter().flatten().flatten());
+    abbrevs.extend(
+        VerbDerivationKind::ALL
+            .iter()
+            .map(|k| k.abbreviation().to_string()),
+    );
+    abbrevs.sort();
+    abbrevs.dedup();
+
+    Ok(abbrevs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::inflections::test_host::Host;
+    use futures_executor::ThreadPool;
+    use std::sync::mpsc;
     use test_case::test_case;
 
     #[test_case("agamaṃsu",true,"xx"; "indeclinable - 1")]
@@ -313,6 +381,75 @@ mod tests {
         insta::assert_yaml_snapshot!(output);
     }
 
+    #[test_case("ābādheti"; "conjugation")]
+    #[test_case("kamma 1"; "irregular")]
+    #[test_case("ahesuṃ"; "inflected form")]
+    fn async_host_generates_same_output_tests(pali1: &str) {
+        let host = Host {
+            locale: "en",
+            url: "test case",
+            version: "v0.1",
+            psuedo_transliterate: true,
+        };
+        let async_host = test_host::YieldingHost(&host);
+
+        assert_eq!(
+            block_on(generate_inflection_table_async(pali1, true, &async_host)),
+            generate_inflection_table(pali1, true, &host)
+        );
+        assert_eq!(
+            block_on(generate_all_inflections_async(pali1, &async_host)),
+            generate_all_inflections(pali1, &host)
+        );
+    }
+
+    #[test]
+    fn async_generation_runs_on_a_thread_pool() {
+        let pool = ThreadPool::builder()
+            .pool_size(2)
+            .create()
+            .expect("unexpected test setup failure");
+        let (sender, receiver) = mpsc::channel();
+        for pali1 in &["ābādheti", "kamma 1", "ahesuṃ"] {
+            for yielding in &[true, false] {
+                let sender = sender.clone();
+                pool.spawn_ok(async move {
+                    let host = Host {
+                        locale: "en",
+                        url: "test case",
+                        version: "v0.1",
+                        psuedo_transliterate: true,
+                    };
+                    let table = if *yielding {
+                        let async_host = test_host::YieldingHost(&host);
+                        generate_inflection_table_async(pali1, true, &async_host).await
+                    } else {
+                        generate_inflection_table_async(pali1, true, &SyncHost(&host)).await
+                    };
+                    sender.send((*pali1, table)).expect("receiver dropped");
+                });
+            }
+        }
+        drop(sender);
+
+        let host = Host {
+            locale: "en",
+            url: "test case",
+            version: "v0.1",
+            psuedo_transliterate: true,
+        };
+        let tables: Vec<(&str, Result<String, String>)> = receiver.iter().collect();
+        assert_eq!(tables.len(), 6);
+        for (pali1, table) in tables {
+            assert_eq!(
+                table,
+                generate_inflection_table(pali1, true, &host),
+                "{}",
+                pali1
+            );
+        }
+    }
+
     #[test_case("xx", "missingAbbreviation")]
     #[test_case("xx", "pl")]
     #[test_case("en", "pl")]
@@ -348,8 +485,12 @@ mod tests {
 
 #[cfg(test)]
 mod test_host {
-    use crate::inflections::host::PlsInflectionsHost;
+    use crate::inflections::host::{PlsInflectionsHost, PlsInflectionsHostAsync};
     use rusqlite::{Connection, Row, NO_PARAMS};
+    use std::cell::Cell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
 
     pub(crate) struct Host<'a> {
         pub(crate) locale: &'a str,
@@ -358,16 +499,16 @@ mod test_host {
         pub(crate) psuedo_transliterate: bool,
     }
 
-    impl<'a> PlsInflectionsHost<'a> for Host<'a> {
-        fn get_locale(&self) -> &'a str {
+    impl<'a> PlsInflectionsHost for Host<'a> {
+        fn get_locale(&self) -> &str {
             self.locale
         }
 
-        fn get_version(&self) -> &'a str {
+        fn get_version(&self) -> &str {
             self.version
         }
 
-        fn get_url(&self) -> &'a str {
+        fn get_url(&self) -> &str {
             self.url
         }
 
@@ -391,6 +532,132 @@ mod test_host {
         }
     }
 
+    /// Answers the queries from a db the test creates in memory, counting the queries and the
+    /// transliterations.
+    pub(crate) struct DbHost<'a> {
+        pub(crate) conn: Connection,
+        pub(crate) locale: &'a str,
+        pub(crate) version: &'a str,
+        pub(crate) url: &'a str,
+        pub(crate) cache_id: Option<&'a str>,
+        pub(crate) table_cache_capacity: usize,
+        pub(crate) psuedo_transliterate: bool,
+        pub(crate) queries: Cell<usize>,
+        pub(crate) transliterations: Cell<usize>,
+    }
+
+    impl<'a> DbHost<'a> {
+        /// A host over an in-memory db set up by the ;-separated statements.
+        pub(crate) fn new(db_sql: &str) -> DbHost<'a> {
+            let conn = Connection::open_in_memory().unwrap();
+            conn.execute_batch(db_sql).unwrap();
+            DbHost {
+                conn,
+                locale: "en",
+                version: "v0.1",
+                url: "test case",
+                cache_id: None,
+                table_cache_capacity: 0,
+                psuedo_transliterate: false,
+                queries: Cell::new(0),
+                transliterations: Cell::new(0),
+            }
+        }
+    }
+
+    impl<'a> PlsInflectionsHost for DbHost<'a> {
+        fn get_locale(&self) -> &str {
+            self.locale
+        }
+
+        fn get_version(&self) -> &str {
+            self.version
+        }
+
+        fn get_url(&self) -> &str {
+            self.url
+        }
+
+        fn get_cache_id(&self) -> Option<&str> {
+            self.cache_id
+        }
+
+        fn get_table_cache_capacity(&self) -> usize {
+            self.table_cache_capacity
+        }
+
+        fn transliterate(&self, s: &str) -> Result<String, String> {
+            self.transliterations.set(self.transliterations.get() + 1);
+            let ret = if self.psuedo_transliterate {
+                format!("^{}$", s)
+            } else {
+                s.to_string()
+            };
+
+            Ok(ret)
+        }
+
+        fn exec_sql_query_core(&self, sql: &str) -> Result<String, String> {
+            self.queries.set(self.queries.get() + 1);
+            let table = exec_sql(&self.conn, sql).map_err(|x| x.to_string())?;
+            serde_json::to_string(&table).map_err(|x| x.to_string())
+        }
+
+        fn log_warning(&self, msg: &str) {
+            println!("WARNING: {}", msg)
+        }
+    }
+
+    /// Answers each query only after yielding once, as a host whose queries run elsewhere would.
+    pub(crate) struct YieldingHost<'a>(pub(crate) &'a Host<'a>);
+
+    impl<'a> PlsInflectionsHostAsync for YieldingHost<'a> {
+        fn get_locale(&self) -> &str {
+            self.0.get_locale()
+        }
+
+        fn get_version(&self) -> &str {
+            self.0.get_version()
+        }
+
+        fn get_url(&self) -> &str {
+            self.0.get_url()
+        }
+
+        fn transliterate(&self, s: &str) -> Result<String, String> {
+            self.0.transliterate(s)
+        }
+
+        async fn exec_sql_query_core_async(&self, sql: &str) -> Result<String, String> {
+            YieldNow(false).await;
+            self.0.exec_sql_query_core(sql)
+        }
+
+        fn log_warning(&self, msg: &str) {
+            self.0.log_warning(msg)
+        }
+
+        fn log_debug(&self, msg: &str) {
+            self.0.log_debug(msg)
+        }
+    }
+
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
     fn get_row_cells(row: &Row) -> Vec<String> {
         let cells: Vec<String> = row
             .column_names()
@@ -409,6 +676,14 @@ mod test_host {
 
     fn exec_sql_core(sql: &str) -> rusqlite::Result<Vec<Vec<Vec<String>>>, rusqlite::Error> {
         let conn = Connection::open("../inflections.db")?;
+        exec_sql(&conn, sql)
+    }
+
+    /// Runs the ;-separated queries.
+    fn exec_sql(
+        conn: &Connection,
+        sql: &str,
+    ) -> rusqlite::Result<Vec<Vec<Vec<String>>>, rusqlite::Error> {
         let mut result: Vec<Vec<Vec<String>>> = Vec::new();
         for s in sql.split(';').filter(|s| !s.trim().is_empty()) {
             let mut stmt = conn.prepare(s)?;
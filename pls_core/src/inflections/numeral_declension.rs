@@ -0,0 +1,242 @@
+use crate::alphabet::sort_key;
+use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::table_data::{generate_inflection_table_data, InflectionCell};
+use crate::numerals::{spell_numeral, NumeralKind, NumeralWord};
+
+/// Declines the spelled number in the case and gender, e.g. "dvisatena pañcahi" for 205 in the
+/// instrumental. Each word declines like its head word in _stems and the words before the last
+/// take their first form.
+pub fn inflect_numeral(
+    n: u64,
+    kind: NumeralKind,
+    case: &str,
+    gender: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<String>, String> {
+    let words = spell_numeral(n, kind)?;
+    let last_index = words.len() - 1;
+    let mut inflected = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            // The collective numerals from vīsati up and the ordinals are singular.
+            let singular = (kind == NumeralKind::Ordinal && i == last_index)
+                || word.value == 1
+                || word.value >= 19;
+            inflect_numeral_word(word, case, gender, singular, host)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let last = inflected.pop().unwrap_or_default();
+    let preceding: Vec<&str> = inflected
+        .iter()
+        .filter_map(|forms| forms.first().map(String::as_str))
+        .collect();
+
+    Ok(last
+        .iter()
+        .map(|form| {
+            let mut phrase = preceding.clone();
+            phrase.push(form);
+            phrase.join(" ")
+        })
+        .collect())
+}
+
+fn inflect_numeral_word(
+    word: &NumeralWord,
+    case: &str,
+    gender: &str,
+    singular: bool,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<String>, String> {
+    // The word itself, e.g. terasa, else the head word it declines like, e.g. dasa.
+    let mut names = vec![word.text.as_str()];
+    if word.head != word.text {
+        names.push(&word.head);
+    }
+
+    for name in names {
+        let pali1 = match find_numeral_pali1(name, host)? {
+            Some(pali1) => pali1,
+            None => continue,
+        };
+
+        let data = generate_inflection_table_data(&pali1, host)?;
+        let forms = select_forms(
+            &data.cells,
+            case,
+            gender,
+            if singular { "sg" } else { "pl" },
+        );
+        if forms.is_empty() {
+            return Err(format!("'{}' has no '{}' forms.", pali1, case));
+        }
+
+        // dve declines as dvi, the compounds change the ending of the head word.
+        let is_compound = word.text.chars().count() > name.chars().count();
+        let mut forms: Vec<String> = forms
+            .into_iter()
+            .map(|f| {
+                if is_compound {
+                    rebase_form(&f, name, &word.text)
+                } else {
+                    f
+                }
+            })
+            .collect();
+        forms.dedup();
+        return Ok(forms);
+    }
+
+    Err(format!("No numeral head word found for '{}'.", word.text))
+}
+
+fn find_numeral_pali1(name: &str, host: &dyn PlsInflectionsHost) -> Result<Option<String>, String> {
+    let name = name.replace("'", "''");
+    let sql = format!(
+        r#"SELECT pāli1 FROM _stems WHERE (pāli1 = '{0}' OR pāli1 LIKE '{1} %' ESCAPE '\') AND pos IN ('card', 'ordin')"#,
+        name,
+        name.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    Ok(host
+        .exec_sql_query(&sql)?
+        .into_iter()
+        .flatten()
+        .filter_map(|row| row.into_iter().next())
+        .min_by_key(|pali1| sort_key(pali1)))
+}
+
+/// The forms of the case, of the gender and number when the table has them, e.g. pañca has only
+/// plural forms of no gender.
+fn select_forms(cells: &[InflectionCell], case: &str, gender: &str, number: &str) -> Vec<String> {
+    let in_case = cells
+        .iter()
+        .filter(|c| c.parameters.get("case").map(String::as_str) == Some(case))
+        .collect();
+    let in_gender = prefer_parameter(in_case, "gender", gender);
+    let in_number = prefer_parameter(in_gender, "number", number);
+
+    let mut forms: Vec<String> = in_number
+        .into_iter()
+        .flat_map(|c| c.inflections.iter().cloned())
+        .collect();
+    forms.dedup();
+    forms
+}
+
+fn prefer_parameter<'a>(
+    cells: Vec<&'a InflectionCell>,
+    parameter: &str,
+    value: &str,
+) -> Vec<&'a InflectionCell> {
+    let matching: Vec<&InflectionCell> = cells
+        .iter()
+        .copied()
+        .filter(|c| c.parameters.get(parameter).map(String::as_str) == Some(value))
+        .collect();
+
+    if matching.is_empty() {
+        cells
+    } else {
+        matching
+    }
+}
+
+/// The form of the compound word for the form of its head word, e.g. chabbīsatiyā for vīsatiyā.
+fn rebase_form(form: &str, head: &str, word: &str) -> String {
+    let common = form
+        .chars()
+        .zip(head.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let head_rest = head.chars().count() - common;
+    let word_len = word.chars().count();
+
+    word.chars()
+        .take(word_len.saturating_sub(head_rest))
+        .chain(form.chars().skip(common))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflections::test_host;
+    use test_case::test_case;
+
+    const NUMERALS_DB: &str = r#"
+        CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+        CREATE TABLE _index (name TEXT, inflection_class TEXT, like TEXT);
+        CREATE TABLE a1_card ("case" TEXT, gender TEXT, number TEXT, inflections TEXT);
+        CREATE TABLE i_card ("case" TEXT, gender TEXT, number TEXT, inflections TEXT);
+        CREATE TABLE a_nt ("case" TEXT, gender TEXT, number TEXT, inflections TEXT);
+        CREATE TABLE a_adj ("case" TEXT, gender TEXT, number TEXT, inflections TEXT);
+        INSERT INTO _stems VALUES
+            ('pañca', 'pañc', 'a1 card', 'card', 'five'),
+            ('pañca 2', 'pañc', 'a nt', 'nt', 'the five'),
+            ('vīsati', 'vīsat', 'i card', 'card', 'twenty'),
+            ('sata', 'sat', 'a nt', 'card', 'hundred'),
+            ('dasama', 'dasam', 'a adj', 'ordin', 'tenth');
+        INSERT INTO _index VALUES
+            ('a1 card', '', 'pañca'),
+            ('i card', '', 'vīsati'),
+            ('a nt', '', 'citta'),
+            ('a adj', '', 'dasama');
+        INSERT INTO a1_card VALUES
+            ('nom', 'x', 'pl', 'a'),
+            ('instr', 'x', 'pl', 'abhi,ahi');
+        INSERT INTO i_card VALUES
+            ('nom', 'fem', 'sg', 'i'),
+            ('instr', 'fem', 'sg', 'iyā');
+        INSERT INTO a_nt VALUES
+            ('nom', 'nt', 'sg', 'aṃ'),
+            ('nom', 'nt', 'pl', 'āni'),
+            ('instr', 'nt', 'sg', 'ena'),
+            ('instr', 'nt', 'pl', 'ehi');
+        INSERT INTO a_adj VALUES
+            ('nom', 'masc', 'sg', 'o'),
+            ('nom', 'fem', 'sg', 'ā'),
+            ('instr', 'masc', 'sg', 'ena');
+    "#;
+
+    #[test_case(5, NumeralKind::Cardinal, "instr", "masc" => Ok(vec!["pañcabhi".to_string(), "pañcahi".to_string()]); "no gender")]
+    #[test_case(26, NumeralKind::Cardinal, "instr", "masc" => Ok(vec!["chabbīsatiyā".to_string()]); "compound of vīsati")]
+    #[test_case(205, NumeralKind::Cardinal, "instr", "masc" => Ok(vec!["dvisatena pañcabhi".to_string(), "dvisatena pañcahi".to_string()]))]
+    #[test_case(500, NumeralKind::Cardinal, "nom", "nt" => Ok(vec!["pañcasataṃ".to_string()]))]
+    #[test_case(20, NumeralKind::Ordinal, "nom", "fem" => Ok(vec!["vīsatimā".to_string()]); "declines like dasama")]
+    #[test_case(3, NumeralKind::Cardinal, "nom", "masc" => Err("No numeral head word found for 'ti'.".to_string()))]
+    #[test_case(5, NumeralKind::Cardinal, "loc", "masc" => Err("'pañca' has no 'loc' forms.".to_string()))]
+    fn inflect_numeral_tests(
+        n: u64,
+        kind: NumeralKind,
+        case: &str,
+        gender: &str,
+    ) -> Result<Vec<String>, String> {
+        inflect_numeral(n, kind, case, gender, &test_host::DbHost::new(NUMERALS_DB))
+    }
+
+    #[test_case("pañca" => Ok(Some("pañca".to_string())))]
+    #[test_case("cha" => Ok(Some("cha 1".to_string())))]
+    #[test_case("c_a" => Ok(None); "underscore is not a wildcard")]
+    #[test_case("%" => Ok(None); "percent is not a wildcard")]
+    #[test_case("cha'" => Ok(None); "quote")]
+    fn find_numeral_pali1_tests(name: &str) -> Result<Option<String>, String> {
+        let host = test_host::DbHost::new(NUMERALS_DB);
+        host.conn
+            .execute_batch("INSERT INTO _stems VALUES ('cha 1', 'ch', 'a1 card', 'card', 'six')")
+            .unwrap();
+
+        find_numeral_pali1(name, &host)
+    }
+
+    #[test_case("tayo", "ti", "ti" => "tayo")]
+    #[test_case("dasannaṃ", "dasa", "terasa" => "terasannaṃ")]
+    #[test_case("dasamo", "dasama", "vīsatima" => "vīsatimo")]
+    fn rebase_form_tests(form: &str, head: &str, word: &str) -> String {
+        rebase_form(form, head, word)
+    }
+}
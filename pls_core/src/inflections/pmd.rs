@@ -1,6 +1,8 @@
-use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::cache;
+use crate::inflections::host::{block_on, PlsInflectionsHost, PlsInflectionsHostAsync, SyncHost};
 use regex::{Error, Regex};
 use serde::Serialize;
+use std::fmt;
 use std::str::FromStr;
 
 lazy_static! {
@@ -50,7 +52,7 @@ impl FromStr for InflectionClass {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct Pali1Metadata {
     pub pali1: String,
     pub word_type: WordType,
@@ -60,6 +62,37 @@ pub struct Pali1Metadata {
     pub long_name: String,
 }
 
+/// Why the metadata of a head word could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pali1MetadataError {
+    /// The word is not in _stems.
+    NotFound(String),
+    /// The word could not be read, e.g. a failing query, an empty stem or no _index row.
+    Failed(String),
+}
+
+impl fmt::Display for Pali1MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pali1MetadataError::NotFound(msg) | Pali1MetadataError::Failed(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl From<String> for Pali1MetadataError {
+    fn from(msg: String) -> Pali1MetadataError {
+        Pali1MetadataError::Failed(msg)
+    }
+}
+
+impl From<Pali1MetadataError> for String {
+    fn from(e: Pali1MetadataError) -> String {
+        e.to_string()
+    }
+}
+
 pub fn get_stem_for_indeclinable(pali1: &str) -> Result<String, String> {
     let regex = INDECLINABLE_CRACKER.as_ref().map_err(|e| e.to_string())?;
     Ok(regex.replace(pali1, "").to_string())
@@ -69,13 +102,47 @@ pub fn get_pali1_metadata(
     pali1: &str,
     host: &dyn PlsInflectionsHost,
 ) -> Result<Pali1Metadata, String> {
+    Ok(find_pali1_metadata(pali1, host)?)
+}
+
+pub async fn get_pali1_metadata_async(
+    pali1: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Pali1Metadata, String> {
+    Ok(find_pali1_metadata_async(pali1, host).await?)
+}
+
+/// Same as get_pali1_metadata but tells a word missing from the db from a failure to read it.
+pub fn find_pali1_metadata(
+    pali1: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Pali1Metadata, Pali1MetadataError> {
+    block_on(find_pali1_metadata_async(pali1, &SyncHost(host)))
+}
+
+pub async fn find_pali1_metadata_async(
+    pali1: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Pali1Metadata, Pali1MetadataError> {
     let sql = format!(
         r#"select stem, pattern, pos, definition from '_stems' where pāli1 = "{}""#,
-        pali1,
+        pali1.replace('"', "\"\""),
     );
-    let results = host.exec_sql_query(&sql)?;
+    let results = host
+        .exec_sql_query_async(&sql)
+        .await
+        .map_err(Pali1MetadataError::Failed)?;
+    if results.len() == 1 && results[0].is_empty() {
+        return Err(Pali1MetadataError::NotFound(format!(
+            "Word '{}' not found in db.",
+            pali1
+        )));
+    }
     if results.len() != 1 || results[0].len() != 1 || results[0][0].len() != 4 {
-        return Err(format!("Word '{}' not found in db.", pali1));
+        return Err(Pali1MetadataError::Failed(format!(
+            "Unexpected _stems rows for word '{}'.",
+            pali1
+        )));
     }
 
     let stem = results[0][0][0].to_owned();
@@ -84,7 +151,9 @@ pub fn get_pali1_metadata(
     let meaning = results[0][0][3].to_owned();
 
     if stem.is_empty() {
-        return Err("Stem cannot be empty".to_string());
+        return Err(Pali1MetadataError::Failed(
+            "Stem cannot be empty".to_string(),
+        ));
     }
 
     let pm = match stem.as_str() {
@@ -107,7 +176,7 @@ pub fn get_pali1_metadata(
             long_name: "indeclinable".to_string(),
         },
         "*" => {
-            let (inflection_class, _) = get_index_info(&pattern, host)?;
+            let (inflection_class, _) = get_index_info(&pattern, host).await?;
             Pali1Metadata {
                 pali1: pali1.to_string(),
                 word_type: WordType::Irregular {
@@ -121,7 +190,7 @@ pub fn get_pali1_metadata(
             }
         }
         _ => {
-            let (inflection_class, like) = get_index_info(&pattern, host)?;
+            let (inflection_class, like) = get_index_info(&pattern, host).await?;
             Pali1Metadata {
                 pali1: pali1.to_string(),
                 word_type: WordType::Declinable {
@@ -149,23 +218,23 @@ pub fn get_feedback_url_for_inflection_class(inflection_class: &InflectionClass)
     }
 }
 
-fn get_index_info(
+async fn get_index_info(
     pattern: &str,
-    host: &dyn PlsInflectionsHost,
+    host: &impl PlsInflectionsHostAsync,
 ) -> Result<(InflectionClass, String), String> {
     let sql = format!(
         r#"select inflection_class, like from '_index' where name = "{}""#,
         pattern
     );
 
-    let results = host.exec_sql_query(&sql)?;
-    if results.is_empty() || results[0].is_empty() || results[0][0].is_empty() {
-        host.log_warning(
-            &format!(
-                "No results returned from inflections.db. This is unexpected, will crash now. sql: '{}'",
-                sql
-            )
+    let results = cache::exec_sql_query(&sql, host).await?;
+    if results.is_empty() || results[0].is_empty() || results[0][0].len() != 2 {
+        let msg = format!(
+            "No results returned from inflections.db. This is unexpected. sql: '{}'",
+            sql
         );
+        host.log_warning(&msg);
+        return Err(msg);
     }
 
     let inflection_class = InflectionClass::from_str(&results[0][0][0])?;
@@ -197,4 +266,40 @@ mod tests {
 
         insta::assert_yaml_snapshot!(output);
     }
+
+    #[test_case("dhamma 1" => Err(Pali1MetadataError::NotFound("Word 'dhamma 1' not found in db.".to_string())); "not found")]
+    #[test_case("dhamma 2" => Err(Pali1MetadataError::Failed("Unexpected _stems rows for word 'dhamma 2'.".to_string())); "duplicate rows")]
+    #[test_case("kamma 1" => Err(Pali1MetadataError::Failed("Stem cannot be empty".to_string())); "empty stem")]
+    #[test_case("phala 1" => Err(Pali1MetadataError::Failed("No results returned from inflections.db. This is unexpected. sql: 'select inflection_class, like from '_index' where name = \"a nt\"'".to_string())); "pattern not in db")]
+    #[test_case("a\" or \"1" => Err(Pali1MetadataError::NotFound("Word 'a\" or \"1' not found in db.".to_string())); "quoted")]
+    #[test_case("ababa 1" => Ok("declinable".to_string()); "regular")]
+    fn find_pali1_metadata_tests(pali1: &str) -> Result<String, Pali1MetadataError> {
+        let host = test_host::DbHost::new(
+            r#"
+            CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+            CREATE TABLE _index (name TEXT, inflection_class TEXT, like TEXT);
+            INSERT INTO _stems VALUES
+                ('ababa 1', 'abab', 'a masc', 'masc', 'bird'),
+                ('dhamma 2', 'dhamm', 'a masc', 'masc', 'nature'),
+                ('dhamma 2', 'dhamm', 'a nt', 'nt', 'nature'),
+                ('kamma 1', '', 'a nt', 'nt', 'action'),
+                ('phala 1', 'phal', 'a nt', 'nt', 'fruit');
+            INSERT INTO _index VALUES ('a masc', '', 'dhamma');
+            "#,
+        );
+
+        find_pali1_metadata(pali1, &host).map(|pm| pm.long_name)
+    }
+
+    #[test]
+    fn find_pali1_metadata_query_failure_test() {
+        let host = test_host::DbHost::new("");
+
+        assert_eq!(
+            find_pali1_metadata("ababa 1", &host).map(|pm| pm.long_name),
+            Err(Pali1MetadataError::Failed(
+                "no such table: _stems".to_string()
+            ))
+        );
+    }
 }
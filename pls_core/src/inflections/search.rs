@@ -0,0 +1,123 @@
+use crate::alphabet::{fold_diacritics, sort_key};
+use crate::inflections::host::PlsInflectionsHost;
+use crate::inflections::pmd::get_stem_for_indeclinable;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A head word or inflected form whose folded spelling is the folded query, with the head words it
+/// comes from.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SearchResult {
+    pub word: String,
+    pub pali1s: Vec<String>,
+    pub exact: bool,
+}
+
+/// Finds the words of _stems.pāli1 and _all_words that match the query ignoring diacritics, e.g.
+/// paññā for "panna". Exact matches come first, the rest in alphabet order. This needs the folded
+/// spellings that all_words_gen adds to inflections.db.
+pub fn search_words(
+    query: &str,
+    max_results: usize,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<SearchResult>, String> {
+    let query = query.trim().to_lowercase();
+    let folded_query = fold_diacritics(&query);
+    if folded_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sql = format!(
+        r#"
+        SELECT w.inflection, s.pāli1 FROM _all_words w JOIN _stems s ON s.rowid = w.stem_id
+            WHERE w.inflection_folded = '{0}'
+        UNION
+        SELECT s.pāli1, s.pāli1 FROM _all_words_head_words h JOIN _stems s ON s.rowid = h.stem_id
+            WHERE h.pali1_folded = '{0}'
+        "#,
+        folded_query.replace("'", "''")
+    );
+
+    let mut words: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in host.exec_sql_query(&sql)?.into_iter().flatten() {
+        if let [word, pali1] = row.as_slice() {
+            // The head words are found without their homonym numbers, e.g. dhamma 1.
+            let word = get_stem_for_indeclinable(word)?;
+            words.entry(word).or_default().push(pali1.clone());
+        }
+    }
+
+    let mut results: Vec<SearchResult> = words
+        .into_iter()
+        .map(|(word, mut pali1s)| {
+            pali1s.sort_by_cached_key(|p| sort_key(p));
+            pali1s.dedup();
+            SearchResult {
+                exact: word == query,
+                word,
+                pali1s,
+            }
+        })
+        .collect();
+    results.sort_by_cached_key(|r| (!r.exact, sort_key(&r.word)));
+    results.truncate(max_results);
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflections::test_host;
+
+    const SEARCH_DB: &str = r#"
+        CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+        CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, inflection_folded TEXT NOT NULL, stem_id INTEGER NOT NULL);
+        CREATE TABLE _all_words_head_words (stem_id INTEGER NOT NULL, pali1_folded TEXT NOT NULL);
+        INSERT INTO _stems VALUES
+            ('paññā', 'paññ', 'ā fem', 'fem', 'wisdom'),
+            ('panna 1', 'pann', 'a adj', 'pp', 'fallen'),
+            ('pannaka', 'pannak', 'a masc', 'masc', 'serpent'),
+            ('paññā 1', '-', '', 'ind', 'knowing'),
+            ('pañca', 'pañc', 'a1 card', 'card', 'five');
+        INSERT INTO _all_words (inflection, inflection_folded, stem_id) VALUES
+            ('paññā', 'panna', 1), ('paññaṃ', 'pannam', 1), ('pannā', 'panna', 2), ('panna', 'panna', 2),
+            ('pannassa', 'pannassa', 2), ('pannakā', 'pannaka', 3);
+        INSERT INTO _all_words_head_words VALUES
+            (1, 'panna'), (2, 'panna'), (3, 'pannaka'), (4, 'panna'), (5, 'panca');
+    "#;
+
+    #[test]
+    fn search_words_test() {
+        let results = search_words(" Panna", 10, &test_host::DbHost::new(SEARCH_DB)).unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                SearchResult {
+                    word: "panna".to_string(),
+                    pali1s: vec!["panna 1".to_string()],
+                    exact: true,
+                },
+                SearchResult {
+                    word: "paññā".to_string(),
+                    pali1s: vec!["paññā".to_string(), "paññā 1".to_string()],
+                    exact: false,
+                },
+                SearchResult {
+                    word: "pannā".to_string(),
+                    pali1s: vec!["panna 1".to_string()],
+                    exact: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_words_empty_query_test() {
+        assert_eq!(
+            search_words(" ", 10, &test_host::DbHost::new(SEARCH_DB)),
+            Ok(Vec::new())
+        );
+    }
+}
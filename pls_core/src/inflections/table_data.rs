@@ -0,0 +1,152 @@
+use crate::alphabet::string_compare;
+use crate::inflections::host::{block_on, PlsInflectionsHost, PlsInflectionsHostAsync, SyncHost};
+use crate::inflections::pmd::{get_pali1_metadata_async, InflectionClass, Pali1Metadata, WordType};
+use crate::inflections::{get_pattern_table_columns, get_table_name_from_pattern};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One cell of an inflection table, e.g. case = nom, gender = masc, number = sg. The cell for
+/// use in compounds has all parameters empty.
+#[derive(Debug, Serialize)]
+pub struct InflectionCell {
+    pub parameters: BTreeMap<String, String>,
+    pub inflections: Vec<String>,
+}
+
+/// The inflection table as data rather than html. The inflections are not transliterated.
+#[derive(Debug, Serialize)]
+pub struct InflectionTableData {
+    pub metadata: Pali1Metadata,
+    pub cells: Vec<InflectionCell>,
+}
+
+pub fn generate_inflection_table_data(
+    pali1: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<InflectionTableData, String> {
+    block_on(generate_inflection_table_data_async(pali1, &SyncHost(host)))
+}
+
+pub async fn generate_inflection_table_data_async(
+    pali1: &str,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<InflectionTableData, String> {
+    let metadata = get_pali1_metadata_async(pali1, host).await?;
+    let cells = match &metadata.word_type {
+        WordType::InflectedForm { stems: _ } => vec![],
+        WordType::Indeclinable { stem } => vec![InflectionCell {
+            parameters: BTreeMap::new(),
+            inflections: vec![stem.to_owned()],
+        }],
+        WordType::Irregular {
+            pattern,
+            inflection_class,
+        } => get_inflection_cells("", pattern, inflection_class, host).await?,
+        WordType::Declinable {
+            stem,
+            pattern,
+            inflection_class,
+        } => get_inflection_cells(stem, pattern, inflection_class, host).await?,
+    };
+
+    Ok(InflectionTableData { metadata, cells })
+}
+
+async fn get_inflection_cells(
+    stem: &str,
+    pattern: &str,
+    inflection_class: &InflectionClass,
+    host: &impl PlsInflectionsHostAsync,
+) -> Result<Vec<InflectionCell>, String> {
+    let columns = get_pattern_table_columns(inflection_class);
+    let sql = format!(
+        "SELECT {} FROM '{}'",
+        columns
+            .iter()
+            .map(|c| format!(r#""{}""#, c))
+            .collect::<Vec<String>>()
+            .join(", "),
+        get_table_name_from_pattern(pattern)
+    );
+
+    let rows = host
+        .exec_sql_query_async(&sql)
+        .await?
+        .pop()
+        .ok_or_else(|| format!("No pattern found for {}", pattern))?;
+
+    Ok(create_inflection_cells(stem, columns, rows))
+}
+
+fn create_inflection_cells(
+    stem: &str,
+    columns: &[&str],
+    rows: Vec<Vec<String>>,
+) -> Vec<InflectionCell> {
+    rows.into_iter()
+        .filter_map(|row| {
+            let mut parameters = BTreeMap::new();
+            let mut inflections = Vec::new();
+            for (&column, value) in columns.iter().zip(row) {
+                if column == "inflections" {
+                    inflections = value
+                        .split(',')
+                        .filter(|s| !s.is_empty())
+                        .map(|s| format!("{}{}", stem, s))
+                        .collect();
+                } else {
+                    parameters.insert(column.to_string(), value);
+                }
+            }
+
+            if inflections.is_empty() {
+                return None;
+            }
+
+            inflections.sort_by(|a, b| Ord::cmp(&string_compare(a, b), &0));
+            Some(InflectionCell {
+                parameters,
+                inflections,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_inflection_cells_test() {
+        let rows = vec![
+            vec!["nom", "masc", "sg", "o"],
+            vec!["nom", "masc", "pl", "ā,āse"],
+            vec!["voc", "masc", "sg", ""],
+            vec!["", "", "", "a"],
+        ]
+        .into_iter()
+        .map(|r| r.into_iter().map(|c| c.to_string()).collect())
+        .collect();
+
+        let cells =
+            create_inflection_cells("dhamm", &["case", "gender", "number", "inflections"], rows);
+
+        let cells: Vec<(Vec<&str>, Vec<&str>)> = cells
+            .iter()
+            .map(|c| {
+                (
+                    c.parameters.values().map(|x| x.as_str()).collect(),
+                    c.inflections.iter().map(|x| x.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            cells,
+            vec![
+                (vec!["nom", "masc", "sg"], vec!["dhammo"]),
+                (vec!["nom", "masc", "pl"], vec!["dhammā", "dhammāse"]),
+                (vec!["", "", ""], vec!["dhamma"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::alphabet::{letters_with_offsets, Character, Letter, PaliAlphabet};
+use crate::tokenizer::{tokenize, TokenKind};
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// The recitation traditions, e.g. how ṃ, the retroflexes and the voiced aspirates are said.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Profile {
+    /// The pronunciation the grammarians describe, keeping every contrast of the alphabet.
+    Reconstructed,
+    SriLankan,
+    Burmese,
+    Thai,
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reconstructed" | "standard" => Ok(Profile::Reconstructed),
+            "sri-lankan" | "sinhala" => Ok(Profile::SriLankan),
+            "burmese" | "myanmar" => Ok(Profile::Burmese),
+            "thai" => Ok(Profile::Thai),
+            _ => Err(format!("Unknown pronunciation profile '{}'.", s)),
+        }
+    }
+}
+
+/// A syllable of a word, in Roman script as written and in IPA.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Syllable {
+    pub roman: String,
+    pub ipa: String,
+}
+
+struct ProfileTable {
+    /// The sounds of the letters starting a syllable, indexed by PaliAlphabet. e and o are long.
+    phones: [&'static str; 41],
+    /// The sounds of the consonants closing a syllable, when they differ, indexed by PaliAlphabet.
+    codas: Option<[&'static str; 41]>,
+    /// ṃ is said as the nasal of a following stop, e.g. m in saṃbuddha.
+    homorganic_niggahita: bool,
+    /// e and o are short in closed syllables, e.g. in mettā.
+    short_closed_e_o: bool,
+}
+
+const RECONSTRUCTED: ProfileTable = ProfileTable {
+    phones: [
+        "a", "aː", "i", "iː", "u", "uː", "eː", "oː", // vowels
+        "k", "kʰ", "ɡ", "ɡʱ", "ŋ", "c", "cʰ", "ɟ", "ɟʱ", "ɲ", "ʈ", "ʈʰ", "ɖ", "ɖʱ", "ɳ", "t̪", "t̪ʰ",
+        "d̪", "d̪ʱ", "n̪", "p", "pʰ", "b", "bʱ", "m", "j", "r", "l", "ʋ", "s", "h", "ɭ", "ŋ",
+    ],
+    codas: None,
+    homorganic_niggahita: true,
+    short_closed_e_o: true,
+};
+
+/// ṃ is always ŋ and the palatals are affricates.
+const SRI_LANKAN: ProfileTable = ProfileTable {
+    phones: [
+        "a", "aː", "i", "iː", "u", "uː", "eː", "oː", // vowels
+        "k", "kʰ", "ɡ", "ɡʱ", "ŋ", "tʃ", "tʃʰ", "dʒ", "dʒʱ", "ɲ", "ʈ", "ʈʰ", "ɖ", "ɖʱ", "ɳ", "t̪",
+        "t̪ʰ", "d̪", "d̪ʱ", "n", "p", "pʰ", "b", "bʱ", "m", "j", "r", "l", "ʋ", "s", "h", "ɭ", "ŋ",
+    ],
+    codas: None,
+    homorganic_niggahita: false,
+    short_closed_e_o: true,
+};
+
+/// Vowel length is said as tone and is not kept. The retroflexes are dental, the voiced aspirates
+/// unaspirated, c and j are s and z, s is θ and r is j. Stops closing a syllable are ʔ and nasals
+/// ɴ.
+const BURMESE: ProfileTable = ProfileTable {
+    phones: [
+        "a", "a", "i", "i", "u", "u", "e", "o", // vowels
+        "k", "kʰ", "ɡ", "ɡ", "ŋ", "s", "sʰ", "z", "z", "ɲ", "t", "tʰ", "d", "d", "n", "t", "tʰ",
+        "d", "d", "n", "p", "pʰ", "b", "b", "m", "j", "j", "l", "w", "θ", "h", "l", "ɴ",
+    ],
+    codas: Some([
+        "", "", "", "", "", "", "", "", // vowels
+        "ʔ", "ʔ", "ʔ", "ʔ", "ɴ", "ʔ", "ʔ", "ʔ", "ʔ", "ɴ", "ʔ", "ʔ", "ʔ", "ʔ", "ɴ", "ʔ", "ʔ", "ʔ",
+        "ʔ", "ɴ", "ʔ", "ʔ", "ʔ", "ʔ", "ɴ", "j", "j", "l", "w", "θ", "h", "l", "ɴ",
+    ]),
+    homorganic_niggahita: false,
+    short_closed_e_o: false,
+};
+
+/// The voiced stops and aspirates are voiceless aspirates, as the Thai letters for them are read,
+/// e.g. pʰut̚.tʰoː for buddho. Stops closing a syllable are unreleased k̚, t̚ or p̚.
+const THAI: ProfileTable = ProfileTable {
+    phones: [
+        "a", "aː", "i", "iː", "u", "uː", "eː", "oː", // vowels
+        "k", "kʰ", "kʰ", "kʰ", "ŋ", "tɕ", "tɕʰ", "tɕʰ", "tɕʰ", "j", "t", "tʰ", "tʰ", "tʰ", "n",
+        "t", "tʰ", "tʰ", "tʰ", "n", "p", "pʰ", "pʰ", "pʰ", "m", "j", "r", "l", "w", "s", "h", "l",
+        "ŋ",
+    ],
+    codas: Some([
+        "", "", "", "", "", "", "", "", // vowels
+        "k̚", "k̚", "k̚", "k̚", "ŋ", "t̚", "t̚", "t̚", "t̚", "n", "t̚", "t̚", "t̚", "t̚", "n", "t̚", "t̚", "t̚",
+        "t̚", "n", "p̚", "p̚", "p̚", "p̚", "m", "j", "n", "n", "w", "t̚", "", "n", "ŋ",
+    ]),
+    homorganic_niggahita: true,
+    short_closed_e_o: false,
+};
+
+fn profile_table(profile: Profile) -> &'static ProfileTable {
+    match profile {
+        Profile::Reconstructed => &RECONSTRUCTED,
+        Profile::SriLankan => &SRI_LANKAN,
+        Profile::Burmese => &BURMESE,
+        Profile::Thai => &THAI,
+    }
+}
+
+/// Transcribes the words of a text in Roman script to IPA, keeping everything else as is. Syllables
+/// are separated by "." if syllable_boundaries is set. Elided forms are read with the word before
+/// them, e.g. "sutan’ti" as one word.
+pub fn transcribe(text: &str, profile: Profile, syllable_boundaries: bool) -> String {
+    let separator = if syllable_boundaries { "." } else { "" };
+    let mut output = String::with_capacity(text.len() * 2);
+    let mut word = String::new();
+    for token in tokenize(text) {
+        if matches!(token.kind, TokenKind::Word | TokenKind::Elided) {
+            word.push_str(token.text);
+            continue;
+        }
+
+        push_word(&mut output, &mut word, profile, separator);
+        output.push_str(token.text);
+    }
+    push_word(&mut output, &mut word, profile, separator);
+
+    output
+}
+
+fn push_word(output: &mut String, word: &mut String, profile: Profile, separator: &str) {
+    if !word.is_empty() {
+        let syllables: Vec<String> = transcribe_word(word, profile)
+            .into_iter()
+            .map(|s| s.ipa)
+            .collect();
+        output.push_str(&syllables.join(separator));
+        word.clear();
+    }
+}
+
+/// The syllables of a word with their IPA. A consonant between vowels starts a syllable, and the
+/// first of a cluster closes the one before, e.g. bud.dho, in.dri.yaṃ. ṃ always closes a syllable.
+pub fn transcribe_word(word: &str, profile: Profile) -> Vec<Syllable> {
+    let table = profile_table(profile);
+    let word: String = word.chars().filter(|c| !matches!(c, '\'' | '’')).collect();
+    let letters = letters_with_offsets(&word);
+    let chars: Vec<char> = word.chars().collect();
+
+    let starts = syllable_starts(&letters);
+    let ends = starts.iter().skip(1).copied().chain(Some(letters.len()));
+    starts
+        .iter()
+        .zip(ends)
+        .filter(|&(&start, end)| start < end)
+        .map(|(&start, end)| {
+            let syllable = &letters[start..end];
+            let is_closed = syllable.last().is_some_and(|l| is_consonant(l.character));
+            let nucleus = syllable.iter().position(|l| is_vowel(l.character));
+            let ipa = syllable
+                .iter()
+                .enumerate()
+                .map(|(i, l)| {
+                    let is_coda = nucleus.is_some_and(|n| i > n);
+                    let next = letters.get(start + i + 1).map(|l| l.character);
+                    phone(table, l.character, next, is_coda, is_closed)
+                })
+                .collect();
+
+            Syllable {
+                roman: chars[syllable[0].char_start..syllable[syllable.len() - 1].char_end]
+                    .iter()
+                    .collect(),
+                ipa,
+            }
+        })
+        .collect()
+}
+
+fn phone(
+    table: &ProfileTable,
+    character: Character,
+    next: Option<Character>,
+    is_coda: bool,
+    is_closed: bool,
+) -> String {
+    let c = match character {
+        Character::Pali(c) => c,
+        Character::Other(c) => return c.to_string(),
+    };
+
+    let c = match next {
+        Some(Character::Pali(next)) if c == PaliAlphabet::DotM && table.homorganic_niggahita => {
+            homorganic_nasal(next).unwrap_or(c)
+        }
+        _ => c,
+    };
+    let phones = table
+        .codas
+        .as_ref()
+        .filter(|_| is_coda)
+        .unwrap_or(&table.phones);
+    let phone = phones[c as usize];
+    if table.short_closed_e_o && is_closed && matches!(c, PaliAlphabet::E | PaliAlphabet::O) {
+        phone.trim_end_matches('ː').to_string()
+    } else {
+        phone.to_string()
+    }
+}
+
+/// The letters starting each syllable.
+fn syllable_starts(letters: &[Letter]) -> Vec<usize> {
+    let vowels: Vec<usize> = (0..letters.len())
+        .filter(|&i| is_vowel(letters[i].character))
+        .collect();
+
+    let mut starts = vec![0];
+    for pair in vowels.windows(2) {
+        let (vowel, next_vowel) = (pair[0], pair[1]);
+        let start = match next_vowel - vowel - 1 {
+            1 if letters[vowel + 1].character == Character::Pali(PaliAlphabet::DotM) => next_vowel,
+            0 | 1 => vowel + 1,
+            _ => vowel + 2,
+        };
+        starts.push(start);
+    }
+
+    starts
+}
+
+fn is_vowel(c: Character) -> bool {
+    matches!(c, Character::Pali(c) if c <= PaliAlphabet::O)
+}
+
+fn is_consonant(c: Character) -> bool {
+    matches!(c, Character::Pali(c) if c > PaliAlphabet::O)
+}
+
+/// The nasal of the class of a stop, e.g. ṅ for g.
+fn homorganic_nasal(c: PaliAlphabet) -> Option<PaliAlphabet> {
+    if !(PaliAlphabet::K..=PaliAlphabet::M).contains(&c) {
+        return None;
+    }
+
+    let class_start = PaliAlphabet::K as usize + (c as usize - PaliAlphabet::K as usize) / 5 * 5;
+    PaliAlphabet::try_from(class_start + 4).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("buddho", Profile::Reconstructed => "bud̪.d̪ʱoː")]
+    #[test_case("saṃgha", Profile::Reconstructed => "saŋ.ɡʱa"; "niggahita before a guttural")]
+    #[test_case("Saṁbuddho", Profile::Reconstructed => "sam.bud̪.d̪ʱoː"; "niggahita before a labial")]
+    #[test_case("mettā", Profile::Reconstructed => "met̪.t̪aː"; "e short in closed syllable")]
+    #[test_case("indriyaṃ", Profile::Reconstructed => "in̪.d̪ri.jaŋ")]
+    #[test_case("saṃbuddho", Profile::SriLankan => "saŋ.bud̪.d̪ʱoː")]
+    #[test_case("gacchāmi", Profile::SriLankan => "ɡatʃ.tʃʰaː.mi")]
+    #[test_case("buddhaṃ", Profile::Burmese => "buʔ.daɴ")]
+    #[test_case("saraṇaṃ", Profile::Burmese => "θa.ja.naɴ")]
+    #[test_case("buddho", Profile::Thai => "pʰut̚.tʰoː")]
+    #[test_case("saṃbuddha", Profile::Thai => "sam.pʰut̚.tʰa")]
+    #[test_case("mettā", Profile::Thai => "meːt̚.taː"; "e long in closed syllable")]
+    #[test_case("brahmā", Profile::Thai => "pʰra.maː"; "h closing a syllable is silent")]
+    #[test_case("aīa", Profile::Reconstructed => "a.iː.a"; "vowels in hiatus")]
+    fn transcribe_word_tests(word: &str, profile: Profile) -> String {
+        transcribe_word(word, profile)
+            .iter()
+            .map(|s| s.ipa.as_str())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    #[test_case("Buddhaṃ saraṇaṃ gacchāmi.", Profile::Thai, true => "pʰut̚.tʰaŋ sa.ra.naŋ kʰat̚.tɕʰaː.mi.")]
+    #[test_case("Evaṃ me sutaṃ – sutan’ti", Profile::Reconstructed, false => "eːʋaŋ meː sut̪aŋ – sut̪an̪t̪i")]
+    #[test_case("dhammaṃ 12", Profile::Burmese, true => "daɴ.maɴ 12")]
+    fn transcribe_tests(text: &str, profile: Profile, syllable_boundaries: bool) -> String {
+        transcribe(text, profile, syllable_boundaries)
+    }
+
+    #[test]
+    fn transcribe_word_syllables_test() {
+        insta::assert_yaml_snapshot!(transcribe_word("Paṭiccasamuppādo", Profile::Reconstructed));
+    }
+
+    #[test_case("sri-lankan" => Ok(Profile::SriLankan))]
+    #[test_case("myanmar" => Ok(Profile::Burmese))]
+    #[test_case("pali" => Err("Unknown pronunciation profile 'pali'.".to_string()))]
+    fn profile_from_str_tests(s: &str) -> Result<Profile, String> {
+        Profile::from_str(s)
+    }
+}
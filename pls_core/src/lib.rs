@@ -3,4 +3,11 @@ extern crate lazy_static;
 extern crate serde_json;
 
 pub mod alphabet;
+pub mod diacritics;
 pub mod inflections;
+pub mod ipa;
+pub mod numerals;
+pub mod ocr;
+pub mod phonotactics;
+pub mod spelling;
+pub mod tokenizer;
@@ -0,0 +1,623 @@
+use serde::Serialize;
+
+/// The largest number spelled, 99 koṭi and the rest.
+pub const MAX_NUMERAL: u64 = 999_999_999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumeralKind {
+    Cardinal,
+    Ordinal,
+}
+
+/// A word of a spelled number with its value and the head word it declines like, e.g. chabbīsati
+/// declines like vīsati and dvisata like sata.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NumeralWord {
+    pub text: String,
+    pub head: String,
+    pub value: u64,
+}
+
+/// A number read from a Pāli numeral phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ParsedNumeral {
+    pub value: u64,
+    pub kind: NumeralKind,
+}
+
+const UNITS: [(&str, &str); 10] = [
+    ("eka", "eka"),
+    ("dve", "dvi"),
+    ("ti", "ti"),
+    ("catu", "catu"),
+    ("pañca", "pañca"),
+    ("cha", "cha"),
+    ("satta", "satta"),
+    ("aṭṭha", "aṭṭha"),
+    ("nava", "nava"),
+    ("dasa", "dasa"),
+];
+
+/// The units as the first member of a compound, e.g. dvi in dvisata.
+const UNIT_PREFIXES: [&str; 9] = [
+    "",
+    "dvi",
+    "ti",
+    "catu",
+    "pañca",
+    "cha",
+    "satta",
+    "aṭṭha",
+    "nava",
+];
+
+const TEENS: [&str; 8] = [
+    "ekādasa",
+    "dvādasa",
+    "terasa",
+    "cuddasa",
+    "pañcadasa",
+    "soḷasa",
+    "sattarasa",
+    "aṭṭhārasa",
+];
+
+/// Per ten from vīsati to navuti: one less with ekūna, the ten, and the ten plus 1 to 8 with the
+/// sandhi of the units, e.g. dvattiṃsa, chaḷāsīti.
+const TENS: [[&str; 10]; 8] = [
+    [
+        "ekūnavīsati",
+        "vīsati",
+        "ekavīsati",
+        "dvāvīsati",
+        "tevīsati",
+        "catuvīsati",
+        "pañcavīsati",
+        "chabbīsati",
+        "sattavīsati",
+        "aṭṭhavīsati",
+    ],
+    [
+        "ekūnatiṃsa",
+        "tiṃsa",
+        "ekatiṃsa",
+        "dvattiṃsa",
+        "tettiṃsa",
+        "catuttiṃsa",
+        "pañcatiṃsa",
+        "chattiṃsa",
+        "sattatiṃsa",
+        "aṭṭhatiṃsa",
+    ],
+    [
+        "ekūnacattālīsa",
+        "cattālīsa",
+        "ekacattālīsa",
+        "dvecattālīsa",
+        "tecattālīsa",
+        "catucattālīsa",
+        "pañcacattālīsa",
+        "chacattālīsa",
+        "sattacattālīsa",
+        "aṭṭhacattālīsa",
+    ],
+    [
+        "ekūnapaññāsa",
+        "paññāsa",
+        "ekapaññāsa",
+        "dvepaññāsa",
+        "tepaññāsa",
+        "catupaññāsa",
+        "pañcapaññāsa",
+        "chapaññāsa",
+        "sattapaññāsa",
+        "aṭṭhapaññāsa",
+    ],
+    [
+        "ekūnasaṭṭhi",
+        "saṭṭhi",
+        "ekasaṭṭhi",
+        "dvāsaṭṭhi",
+        "tesaṭṭhi",
+        "catusaṭṭhi",
+        "pañcasaṭṭhi",
+        "chasaṭṭhi",
+        "sattasaṭṭhi",
+        "aṭṭhasaṭṭhi",
+    ],
+    [
+        "ekūnasattati",
+        "sattati",
+        "ekasattati",
+        "dvāsattati",
+        "tesattati",
+        "catusattati",
+        "pañcasattati",
+        "chasattati",
+        "sattasattati",
+        "aṭṭhasattati",
+    ],
+    [
+        "ekūnāsīti",
+        "asīti",
+        "ekāsīti",
+        "dvāsīti",
+        "tiyāsīti",
+        "caturāsīti",
+        "pañcāsīti",
+        "chaḷāsīti",
+        "sattāsīti",
+        "aṭṭhāsīti",
+    ],
+    [
+        "ekūnanavuti",
+        "navuti",
+        "ekanavuti",
+        "dvānavuti",
+        "tenavuti",
+        "catunavuti",
+        "pañcanavuti",
+        "channavuti",
+        "sattanavuti",
+        "aṭṭhanavuti",
+    ],
+];
+
+const NINETY_NINE: &str = "ekūnasata";
+
+/// The powers of ten with a word, largest first, and the head words they decline like.
+const MULTIPLIERS: [(&str, u64, &str); 4] = [
+    ("koṭi", 10_000_000, "koṭi"),
+    ("satasahassa", 100_000, "sahassa"),
+    ("sahassa", 1_000, "sahassa"),
+    ("sata", 100, "sata"),
+];
+
+const ORDINALS: [&str; 10] = [
+    "paṭhama",
+    "dutiya",
+    "tatiya",
+    "catuttha",
+    "pañcama",
+    "chaṭṭha",
+    "sattama",
+    "aṭṭhama",
+    "navama",
+    "dasama",
+];
+
+/// Ordinals past dasama add ma to the cardinal and decline like dasama.
+const ORDINAL_SUFFIX: &str = "ma";
+
+/// Spells the number as Pāli words, e.g. 1250 as sahassa dvisata paññāsa. Only the last word of an
+/// ordinal is an ordinal, e.g. sata pañcama for 105th.
+pub fn spell_numeral(n: u64, kind: NumeralKind) -> Result<Vec<NumeralWord>, String> {
+    if n == 0 {
+        return Err("There is no Pāli numeral for 0.".to_string());
+    }
+    if n > MAX_NUMERAL {
+        return Err(format!(
+            "{} is too large for a Pāli numeral, the largest is {}.",
+            n, MAX_NUMERAL
+        ));
+    }
+
+    let mut words = Vec::new();
+    let mut rest = n;
+    for &(multiplier, power, head) in MULTIPLIERS.iter() {
+        let count = rest / power;
+        rest %= power;
+        if count == 0 {
+            continue;
+        }
+
+        let prefix = match count {
+            1 => String::new(),
+            2..=9 => UNIT_PREFIXES[count as usize - 1].to_string(),
+            _ => spell_below_hundred(count).text,
+        };
+        words.push(NumeralWord {
+            text: format!("{}{}", prefix, multiplier),
+            head: head.to_string(),
+            value: count * power,
+        });
+    }
+    if rest > 0 {
+        words.push(spell_below_hundred(rest));
+    }
+
+    if kind == NumeralKind::Ordinal {
+        if let Some(last) = words.last_mut() {
+            *last = to_ordinal(last);
+        }
+    }
+
+    Ok(words)
+}
+
+/// The cardinal as one string, e.g. "sahassa dvisata paññāsa".
+pub fn cardinal(n: u64) -> Result<String, String> {
+    spell_numeral(n, NumeralKind::Cardinal).map(|words| join_words(&words))
+}
+
+/// The ordinal as one string, e.g. "dvisata vīsatima".
+pub fn ordinal(n: u64) -> Result<String, String> {
+    spell_numeral(n, NumeralKind::Ordinal).map(|words| join_words(&words))
+}
+
+fn join_words(words: &[NumeralWord]) -> String {
+    words
+        .iter()
+        .map(|w| w.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn spell_below_hundred(n: u64) -> NumeralWord {
+    let (text, head) = match n {
+        1..=10 => UNITS[n as usize - 1],
+        11..=18 => (TEENS[n as usize - 11], "dasa"),
+        19..=98 => {
+            let ten = (n + 1) / 10;
+            let row = &TENS[ten as usize - 2];
+            (row[(n + 1 - ten * 10) as usize], row[1])
+        }
+        _ => (NINETY_NINE, "sata"),
+    };
+
+    NumeralWord {
+        text: text.to_string(),
+        head: head.to_string(),
+        value: n,
+    }
+}
+
+fn to_ordinal(word: &NumeralWord) -> NumeralWord {
+    let text = match word.value {
+        1..=10 => ORDINALS[word.value as usize - 1].to_string(),
+        _ => format!("{}{}", word.text, ORDINAL_SUFFIX),
+    };
+
+    NumeralWord {
+        head: if word.value <= 10 {
+            text.clone()
+        } else {
+            "dasama".to_string()
+        },
+        text,
+        value: word.value,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Morpheme {
+    Number(u64),
+    Multiplier(u64),
+    /// uttara, adhika: the number before is added to the one after, e.g. aṭṭhuttarasata is 108.
+    Connector,
+    Ordinal(u64),
+    OrdinalSuffix,
+}
+
+/// Declined forms of 2 to 4 that are not the stem and an ending.
+const IRREGULAR_FORMS: [(&str, u64); 27] = [
+    ("duve", 2),
+    ("dvinnaṃ", 2),
+    ("duvinnaṃ", 2),
+    ("dvīhi", 2),
+    ("dvībhi", 2),
+    ("dvīsu", 2),
+    ("tayo", 3),
+    ("tīṇi", 3),
+    ("tisso", 3),
+    ("tiṇṇaṃ", 3),
+    ("tiṇṇannaṃ", 3),
+    ("tissannaṃ", 3),
+    ("tīhi", 3),
+    ("tībhi", 3),
+    ("tīsu", 3),
+    ("cattāro", 4),
+    ("caturo", 4),
+    ("cattāri", 4),
+    ("catasso", 4),
+    ("catunnaṃ", 4),
+    ("catassannaṃ", 4),
+    ("catūhi", 4),
+    ("catūbhi", 4),
+    ("catūsu", 4),
+    ("catassohi", 4),
+    ("catassobhi", 4),
+    ("catassosu", 4),
+];
+
+/// Other spellings of the teens and tens.
+const VARIANTS: [(&str, u64); 14] = [
+    ("ekārasa", 11),
+    ("bārasa", 12),
+    ("dvārasa", 12),
+    ("tedasa", 13),
+    ("catuddasa", 14),
+    ("pannarasa", 15),
+    ("paṇṇarasa", 15),
+    ("sattadasa", 17),
+    ("aṭṭhādasa", 18),
+    ("vīsa", 20),
+    ("tiṃsati", 30),
+    ("cattārīsa", 40),
+    ("cattāḷīsa", 40),
+    ("paṇṇāsa", 50),
+];
+
+const CONNECTORS: [&str; 3] = ["uttara", "adhika", "ādhika"];
+
+/// The half numerals, e.g. diyaḍḍha for 1½ and aḍḍhatelasa for 12½.
+const HALF_PREFIXES: [&str; 2] = ["aḍḍh", "diyaḍḍh"];
+
+/// The endings after the stem vowel is dropped, e.g. satāni, vīsatiyā.
+const ENDINGS_AFTER_STEM: [&str; 36] = [
+    "a", "ā", "aṃ", "o", "e", "ena", "ehi", "ebhi", "assa", "āya", "ānaṃ", "annaṃ", "amhā", "asmā",
+    "amhi", "asmiṃ", "esu", "āni", "āyo", "āyaṃ", "i", "ī", "iṃ", "iyā", "iyo", "iyaṃ", "īhi",
+    "ībhi", "īnaṃ", "īsu", "ahi", "abhi", "asu", "u", "ū", "ūnaṃ",
+];
+
+/// The endings after the full stem, e.g. pañcannaṃ, koṭiyo.
+const ENDINGS_AFTER_VOWEL: [&str; 10] =
+    ["", "ṃ", "nnaṃ", "hi", "bhi", "su", "yā", "yo", "yaṃ", "ssa"];
+
+lazy_static! {
+    /// The stems of the numerals, longest first so that e.g. sattati is read before satta.
+    static ref LEXICON: Vec<(String, Morpheme)> = {
+        let mut lexicon: Vec<(String, Morpheme)> = Vec::new();
+        for n in 1..=99 {
+            lexicon.push((spell_below_hundred(n).text, Morpheme::Number(n)));
+        }
+        for (n, &prefix) in UNIT_PREFIXES.iter().enumerate().skip(1) {
+            lexicon.push((prefix.to_string(), Morpheme::Number(n as u64 + 1)));
+        }
+        for &(variant, n) in VARIANTS.iter() {
+            lexicon.push((variant.to_string(), Morpheme::Number(n)));
+        }
+        for &(multiplier, power, _) in MULTIPLIERS.iter().filter(|(_, p, _)| *p != 100_000) {
+            lexicon.push((multiplier.to_string(), Morpheme::Multiplier(power)));
+        }
+        lexicon.push(("lakkha".to_string(), Morpheme::Multiplier(100_000)));
+        for &connector in CONNECTORS.iter() {
+            lexicon.push((connector.to_string(), Morpheme::Connector));
+        }
+        for (n, &ordinal) in ORDINALS.iter().enumerate() {
+            lexicon.push((ordinal.to_string(), Morpheme::Ordinal(n as u64 + 1)));
+        }
+        lexicon.push(("turiya".to_string(), Morpheme::Ordinal(4)));
+        lexicon.push((ORDINAL_SUFFIX.to_string(), Morpheme::OrdinalSuffix));
+
+        lexicon.sort_by_key(|(form, _)| std::cmp::Reverse(form.chars().count()));
+        lexicon
+    };
+}
+
+/// Reads a Pāli numeral phrase in any case, e.g. "pañca satāni" as 500, "aṭṭhuttarasataṃ" as 108
+/// and "catutthaṃ" as the 4th. ca between the words is skipped. The half numerals, e.g.
+/// aḍḍhatelasa, are not read.
+pub fn parse_numeral(phrase: &str) -> Result<ParsedNumeral, String> {
+    let phrase = phrase.to_lowercase().replace('ṁ', "ṃ");
+    let words: Vec<&str> = phrase
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .map(|w| w.trim_matches(|c: char| c.is_ascii_punctuation() || c == '’'))
+        .filter(|w| !w.is_empty() && *w != "ca")
+        .collect();
+    if words.is_empty() {
+        return Err(format!("'{}' is not a Pāli numeral.", phrase.trim()));
+    }
+
+    let mut kind = NumeralKind::Cardinal;
+    let mut total: u64 = 0;
+    let mut group: u64 = 0;
+    for word in words {
+        let morphemes = parse_morphemes(word, true, false).ok_or_else(|| {
+            if HALF_PREFIXES.iter().any(|p| word.starts_with(p)) {
+                format!("Half numerals such as '{}' are not supported.", word)
+            } else {
+                format!("'{}' is not a Pāli numeral word.", word)
+            }
+        })?;
+        if morphemes
+            .iter()
+            .any(|m| matches!(m, Morpheme::Ordinal(_) | Morpheme::OrdinalSuffix))
+        {
+            kind = NumeralKind::Ordinal;
+        }
+
+        let value = evaluate_morphemes(&morphemes)
+            .ok_or_else(|| format!("'{}' is too large a Pāli numeral.", word))?;
+        // A word of a bare power multiplies the smaller number before it, e.g. pañca satāni.
+        if matches!(morphemes[0], Morpheme::Multiplier(_)) && group > 0 && group < value {
+            group = group.checked_mul(value).ok_or("Numeral is too large.")?;
+        } else {
+            total = total.checked_add(group).ok_or("Numeral is too large.")?;
+            group = value;
+        }
+    }
+
+    Ok(ParsedNumeral {
+        value: total.checked_add(group).ok_or("Numeral is too large.")?,
+        kind,
+    })
+}
+
+/// Splits the word into numeral stems and a final ending. A stem may drop its final vowel before
+/// a vowel, e.g. aṭṭh-uttara, or before an ending, e.g. sat-āni.
+fn parse_morphemes(rest: &str, at_start: bool, truncated: bool) -> Option<Vec<Morpheme>> {
+    if !at_start {
+        let endings: &[&str] = if truncated {
+            &ENDINGS_AFTER_STEM
+        } else {
+            &ENDINGS_AFTER_VOWEL
+        };
+        if endings.contains(&rest) {
+            return Some(Vec::new());
+        }
+    }
+
+    if at_start {
+        if let Some(&(_, n)) = IRREGULAR_FORMS.iter().find(|(form, _)| *form == rest) {
+            return Some(vec![Morpheme::Number(n)]);
+        }
+    }
+
+    for (form, morpheme) in LEXICON.iter() {
+        if truncated && !form.starts_with(is_vowel) {
+            continue;
+        }
+
+        let last_char_len = form.chars().last().map_or(0, char::len_utf8);
+        let candidates = [
+            (form.as_str(), false),
+            (&form[..form.len() - last_char_len], true),
+        ];
+        for &(candidate, drops_vowel) in candidates.iter() {
+            if candidate.is_empty() || !rest.starts_with(candidate) {
+                continue;
+            }
+            if drops_vowel && !form.ends_with(is_vowel) {
+                continue;
+            }
+            if let Some(mut tail) = parse_morphemes(&rest[candidate.len()..], false, drops_vowel) {
+                tail.insert(0, *morpheme);
+                return Some(tail);
+            }
+        }
+    }
+
+    None
+}
+
+fn evaluate_morphemes(morphemes: &[Morpheme]) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut current: u64 = 0;
+    for morpheme in morphemes {
+        match *morpheme {
+            Morpheme::Number(n) | Morpheme::Ordinal(n) => current = current.checked_add(n)?,
+            Morpheme::Multiplier(power) => current = current.max(1).checked_mul(power)?,
+            Morpheme::Connector => {
+                total = total.checked_add(current)?;
+                current = 0;
+            }
+            Morpheme::OrdinalSuffix => {}
+        }
+    }
+
+    total.checked_add(current)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'ā' | 'i' | 'ī' | 'u' | 'ū' | 'e' | 'o')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(1 => "eka")]
+    #[test_case(2 => "dve")]
+    #[test_case(10 => "dasa")]
+    #[test_case(16 => "soḷasa")]
+    #[test_case(19 => "ekūnavīsati")]
+    #[test_case(26 => "chabbīsati")]
+    #[test_case(32 => "dvattiṃsa")]
+    #[test_case(33 => "tettiṃsa")]
+    #[test_case(84 => "caturāsīti")]
+    #[test_case(86 => "chaḷāsīti")]
+    #[test_case(99 => "ekūnasata")]
+    #[test_case(100 => "sata")]
+    #[test_case(227 => "dvisata sattavīsati")]
+    #[test_case(1250 => "sahassa dvisata paññāsa")]
+    #[test_case(84_000 => "caturāsītisahassa")]
+    #[test_case(500_000 => "pañcasatasahassa")]
+    #[test_case(10_000_000 => "koṭi")]
+    #[test_case(MAX_NUMERAL => "ekūnasatakoṭi ekūnasatasatasahassa ekūnasatasahassa navasata ekūnasata")]
+    fn cardinal_tests(n: u64) -> String {
+        cardinal(n).unwrap()
+    }
+
+    #[test_case(1 => "paṭhama")]
+    #[test_case(4 => "catuttha")]
+    #[test_case(11 => "ekādasama")]
+    #[test_case(20 => "vīsatima")]
+    #[test_case(100 => "satama")]
+    #[test_case(105 => "sata pañcama")]
+    fn ordinal_tests(n: u64) -> String {
+        ordinal(n).unwrap()
+    }
+
+    #[test_case(0 => "There is no Pāli numeral for 0.")]
+    #[test_case(MAX_NUMERAL + 1 => "1000000000 is too large for a Pāli numeral, the largest is 999999999.")]
+    fn spell_numeral_error_tests(n: u64) -> String {
+        spell_numeral(n, NumeralKind::Cardinal).unwrap_err()
+    }
+
+    #[test]
+    fn spell_numeral_heads_test() {
+        let heads: Vec<(String, String, u64)> = spell_numeral(1_200_026, NumeralKind::Cardinal)
+            .unwrap()
+            .into_iter()
+            .map(|w| (w.text, w.head, w.value))
+            .collect();
+
+        assert_eq!(
+            heads,
+            vec![
+                (
+                    "dvādasasatasahassa".to_string(),
+                    "sahassa".to_string(),
+                    1_200_000
+                ),
+                ("chabbīsati".to_string(), "vīsati".to_string(), 26),
+            ]
+        );
+    }
+
+    #[test_case("pañca" => Ok((5, NumeralKind::Cardinal)))]
+    #[test_case("tayo" => Ok((3, NumeralKind::Cardinal)); "irregular form")]
+    #[test_case("pañcannaṃ" => Ok((5, NumeralKind::Cardinal)); "ending after the stem vowel")]
+    #[test_case("pañca satāni" => Ok((500, NumeralKind::Cardinal)); "multiplier word")]
+    #[test_case("caturāsīti sahassāni" => Ok((84_000, NumeralKind::Cardinal)))]
+    #[test_case("aṭṭhuttarasataṃ" => Ok((108, NumeralKind::Cardinal)); "uttara")]
+    #[test_case("sattavīsādhikaṃ dvisataṃ" => Ok((227, NumeralKind::Cardinal)); "adhika")]
+    #[test_case("Ekūnavīsatiyā" => Ok((19, NumeralKind::Cardinal)); "case insensitive")]
+    #[test_case("sahassaṃ dve ca satāni paññāsaṃ" => Ok((1250, NumeralKind::Cardinal)); "ca")]
+    #[test_case("catutthaṃ" => Ok((4, NumeralKind::Ordinal)))]
+    #[test_case("vīsatimo" => Ok((20, NumeralKind::Ordinal)))]
+    #[test_case("dasa koṭiyo" => Ok((100_000_000, NumeralKind::Cardinal)))]
+    #[test_case("pañca bhikkhū" => Err("'bhikkhū' is not a Pāli numeral word.".to_string()); "not a numeral")]
+    #[test_case("aḍḍhatelasāni ca bhikkhusatāni" => Err("Half numerals such as 'aḍḍhatelasāni' are not supported.".to_string()); "half numeral")]
+    #[test_case("diyaḍḍhasataṃ" => Err("Half numerals such as 'diyaḍḍhasataṃ' are not supported.".to_string()); "diyaḍḍha")]
+    #[test_case(" , " => Err("',' is not a Pāli numeral.".to_string()); "no words")]
+    fn parse_numeral_tests(phrase: &str) -> Result<(u64, NumeralKind), String> {
+        parse_numeral(phrase).map(|p| (p.value, p.kind))
+    }
+
+    #[test]
+    fn parse_numeral_reads_small_numbers() {
+        for n in 1..=2000 {
+            assert_eq!(parse_numeral(&cardinal(n).unwrap()).map(|p| p.value), Ok(n));
+            assert_eq!(parse_numeral(&ordinal(n).unwrap()).map(|p| p.value), Ok(n));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn parse_numeral_reads_cardinals(n in 1..=MAX_NUMERAL) {
+            let parsed = parse_numeral(&cardinal(n).unwrap()).unwrap();
+            prop_assert_eq!(parsed, ParsedNumeral { value: n, kind: NumeralKind::Cardinal });
+        }
+
+        #[test]
+        fn parse_numeral_reads_ordinals(n in 1..=MAX_NUMERAL) {
+            let parsed = parse_numeral(&ordinal(n).unwrap()).unwrap();
+            prop_assert_eq!(parsed, ParsedNumeral { value: n, kind: NumeralKind::Ordinal });
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use crate::alphabet::sort_key;
+use crate::tokenizer::words;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::str::FromStr;
+
+/// The cost of a confusion that is all but certain, e.g. ṁ printed for ṃ.
+pub const CERTAIN_CONFUSION_COST: u32 = 1;
+/// The cost of a lost or added diacritic, e.g. m for ṃ.
+pub const DIACRITIC_CONFUSION_COST: u32 = 4;
+/// The cost of a misread glyph, e.g. rn for m.
+pub const GLYPH_CONFUSION_COST: u32 = 6;
+
+const MAX_EDITS: usize = 3;
+const MAX_STATES: usize = 20_000;
+
+/// A misreading of the OCR: the text it produces for the text printed, and its cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confusion {
+    pub read: String,
+    pub printed: String,
+    pub cost: u32,
+}
+
+/// The confusions the corrector may undo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfusionMatrix {
+    pub confusions: Vec<Confusion>,
+}
+
+impl Default for ConfusionMatrix {
+    /// The confusions of the PTS-era editions: lost or swapped diacritics, decomposed letters and
+    /// broken ligatures.
+    fn default() -> Self {
+        let mut confusions = Vec::new();
+        let mut add = |read: &str, printed: &str, cost: u32| {
+            confusions.push(Confusion {
+                read: read.to_string(),
+                printed: printed.to_string(),
+                cost,
+            })
+        };
+
+        add("ṁ", "ṃ", CERTAIN_CONFUSION_COST);
+        for (decomposed, letter) in &[
+            ("m\u{0323}", "ṃ"),
+            ("m\u{0307}", "ṃ"),
+            ("n\u{0323}", "ṇ"),
+            ("n\u{0307}", "ṅ"),
+            ("n\u{0303}", "ñ"),
+            ("t\u{0323}", "ṭ"),
+            ("d\u{0323}", "ḍ"),
+            ("l\u{0323}", "ḷ"),
+            ("a\u{0304}", "ā"),
+            ("i\u{0304}", "ī"),
+            ("u\u{0304}", "ū"),
+        ] {
+            add(decomposed, letter, CERTAIN_CONFUSION_COST);
+        }
+        for (plain, letter) in &[
+            ("m", "ṃ"),
+            ("l", "ḷ"),
+            ("n", "ñ"),
+            ("n", "ṇ"),
+            ("n", "ṅ"),
+            ("t", "ṭ"),
+            ("d", "ḍ"),
+            ("a", "ā"),
+            ("i", "ī"),
+            ("u", "ū"),
+        ] {
+            add(plain, letter, DIACRITIC_CONFUSION_COST);
+            add(letter, plain, DIACRITIC_CONFUSION_COST);
+        }
+        add("ii", "ī", DIACRITIC_CONFUSION_COST - 1);
+        add("uu", "ū", DIACRITIC_CONFUSION_COST - 1);
+        add("aa", "ā", DIACRITIC_CONFUSION_COST - 1);
+        add("rn", "m", GLYPH_CONFUSION_COST);
+        add("ri", "ṁ", GLYPH_CONFUSION_COST);
+        add("cl", "d", GLYPH_CONFUSION_COST);
+        add("li", "h", GLYPH_CONFUSION_COST);
+        add("c", "e", GLYPH_CONFUSION_COST);
+        add("e", "c", GLYPH_CONFUSION_COST);
+
+        ConfusionMatrix { confusions }
+    }
+}
+
+impl FromStr for ConfusionMatrix {
+    type Err = String;
+
+    /// One confusion per line: the text read, the text printed and the cost, separated by tabs.
+    /// Empty lines and lines starting with # are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut confusions = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                [read, printed, cost] if !read.is_empty() => confusions.push(Confusion {
+                    read: read.to_string(),
+                    printed: printed.to_string(),
+                    cost: cost.trim().parse().map_err(|e| {
+                        format!("Invalid cost '{}' on line {}: {}.", cost, i + 1, e)
+                    })?,
+                }),
+                _ => {
+                    return Err(format!(
+                        "Expected 'read<TAB>printed<TAB>cost' on line {}, found '{}'.",
+                        i + 1,
+                        line
+                    ))
+                }
+            }
+        }
+
+        Ok(ConfusionMatrix { confusions })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OcrEdit {
+    pub original: String,
+    pub corrected: String,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub cost: u32,
+    /// The other words within reach, cheapest first.
+    pub alternatives: Vec<String>,
+    /// Costly or ambiguous, for a human to check.
+    pub needs_review: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WordSpan {
+    pub text: String,
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OcrCorrection {
+    pub text: String,
+    pub edits: Vec<OcrEdit>,
+    /// Words neither in the vocabulary nor within reach of a word that is.
+    pub unknown_words: Vec<WordSpan>,
+}
+
+impl OcrCorrection {
+    pub fn edits_to_review(&self) -> impl Iterator<Item = &OcrEdit> {
+        self.edits.iter().filter(|e| e.needs_review)
+    }
+}
+
+/// Corrects the words of an OCR page to the cheapest vocabulary words the confusions reach.
+pub struct OcrCorrector {
+    vocabulary: HashSet<String>,
+    confusions: ConfusionMatrix,
+    /// Corrections costing more are not made.
+    pub max_cost: u32,
+    /// Corrections costing more are reviewed.
+    pub review_cost: u32,
+    /// Corrections with an alternative costing at most this much more are reviewed.
+    pub ambiguity_margin: u32,
+}
+
+impl OcrCorrector {
+    pub fn new<I, S>(vocabulary: I, confusions: ConfusionMatrix) -> OcrCorrector
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        OcrCorrector {
+            vocabulary: vocabulary
+                .into_iter()
+                .map(|w| w.as_ref().to_lowercase())
+                .collect(),
+            confusions,
+            max_cost: 3 * DIACRITIC_CONFUSION_COST,
+            review_cost: DIACRITIC_CONFUSION_COST,
+            ambiguity_margin: DIACRITIC_CONFUSION_COST / 2,
+        }
+    }
+
+    pub fn correct(&self, text: &str) -> OcrCorrection {
+        let mut corrected_text = String::with_capacity(text.len());
+        let mut edits = Vec::new();
+        let mut unknown_words = Vec::new();
+        let mut last_end = 0;
+        for token in words(text) {
+            corrected_text.push_str(&text[last_end..token.byte_start]);
+            last_end = token.byte_end;
+
+            // Elided forms keep their apostrophes, e.g. ’ti.
+            let is_apostrophe = |c| c == '\'' || c == '’';
+            let prefix_len = token.text.len() - token.text.trim_start_matches(is_apostrophe).len();
+            let core = token.text[prefix_len..].trim_end_matches(is_apostrophe);
+            let prefix = &token.text[..prefix_len];
+            let suffix = &token.text[prefix_len + core.len()..];
+            let word = core.to_lowercase();
+            if word.is_empty() || self.vocabulary.contains(&word) {
+                corrected_text.push_str(token.text);
+                continue;
+            }
+
+            let candidates = self.find_candidates(&word);
+            match candidates.first() {
+                Some((cost, best)) => {
+                    let corrected = format!("{}{}{}", prefix, match_case(core, best), suffix);
+                    corrected_text.push_str(&corrected);
+                    let ambiguous = candidates
+                        .get(1)
+                        .is_some_and(|(c, _)| *c <= cost + self.ambiguity_margin);
+                    edits.push(OcrEdit {
+                        original: token.text.to_string(),
+                        corrected,
+                        byte_start: token.byte_start,
+                        byte_end: token.byte_end,
+                        cost: *cost,
+                        alternatives: candidates.iter().skip(1).map(|(_, w)| w.clone()).collect(),
+                        needs_review: *cost > self.review_cost || ambiguous,
+                    });
+                }
+                None => {
+                    corrected_text.push_str(token.text);
+                    unknown_words.push(WordSpan {
+                        text: token.text.to_string(),
+                        byte_start: token.byte_start,
+                        byte_end: token.byte_end,
+                    });
+                }
+            }
+        }
+        corrected_text.push_str(&text[last_end..]);
+
+        OcrCorrection {
+            text: corrected_text,
+            edits,
+            unknown_words,
+        }
+    }
+
+    /// The vocabulary words the confusions turn the word into, cheapest first. The confusions are
+    /// undone left to right without overlapping, so each set of edits is tried once.
+    fn find_candidates(&self, word: &str) -> Vec<(u32, String)> {
+        let mut candidates: Vec<(u32, String)> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, 0, 0, word.to_string())));
+        let mut states = 0;
+
+        while let Some(Reverse((cost, edit_count, pos, candidate))) = queue.pop() {
+            states += 1;
+            if states > MAX_STATES {
+                break;
+            }
+            if cost > 0 && self.vocabulary.contains(&candidate) && seen.insert(candidate.clone()) {
+                candidates.push((cost, candidate.clone()));
+            }
+            if edit_count == MAX_EDITS {
+                continue;
+            }
+
+            for confusion in &self.confusions.confusions {
+                let new_cost = cost + confusion.cost;
+                if new_cost > self.max_cost {
+                    continue;
+                }
+                for (i, _) in candidate[pos..].match_indices(confusion.read.as_str()) {
+                    let at = pos + i;
+                    let mut next = String::with_capacity(candidate.len() + 4);
+                    next.push_str(&candidate[..at]);
+                    next.push_str(&confusion.printed);
+                    let next_pos = next.len();
+                    next.push_str(&candidate[at + confusion.read.len()..]);
+                    queue.push(Reverse((new_cost, edit_count + 1, next_pos, next)));
+                }
+            }
+        }
+
+        candidates.sort_by_cached_key(|(cost, w)| (*cost, sort_key(w)));
+        candidates
+    }
+}
+
+/// The correction capitalised as the original, e.g. Dhamma for Dharnma.
+fn match_case(original: &str, corrected: &str) -> String {
+    let mut chars = corrected.chars();
+    match (original.chars().next(), chars.next()) {
+        (Some(o), Some(c)) if o.is_uppercase() => c.to_uppercase().chain(chars).collect(),
+        _ => corrected.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn create_corrector() -> OcrCorrector {
+        OcrCorrector::new(
+            vec![
+                "dhammaṃ",
+                "dhamma",
+                "saṃgha",
+                "phala",
+                "phaḷa",
+                "paññā",
+                "bhikkhū",
+                "ti",
+                "ca",
+                "deseti",
+                "kaṭa",
+                "kāta",
+            ],
+            ConfusionMatrix::default(),
+        )
+    }
+
+    #[test_case("Dhammaṁ ca" => "Dhammaṃ ca"; "m dot above")]
+    #[test_case("dhamrna" => "dhamma"; "broken m")]
+    #[test_case("bhikkhuu’ti" => "bhikkhū’ti"; "split long vowel")]
+    #[test_case("panna" => "paññā"; "three diacritics")]
+    #[test_case("saṃgha dhesana" => "saṃgha dhesana"; "known and unknown words kept")]
+    #[test_case("dcseti" => "deseti"; "broken e")]
+    fn correct_text_tests(text: &str) -> String {
+        create_corrector().correct(text).text
+    }
+
+    #[test]
+    fn correct_reports_edits_test() {
+        let correction = create_corrector().correct("Dhammaṁ ca panna, kata dhesana.");
+
+        insta::assert_yaml_snapshot!(correction);
+        assert_eq!(
+            correction
+                .edits_to_review()
+                .map(|e| e.original.as_str())
+                .collect::<Vec<_>>(),
+            vec!["panna", "kata"]
+        );
+    }
+
+    #[test]
+    fn confusion_matrix_from_str_test() {
+        let matrix: ConfusionMatrix = "# read\tprinted\tcost\n\nṁ\tṃ\t1\nrn\tm\t 6"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            matrix.confusions,
+            vec![
+                Confusion {
+                    read: "ṁ".to_string(),
+                    printed: "ṃ".to_string(),
+                    cost: 1
+                },
+                Confusion {
+                    read: "rn".to_string(),
+                    printed: "m".to_string(),
+                    cost: 6
+                },
+            ]
+        );
+    }
+
+    #[test_case("m\tṃ" => "Expected 'read<TAB>printed<TAB>cost' on line 1, found 'm\tṃ'.")]
+    #[test_case("a\tā\t1\nm\tṃ\tx" => "Invalid cost 'x' on line 2: invalid digit found in string.")]
+    fn confusion_matrix_from_str_error_tests(s: &str) -> String {
+        s.parse::<ConfusionMatrix>().unwrap_err()
+    }
+}
@@ -0,0 +1,430 @@
+use crate::alphabet::{letters_with_offsets, Character, Letter, PaliAlphabet, PALI_ALPHABET_ROMAN};
+use crate::tokenizer::words;
+use serde::Serialize;
+use std::fmt;
+use std::iter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// A letter that is not in the Pāli alphabet, e.g. f, w or a decomposed diacritic.
+    NonPaliLetter,
+    /// Two consonants that are not a doubling (kk), an aspirate doubling (kkh), a nasal before a
+    /// stop of its class (ṅk), or a consonant with y, r, v, h, s or a nasal (ky, tv, mh, sm).
+    IllegalCluster,
+    /// More consonants than a syllable boundary allows, e.g. "ntth".
+    ClusterTooLong,
+    /// A word starting with a cluster other than a stop before y, r or v, or an s before y, v or a
+    /// nasal.
+    IllegalOnset,
+    /// A word ending in a consonant other than ṃ, unless elided, e.g. "sutan’ti".
+    ConsonantEnding,
+    /// ṃ before a vowel in the same word, which sandhi writes m, e.g. "evamāha".
+    NiggahitaBeforeVowel,
+    /// ṃ starting a word or following a consonant.
+    MisplacedNiggahita,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Rule {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::NonPaliLetter => "non-pali-letter",
+            Rule::IllegalCluster => "illegal-cluster",
+            Rule::ClusterTooLong => "cluster-too-long",
+            Rule::IllegalOnset => "illegal-onset",
+            Rule::ConsonantEnding => "consonant-ending",
+            Rule::NiggahitaBeforeVowel => "niggahita-before-vowel",
+            Rule::MisplacedNiggahita => "misplaced-niggahita",
+        }
+    }
+
+    /// Onsets and endings are warnings, as loan words and names break them.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::IllegalOnset | Rule::ConsonantEnding => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// A rule a word breaks, with the offsets of the offending letters into the text, end exclusive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub word: String,
+    pub text: String,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}[{}]: {}", severity, self.rule.id(), self.message)
+    }
+}
+
+/// Checks the words of a text in Roman script against Pāli phonotactics: the letters, the
+/// consonant clusters, the start and end of words and the place of ṃ.
+pub fn lint_text(text: &str) -> Vec<Diagnostic> {
+    let is_apostrophe = |c| c == '\'' || c == '’';
+    let mut diagnostics = Vec::new();
+    for token in words(text) {
+        let word = token.text.trim_start_matches(is_apostrophe);
+        let prefix_len = token.text.len() - word.len();
+        let word = word.trim_end_matches(is_apostrophe);
+        // The word before an elided one, e.g. "sutan" in "sutan’ti", may end in a consonant.
+        let is_elided = word.len() + prefix_len < token.text.len()
+            || text[token.byte_end..].starts_with(is_apostrophe);
+        let char_offset = token.char_start + token.text[..prefix_len].chars().count();
+        for d in lint_word(word, is_elided) {
+            diagnostics.push(Diagnostic {
+                word: token.text.to_string(),
+                byte_start: token.byte_start + prefix_len + d.byte_start,
+                byte_end: token.byte_start + prefix_len + d.byte_end,
+                char_start: char_offset + d.char_start,
+                char_end: char_offset + d.char_end,
+                ..d
+            });
+        }
+    }
+
+    diagnostics
+}
+
+struct WordLinter<'a> {
+    word: &'a str,
+    letters: Vec<Letter>,
+    byte_offsets: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The diagnostics of a word without apostrophes, with offsets into the word.
+fn lint_word(word: &str, is_elided: bool) -> Vec<Diagnostic> {
+    let mut linter = WordLinter::new(word);
+    linter.check_letters();
+    linter.check_niggahitas();
+    linter.check_clusters(is_elided);
+
+    linter.diagnostics
+}
+
+impl<'a> WordLinter<'a> {
+    fn new(word: &'a str) -> WordLinter<'a> {
+        WordLinter {
+            word,
+            letters: letters_with_offsets(word),
+            byte_offsets: word
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(iter::once(word.len()))
+                .collect(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn pali(&self, i: usize) -> Option<PaliAlphabet> {
+        match self.letters.get(i)?.character {
+            Character::Pali(c) => Some(c),
+            Character::Other(_) => None,
+        }
+    }
+
+    /// Reports the letters first to last, inclusive.
+    fn report(&mut self, rule: Rule, first: usize, last: usize, message: String) {
+        let char_start = self.letters[first].char_start;
+        let char_end = self.letters[last].char_end;
+        let byte_start = self.byte_offsets[char_start];
+        let byte_end = self.byte_offsets[char_end];
+        self.diagnostics.push(Diagnostic {
+            rule,
+            severity: rule.severity(),
+            message,
+            word: self.word.to_string(),
+            text: self.word[byte_start..byte_end].to_string(),
+            byte_start,
+            byte_end,
+            char_start,
+            char_end,
+        });
+    }
+
+    fn check_letters(&mut self) {
+        for i in 0..self.letters.len() {
+            if let Character::Other(c) = self.letters[i].character {
+                let message = if ('\u{300}'..='\u{36f}').contains(&c) {
+                    format!(
+                        "U+{:04X} is a combining mark, use the precomposed letter.",
+                        c as u32
+                    )
+                } else {
+                    format!("'{}' is not a Pāli letter.", c)
+                };
+                self.report(Rule::NonPaliLetter, i, i, message);
+            }
+        }
+    }
+
+    fn check_niggahitas(&mut self) {
+        for i in 0..self.letters.len() {
+            if self.pali(i) != Some(PaliAlphabet::DotM) {
+                continue;
+            }
+
+            if i == 0 {
+                self.report(
+                    Rule::MisplacedNiggahita,
+                    i,
+                    i,
+                    "A word cannot start with ṃ.".to_string(),
+                );
+            } else if self.pali(i - 1).is_some_and(is_consonant) {
+                self.report(
+                    Rule::MisplacedNiggahita,
+                    i - 1,
+                    i,
+                    "ṃ must follow a vowel.".to_string(),
+                );
+            }
+            if self.pali(i + 1).is_some_and(is_vowel) {
+                self.report(
+                    Rule::NiggahitaBeforeVowel,
+                    i,
+                    i + 1,
+                    "ṃ before a vowel is written m within a word.".to_string(),
+                );
+            }
+        }
+    }
+
+    /// Checks each run of consonants between vowels. Runs next to a non Pāli letter are left
+    /// alone, as it is reported already.
+    fn check_clusters(&mut self, is_elided: bool) {
+        let mut start = 0;
+        while start < self.letters.len() {
+            if !self.pali(start).is_some_and(is_consonant) {
+                start += 1;
+                continue;
+            }
+            let mut end = start;
+            while self.pali(end).is_some_and(is_consonant) {
+                end += 1;
+            }
+
+            let next_to_other = (start > 0 && self.pali(start - 1).is_none())
+                || (end < self.letters.len() && self.pali(end).is_none());
+            if !next_to_other {
+                let run: Vec<PaliAlphabet> = (start..end).filter_map(|i| self.pali(i)).collect();
+                self.check_run(&run, start, end == self.letters.len() && !is_elided);
+            }
+            start = end;
+        }
+    }
+
+    fn check_run(&mut self, run: &[PaliAlphabet], start: usize, is_word_end: bool) {
+        let last = start + run.len() - 1;
+        let text = run_text(run);
+        let has_niggahita = run.contains(&PaliAlphabet::DotM);
+        if is_word_end {
+            if run.last() != Some(&PaliAlphabet::DotM) {
+                let message = format!("A word cannot end in '{}' unless elided.", text);
+                self.report(Rule::ConsonantEnding, start, last, message);
+            }
+        } else if start == 0 {
+            if run.len() > 1 && !has_niggahita && !is_permitted_onset(run) {
+                let message = format!("A word cannot start with '{}'.", text);
+                self.report(Rule::IllegalOnset, start, last, message);
+            }
+        } else {
+            let mut all_permitted = true;
+            for (i, pair) in run.windows(2).enumerate() {
+                // A ṃ after a consonant is a misplaced niggahīta.
+                if pair[1] != PaliAlphabet::DotM && !is_permitted_cluster(pair[0], pair[1]) {
+                    all_permitted = false;
+                    let message = format!("'{}' is not a Pāli cluster.", run_text(pair));
+                    self.report(Rule::IllegalCluster, start + i, start + i + 1, message);
+                }
+            }
+            if all_permitted && run.len() > 2 && !is_permitted_triple(run) {
+                let message = format!("'{}' has too many consonants for Pāli.", text);
+                self.report(Rule::ClusterTooLong, start, last, message);
+            }
+        }
+    }
+}
+
+fn run_text(run: &[PaliAlphabet]) -> String {
+    run.iter()
+        .map(|&c| PALI_ALPHABET_ROMAN[c as usize])
+        .collect()
+}
+
+fn is_vowel(c: PaliAlphabet) -> bool {
+    c <= PaliAlphabet::O
+}
+
+fn is_consonant(c: PaliAlphabet) -> bool {
+    !is_vowel(c)
+}
+
+/// The class (guttural to labial) and the place in it (k, kh, g, gh, ṅ) of a stop or its nasal.
+fn stop_class(c: PaliAlphabet) -> Option<(usize, usize)> {
+    if (PaliAlphabet::K..=PaliAlphabet::M).contains(&c) {
+        let i = c as usize - PaliAlphabet::K as usize;
+        Some((i / 5, i % 5))
+    } else {
+        None
+    }
+}
+
+fn is_stop(c: PaliAlphabet) -> bool {
+    stop_class(c).is_some_and(|(_, place)| place < 4)
+}
+
+fn is_aspirate(c: PaliAlphabet) -> bool {
+    stop_class(c).is_some_and(|(_, place)| place == 1 || place == 3)
+}
+
+fn is_nasal(c: PaliAlphabet) -> bool {
+    stop_class(c).is_some_and(|(_, place)| place == 4)
+}
+
+fn is_aspirate_doubling(c1: PaliAlphabet, c2: PaliAlphabet) -> bool {
+    is_stop(c1) && !is_aspirate(c1) && c2 as usize == c1 as usize + 1
+}
+
+fn is_doubling(c1: PaliAlphabet, c2: PaliAlphabet) -> bool {
+    use PaliAlphabet::*;
+
+    c1 == c2
+        && ((is_stop(c1) && !is_aspirate(c1))
+            || matches!(c1, TildeN | DotN | N | M | Y | L | V | S))
+}
+
+fn is_permitted_cluster(c1: PaliAlphabet, c2: PaliAlphabet) -> bool {
+    use PaliAlphabet::*;
+
+    let is_homorganic_nasal = is_nasal(c1)
+        && is_stop(c2)
+        && stop_class(c1).map(|(class, _)| class) == stop_class(c2).map(|(class, _)| class);
+    let is_unaspirated_stop = is_stop(c1) && !is_aspirate(c1);
+
+    c1 == DotM
+        || is_doubling(c1, c2)
+        || is_aspirate_doubling(c1, c2)
+        || is_homorganic_nasal
+        || match c2 {
+            Y => is_stop(c1) || matches!(c1, S | V | L),
+            R => is_unaspirated_stop,
+            V => is_unaspirated_stop || matches!(c1, S | H | N),
+            H => matches!(c1, DotN | N | M | Y | L | V | DotL),
+            M => matches!(c1, S | H),
+            N => c1 == S,
+            _ => false,
+        }
+}
+
+fn is_permitted_onset(run: &[PaliAlphabet]) -> bool {
+    use PaliAlphabet::*;
+
+    match run {
+        [c1, Y] => is_stop(*c1) || matches!(c1, S | V),
+        [c1, R | V] => (is_stop(*c1) && !is_aspirate(*c1)) || *c1 == S,
+        [S, M | N] | [N, H] => true,
+        _ => false,
+    }
+}
+
+/// Three consonants are a nasal, ṃ or a doubling before a permitted cluster ending in y, r or v,
+/// e.g. "ndr" in indriya.
+fn is_permitted_triple(run: &[PaliAlphabet]) -> bool {
+    use PaliAlphabet::*;
+
+    match run {
+        [c1, c2, Y | R | V] => {
+            is_nasal(*c1) || *c1 == DotM || is_doubling(*c1, *c2) || is_aspirate_doubling(*c1, *c2)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn lint(text: &str) -> String {
+        lint_text(text)
+            .iter()
+            .map(|d| format!("{}:{}", d.rule.id(), d.text))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test_case("dhammaṃ saṅgho buddhassa"; "doublings and homorganic nasals")]
+    #[test_case("Indriyaṃ brāhmaṇa tvaṃ svākkhāto"; "permitted onsets and triples")]
+    #[test_case("taṇhā amhākaṃ brahmā jivhā kalyāṇa saṃyutta"; "h, y and ṃ clusters")]
+    #[test_case("sutan’ti yad’idaṃ ’pi"; "elided forms may end in a consonant")]
+    #[test_case("Saṁsāra"; "ṁ is read as ṃ")]
+    fn lint_text_permitted_tests(text: &str) {
+        assert_eq!(lint(text), "", "{}", text);
+    }
+
+    #[test_case("akga" => "illegal-cluster:kg")]
+    #[test_case("asta" => "illegal-cluster:st")]
+    #[test_case("akkhha" => "illegal-cluster:khh"; "aspirate doubled")]
+    #[test_case("ṅka aṅta" => "illegal-onset:ṅk illegal-cluster:ṅt"; "nasal of another class")]
+    #[test_case("antthya" => "cluster-too-long:ntthy")]
+    #[test_case("sanntta" => "cluster-too-long:nntt")]
+    #[test_case("evaṃaha" => "niggahita-before-vowel:ṃa")]
+    #[test_case("ṃka akṃa" => "misplaced-niggahita:ṃ misplaced-niggahita:kṃ niggahita-before-vowel:ṃa")]
+    #[test_case("fala wata" => "non-pali-letter:f non-pali-letter:w")]
+    #[test_case("sutan buddhak’ti" => "consonant-ending:n")]
+    #[test_case("Kgama" => "illegal-onset:Kg")]
+    fn lint_text_tests(text: &str) -> String {
+        lint(text)
+    }
+
+    #[test]
+    fn lint_text_spans_test() {
+        let text = "Evaṃ me sutaṃ – evaṃāha fāla’ti.";
+        let diagnostics = lint_text(text);
+
+        insta::assert_yaml_snapshot!(diagnostics);
+        for d in &diagnostics {
+            assert_eq!(&text[d.byte_start..d.byte_end], d.text);
+            let chars: String = text
+                .chars()
+                .skip(d.char_start)
+                .take(d.char_end - d.char_start)
+                .collect();
+            assert_eq!(chars, d.text);
+        }
+    }
+
+    #[test]
+    fn lint_text_combining_mark_test() {
+        let diagnostics = lint_text("dha\u{304}mma");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::NonPaliLetter);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error[non-pali-letter]: U+0304 is a combining mark, use the precomposed letter."
+        );
+    }
+}
@@ -0,0 +1,522 @@
+use crate::alphabet::{sort_key, Character, CharacterTokenizer, PaliAlphabet};
+use serde::Serialize;
+
+/// The cost of an insertion, deletion or unrelated substitution.
+pub const EDIT_COST: u32 = 10;
+/// Vowel length (a/ā), aspiration (k/kh), niggahīta for a nasal (ṃ/n), single for double
+/// consonant (t/tt).
+pub const MINOR_EDIT_COST: u32 = 2;
+/// Retroflex for dental (ṭ/t, ṇ/n, ḷ/l).
+pub const PLACE_EDIT_COST: u32 = 3;
+/// Up to two full edits or several minor ones.
+pub const DEFAULT_MAX_COST: u32 = 2 * EDIT_COST;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub word: String,
+    pub cost: u32,
+}
+
+/// Suggests the words of a word list that are a low phonetic edit distance from a word. The words
+/// are kept in a trie, so the distances of a prefix are computed once for all the words sharing it
+/// and the prefixes already too far from the word are not followed.
+pub struct SpellingSuggester {
+    words: Vec<String>,
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    /// The index in words of the word ending here.
+    word: Option<usize>,
+    children: Vec<(Character, usize)>,
+}
+
+/// The word being looked up and the words found within max_cost of it so far.
+struct Search<'a> {
+    chars: &'a [Character],
+    max_cost: u32,
+    found: Vec<(u32, usize)>,
+    /// The trie nodes looked at, for the tests to check that the search skips most of the list.
+    #[cfg(test)]
+    visited: usize,
+}
+
+impl SpellingSuggester {
+    pub fn new<I, S>(words: I) -> SpellingSuggester
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut suggester = SpellingSuggester {
+            words: Vec::new(),
+            nodes: vec![TrieNode::default()],
+        };
+        for word in words {
+            let word: String = word.into();
+            let mut node = 0;
+            for c in CharacterTokenizer::new(word.chars()) {
+                node = suggester.get_or_add_child(node, c);
+            }
+            if node != 0 && suggester.nodes[node].word.is_none() {
+                suggester.nodes[node].word = Some(suggester.words.len());
+                suggester.words.push(word);
+            }
+        }
+
+        suggester
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The words within max_cost of the word, cheapest first, ties in alphabet order. The word
+    /// itself is included, at cost 0, if it is in the list.
+    pub fn suggest(&self, word: &str, max_cost: u32, max_results: usize) -> Vec<Suggestion> {
+        let chars: Vec<Character> = CharacterTokenizer::new(word.chars()).collect();
+        let mut suggestions: Vec<(u32, Vec<u32>, &str)> = self
+            .search(&chars, max_cost)
+            .found
+            .into_iter()
+            .map(|(cost, i)| (cost, sort_key(&self.words[i]), self.words[i].as_str()))
+            .collect();
+        suggestions.sort();
+        suggestions.truncate(max_results);
+
+        suggestions
+            .into_iter()
+            .map(|(cost, _, word)| Suggestion {
+                word: word.to_string(),
+                cost,
+            })
+            .collect()
+    }
+
+    fn search<'a>(&self, chars: &'a [Character], max_cost: u32) -> Search<'a> {
+        let mut search = Search {
+            chars,
+            max_cost,
+            found: Vec::new(),
+            #[cfg(test)]
+            visited: 0,
+        };
+
+        // The distances of the empty prefix to each prefix of the word.
+        let mut column: Vec<u32> = Vec::with_capacity(chars.len() + 1);
+        column.push(0);
+        for i in 0..chars.len() {
+            column.push(column[i].saturating_add(indel_cost(chars, i)));
+        }
+
+        for &(letter, child) in &self.nodes[0].children {
+            self.visit(&mut search, child, None, letter, &column);
+        }
+
+        search
+    }
+
+    /// Extends the distances of the prefix before the node's letter, in column, by the letter and
+    /// follows the children while some prefix of the word is within max_cost. The cost of adding
+    /// the letter depends on the letter after it, so the column is extended once per such cost.
+    fn visit(
+        &self,
+        search: &mut Search,
+        node: usize,
+        previous: Option<Character>,
+        letter: Character,
+        column: &[u32],
+    ) {
+        #[cfg(test)]
+        {
+            search.visited += 1;
+        }
+        let node = &self.nodes[node];
+        let mut columns: Vec<(u32, Vec<u32>)> = Vec::with_capacity(2);
+
+        if let Some(word) = node.word {
+            let indel = letter_indel_cost(previous, letter, None);
+            let extended = get_or_extend_column(&mut columns, indel, column, search.chars, letter);
+            let cost = extended[search.chars.len()];
+            if cost <= search.max_cost {
+                search.found.push((cost, word));
+            }
+        }
+
+        for &(next, child) in &node.children {
+            let indel = letter_indel_cost(previous, letter, Some(next));
+            let extended = get_or_extend_column(&mut columns, indel, column, search.chars, letter);
+            // Longer prefixes cost at least the cheapest alignment of this one.
+            if extended.iter().min().is_some_and(|&c| c <= search.max_cost) {
+                self.visit(search, child, Some(letter), next, extended);
+            }
+        }
+    }
+
+    fn get_or_add_child(&mut self, node: usize, c: Character) -> usize {
+        if let Some(&(_, child)) = self.nodes[node].children.iter().find(|(l, _)| *l == c) {
+            return child;
+        }
+
+        let child = self.nodes.len();
+        self.nodes.push(TrieNode::default());
+        self.nodes[node].children.push((c, child));
+        child
+    }
+}
+
+fn get_or_extend_column<'a>(
+    columns: &'a mut Vec<(u32, Vec<u32>)>,
+    indel: u32,
+    column: &[u32],
+    chars: &[Character],
+    letter: Character,
+) -> &'a [u32] {
+    let i = match columns.iter().position(|(c, _)| *c == indel) {
+        Some(i) => i,
+        None => {
+            columns.push((indel, extend_column(column, chars, letter, indel)));
+            columns.len() - 1
+        }
+    };
+
+    &columns[i].1
+}
+
+/// The distances of each prefix of chars to a prefix of a word, given those to the word without
+/// its last letter and the cost of adding that letter.
+fn extend_column(column: &[u32], chars: &[Character], letter: Character, indel: u32) -> Vec<u32> {
+    let mut extended = Vec::with_capacity(column.len());
+    extended.push(column[0].saturating_add(indel));
+    for i in 0..chars.len() {
+        let cost = (column[i].saturating_add(substitution_cost(chars[i], letter)))
+            .min(column[i + 1].saturating_add(indel))
+            .min(extended[i].saturating_add(indel_cost(chars, i)));
+        extended.push(cost);
+    }
+
+    extended
+}
+
+/// Edit distance with the costs above, so that likely misspellings are close.
+pub fn phonetic_edit_distance(str1: &str, str2: &str) -> u32 {
+    let chars1: Vec<Character> = CharacterTokenizer::new(str1.chars()).collect();
+    let chars2: Vec<Character> = CharacterTokenizer::new(str2.chars()).collect();
+
+    phonetic_edit_distance_within(&chars1, &chars2, u32::MAX).unwrap_or(u32::MAX)
+}
+
+/// None as soon as every alignment costs more than max_cost.
+fn phonetic_edit_distance_within(
+    chars1: &[Character],
+    chars2: &[Character],
+    max_cost: u32,
+) -> Option<u32> {
+    let mut previous: Vec<u32> = Vec::with_capacity(chars2.len() + 1);
+    previous.push(0);
+    for j in 0..chars2.len() {
+        previous.push(previous[j].saturating_add(indel_cost(chars2, j)));
+    }
+
+    let mut current = vec![0; chars2.len() + 1];
+    for i in 0..chars1.len() {
+        current[0] = previous[0].saturating_add(indel_cost(chars1, i));
+        let mut row_min = current[0];
+        for j in 0..chars2.len() {
+            current[j + 1] = (previous[j].saturating_add(substitution_cost(chars1[i], chars2[j])))
+                .min(previous[j + 1].saturating_add(indel_cost(chars1, i)))
+                .min(current[j].saturating_add(indel_cost(chars2, j)));
+            row_min = row_min.min(current[j + 1]);
+        }
+        if row_min > max_cost {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[chars2.len()]).filter(|&c| c <= max_cost)
+}
+
+fn indel_cost(chars: &[Character], i: usize) -> u32 {
+    letter_indel_cost(
+        i.checked_sub(1).map(|p| chars[p]),
+        chars[i],
+        chars.get(i + 1).copied(),
+    )
+}
+
+/// Dropping or adding a letter is minor when it doubles its neighbour: tt, or the unaspirated
+/// letter before its aspirate as in ccha.
+fn letter_indel_cost(
+    previous: Option<Character>,
+    letter: Character,
+    next: Option<Character>,
+) -> u32 {
+    let doubles = |a: Character, b: Character| match (a, b) {
+        (Character::Pali(a), Character::Pali(b)) if is_consonant(a) => {
+            a == b || aspirate_of(a) == Some(b)
+        }
+        _ => false,
+    };
+    let doubles_next = next.is_some_and(|next| doubles(letter, next));
+    let doubles_previous = previous == Some(letter) && doubles(letter, letter);
+
+    if doubles_next || doubles_previous {
+        MINOR_EDIT_COST
+    } else {
+        EDIT_COST
+    }
+}
+
+fn substitution_cost(c1: Character, c2: Character) -> u32 {
+    match (c1, c2) {
+        _ if c1 == c2 => 0,
+        (Character::Pali(l1), Character::Pali(l2)) => letter_substitution_cost(l1, l2),
+        _ => EDIT_COST,
+    }
+}
+
+fn letter_substitution_cost(l1: PaliAlphabet, l2: PaliAlphabet) -> u32 {
+    use PaliAlphabet::*;
+
+    match (l1, l2) {
+        (A, Aa) | (Aa, A) | (I, Ii) | (Ii, I) | (U, Uu) | (Uu, U) => MINOR_EDIT_COST,
+        (DotM, l) | (l, DotM) if is_nasal(l) => MINOR_EDIT_COST,
+        (L, DotL) | (DotL, L) => PLACE_EDIT_COST,
+        _ => match (stop_features(l1), stop_features(l2)) {
+            (Some((place1, voiced1, aspirated1)), Some((place2, voiced2, aspirated2)))
+                if voiced1 == voiced2
+                    && (place1 == place2 || is_retroflex_dental(place1, place2)) =>
+            {
+                let aspiration = if aspirated1 != aspirated2 {
+                    MINOR_EDIT_COST
+                } else {
+                    0
+                };
+                let place = if place1 != place2 { PLACE_EDIT_COST } else { 0 };
+                aspiration + place
+            }
+            _ if is_nasal(l1) && is_nasal(l2) && is_retroflex_dental(varga(l1), varga(l2)) => {
+                PLACE_EDIT_COST
+            }
+            _ => EDIT_COST,
+        },
+    }
+}
+
+const RETROFLEX: usize = 2;
+const DENTAL: usize = 3;
+
+fn is_retroflex_dental(place1: usize, place2: usize) -> bool {
+    matches!((place1, place2), (RETROFLEX, DENTAL) | (DENTAL, RETROFLEX))
+}
+
+/// The five rows of stops and nasals, k to m, are letters 8 to 32: guttural, palatal, retroflex,
+/// dental and labial, each unvoiced, aspirate, voiced, aspirate and nasal.
+fn varga(l: PaliAlphabet) -> usize {
+    (l as usize).wrapping_sub(PaliAlphabet::K as usize) / 5
+}
+
+fn position_in_varga(l: PaliAlphabet) -> Option<usize> {
+    let i = (l as usize).checked_sub(PaliAlphabet::K as usize)?;
+    if l <= PaliAlphabet::M {
+        Some(i % 5)
+    } else {
+        None
+    }
+}
+
+/// (place, voiced, aspirated) of the stops.
+fn stop_features(l: PaliAlphabet) -> Option<(usize, bool, bool)> {
+    match position_in_varga(l)? {
+        4 => None,
+        p => Some((varga(l), p >= 2, p % 2 == 1)),
+    }
+}
+
+fn is_nasal(l: PaliAlphabet) -> bool {
+    position_in_varga(l) == Some(4)
+}
+
+fn is_consonant(l: PaliAlphabet) -> bool {
+    l >= PaliAlphabet::K && l != PaliAlphabet::DotM
+}
+
+fn aspirate_of(l: PaliAlphabet) -> Option<PaliAlphabet> {
+    use std::convert::TryFrom;
+
+    match stop_features(l)? {
+        (_, _, false) => PaliAlphabet::try_from(l as usize + 1).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case("dhamma", "dhamma" => 0)]
+    #[test_case("dhamma", "dhammā" => MINOR_EDIT_COST; "vowel length")]
+    #[test_case("dhamma", "damma" => MINOR_EDIT_COST; "aspiration")]
+    #[test_case("kaṭa", "kata" => PLACE_EDIT_COST; "retroflex dental")]
+    #[test_case("kaṭha", "kata" => MINOR_EDIT_COST + PLACE_EDIT_COST; "retroflex aspirate")]
+    #[test_case("phaḷa", "phala" => PLACE_EDIT_COST; "retroflex l")]
+    #[test_case("guṇa", "guna" => PLACE_EDIT_COST; "retroflex n")]
+    #[test_case("saṃgha", "saṅgha" => MINOR_EDIT_COST; "niggahita")]
+    #[test_case("dhamma", "dhama" => MINOR_EDIT_COST; "gemination")]
+    #[test_case("gacchati", "gachati" => MINOR_EDIT_COST; "aspirate gemination")]
+    #[test_case("buddha", "budha" => MINOR_EDIT_COST; "aspirate gemination voiced")]
+    #[test_case("kata", "gata" => EDIT_COST; "voicing")]
+    #[test_case("kata", "kapa" => EDIT_COST; "place")]
+    #[test_case("dhamma", "dhammo" => EDIT_COST)]
+    #[test_case("kata", "katva" => EDIT_COST)]
+    #[test_case("", "ca" => 2 * EDIT_COST)]
+    fn phonetic_edit_distance_tests(str1: &str, str2: &str) -> u32 {
+        phonetic_edit_distance(str1, str2)
+    }
+
+    #[test]
+    fn suggest_ranks_by_cost_test() {
+        let suggester = SpellingSuggester::new(vec![
+            "dhammo",
+            "dhammā",
+            "dhammaṃ",
+            "damma",
+            "kamma",
+            "buddho",
+            "dhamma",
+        ]);
+
+        let suggestions = suggester.suggest("dhama", DEFAULT_MAX_COST, 10);
+
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|s| (s.word.as_str(), s.cost))
+                .collect::<Vec<_>>(),
+            vec![
+                ("dhamma", 2),
+                ("damma", 4),
+                ("dhammā", 4),
+                ("kamma", 12),
+                ("dhammaṃ", 12),
+                ("dhammo", 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggest_limits_results_test() {
+        let suggester = SpellingSuggester::new(vec!["a", "ā", "i", "", "ī"]);
+
+        let suggestions = suggester.suggest("a", MINOR_EDIT_COST, 1);
+
+        assert_eq!(suggester.len(), 4);
+        assert_eq!(
+            suggestions,
+            vec![Suggestion {
+                word: "a".to_string(),
+                cost: 0
+            }]
+        );
+    }
+
+    /// The suggestions of a scan of every word, to check the trie against.
+    fn suggest_by_scan(words: &[String], word: &str, max_cost: u32) -> Vec<(String, u32)> {
+        let chars: Vec<Character> = CharacterTokenizer::new(word.chars()).collect();
+        let mut suggestions: Vec<(u32, Vec<u32>, &String)> = words
+            .iter()
+            .filter(|w| !w.is_empty())
+            .filter_map(|w| {
+                let w_chars: Vec<Character> = CharacterTokenizer::new(w.chars()).collect();
+                let cost = phonetic_edit_distance_within(&chars, &w_chars, max_cost)?;
+                Some((cost, sort_key(w), w))
+            })
+            .collect();
+        suggestions.sort();
+        suggestions.dedup();
+
+        suggestions
+            .into_iter()
+            .map(|(cost, _, w)| (w.clone(), cost))
+            .collect()
+    }
+
+    fn suggest_all(suggester: &SpellingSuggester, word: &str, max_cost: u32) -> Vec<(String, u32)> {
+        suggester
+            .suggest(word, max_cost, usize::MAX)
+            .into_iter()
+            .map(|s| (s.word, s.cost))
+            .collect()
+    }
+
+    #[test]
+    fn suggest_searches_a_fraction_of_a_large_list_test() {
+        let syllables: Vec<String> = ["k", "kh", "t", "ṭ", "d", "dh", "n", "m"]
+            .iter()
+            .flat_map(|c| {
+                ["a", "ā", "i", "u", "e", "o"]
+                    .iter()
+                    .map(move |v| format!("{}{}", c, v))
+            })
+            .collect();
+        let words: Vec<String> = syllables
+            .iter()
+            .flat_map(|s1| syllables.iter().map(move |s2| format!("{}{}", s1, s2)))
+            .flat_map(|w| {
+                let two_syllables = w.clone();
+                syllables
+                    .iter()
+                    .map(move |s3| format!("{}{}", w, s3))
+                    .chain(std::iter::once(two_syllables))
+            })
+            .collect();
+        let suggester = SpellingSuggester::new(words.iter().cloned());
+
+        assert_eq!(suggester.len(), 48 * 48 * 48 + 48 * 48);
+        for word in ["dhamato", "kaṭṭhena", "sīvo", "mvk"] {
+            let chars: Vec<Character> = CharacterTokenizer::new(word.chars()).collect();
+            let visited = suggester.search(&chars, DEFAULT_MAX_COST).visited;
+
+            assert_eq!(
+                suggest_all(&suggester, word, DEFAULT_MAX_COST),
+                suggest_by_scan(&words, word, DEFAULT_MAX_COST),
+                "{}",
+                word
+            );
+            assert!(
+                visited * 10 < suggester.nodes.len(),
+                "{} visited {} of {} nodes",
+                word,
+                visited,
+                suggester.nodes.len()
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn suggest_finds_what_a_scan_finds(words in prop::collection::vec("[aāikhtṭdnṇṃm]{0,5}", 0..40), word in "[aāikhtṭdnṇṃm]{0,5}", max_cost in 0..30u32) {
+            let suggester = SpellingSuggester::new(words.iter().cloned());
+
+            prop_assert_eq!(suggest_all(&suggester, &word, max_cost), suggest_by_scan(&words, &word, max_cost));
+        }
+
+        #[test]
+        fn phonetic_edit_distance_is_symmetric(s1 in "[aāikhtṭdnṇṃm]{0,6}", s2 in "[aāikhtṭdnṇṃm]{0,6}") {
+            prop_assert_eq!(phonetic_edit_distance(&s1, &s2), phonetic_edit_distance(&s2, &s1));
+        }
+
+        #[test]
+        fn phonetic_edit_distance_is_zero_only_for_equal(s1 in "[aāikhtṭdnṇṃm]{0,6}", s2 in "[aāikhtṭdnṇṃm]{0,6}") {
+            let equal = sort_key(&s1) == sort_key(&s2);
+            prop_assert_eq!(phonetic_edit_distance(&s1, &s2) == 0, equal);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TokenKind {
+    Word,
+    /// A word with an apostrophe for an elided vowel, e.g. "’ti" (iti), "'pi" (api) or "ti'".
+    Elided,
+    Number,
+    /// The "…pe…" marking an abbreviated repetition, with its dots.
+    Peyyala,
+    /// One mark, or a run of periods, e.g. "।", "॥", ",", "...".
+    Punctuation,
+    Whitespace,
+    Other,
+}
+
+/// A token with its offsets into the source text, end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// Splits running text into tokens. Concatenating the tokens' text gives back the source.
+pub struct WordTokenizer<'a> {
+    source: &'a str,
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    /// After ‘, a ’ ending a word closes the quote rather than eliding.
+    in_quote: bool,
+}
+
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    WordTokenizer::new(text).collect()
+}
+
+/// The words and elided forms only, e.g. for lookup or frequency counts.
+pub fn words(text: &str) -> impl Iterator<Item = Token<'_>> {
+    WordTokenizer::new(text).filter(|t| t.kind == TokenKind::Word || t.kind == TokenKind::Elided)
+}
+
+/// Lower case, without the elision apostrophes, as the forms are in _all_words.
+pub fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| !matches!(c, '\'' | '’'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl<'a> WordTokenizer<'a> {
+    pub fn new(source: &'a str) -> WordTokenizer<'a> {
+        WordTokenizer {
+            source,
+            chars: source.char_indices().collect(),
+            pos: 0,
+            in_quote: false,
+        }
+    }
+
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.chars.get(pos).map(|&(_, c)| c)
+    }
+
+    fn is_letter_at(&self, pos: usize) -> bool {
+        self.char_at(pos).is_some_and(is_letter)
+    }
+
+    fn is_letter_before(&self, pos: usize) -> bool {
+        pos > 0 && self.is_letter_at(pos - 1)
+    }
+
+    fn skip_chars_while(&self, mut pos: usize, f: impl Fn(char) -> bool) -> usize {
+        while self.char_at(pos).is_some_and(&f) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// The end of "…pe…", "...pe...", "…pe" or "pe…" at pos. Bare "pe" is left to be a word.
+    fn match_peyyala(&self, pos: usize) -> Option<usize> {
+        let dots_end = self.skip_dots(pos);
+        let has_leading_dots = dots_end > pos;
+        if !has_leading_dots && self.is_letter_before(pos) {
+            return None;
+        }
+        if self.char_at(dots_end) != Some('p') || self.char_at(dots_end + 1) != Some('e') {
+            return None;
+        }
+
+        let pe_end = dots_end + 2;
+        let end = self.skip_dots(pe_end);
+        let has_trailing_dots = end > pe_end;
+        if !has_trailing_dots && (!has_leading_dots || self.is_letter_at(end)) {
+            return None;
+        }
+
+        Some(end)
+    }
+
+    /// Past "…" or up to three periods.
+    fn skip_dots(&self, pos: usize) -> usize {
+        match self.char_at(pos) {
+            Some('…') => pos + 1,
+            Some('.') => {
+                let end = self.skip_chars_while(pos, |c| c == '.');
+                end.min(pos + 3)
+            }
+            _ => pos,
+        }
+    }
+
+    fn match_word(&self, pos: usize) -> (TokenKind, usize) {
+        let mut kind = TokenKind::Word;
+        let mut start = pos;
+        if is_apostrophe(self.char_at(pos)) {
+            kind = TokenKind::Elided;
+            start += 1;
+        }
+
+        let end = self.skip_chars_while(start, is_letter);
+        // An apostrophe between two words elides the vowel starting the second, e.g. "sutan’ti".
+        let closes_quote = self.in_quote && self.char_at(end) == Some('’');
+        if is_apostrophe(self.char_at(end)) && !self.is_letter_at(end + 1) && !closes_quote {
+            return (TokenKind::Elided, end + 1);
+        }
+
+        (kind, end)
+    }
+
+    fn match_number(&self, pos: usize) -> usize {
+        let mut end = self.skip_chars_while(pos, char::is_numeric);
+        while matches!(self.char_at(end), Some('.') | Some(','))
+            && self.char_at(end + 1).is_some_and(char::is_numeric)
+        {
+            end = self.skip_chars_while(end + 1, char::is_numeric);
+        }
+        end
+    }
+
+    fn make_token(&self, kind: TokenKind, start: usize, end: usize) -> Token<'a> {
+        let byte_start = self.chars[start].0;
+        let byte_end = self.chars.get(end).map_or(self.source.len(), |&(bi, _)| bi);
+
+        Token {
+            kind,
+            text: &self.source[byte_start..byte_end],
+            byte_start,
+            byte_end,
+            char_start: start,
+            char_end: end,
+        }
+    }
+}
+
+impl<'a> Iterator for WordTokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let pos = self.pos;
+        let c = self.char_at(pos)?;
+
+        let (kind, end) = if let Some(end) = self.match_peyyala(pos) {
+            (TokenKind::Peyyala, end)
+        } else if is_letter(c) || (is_apostrophe(Some(c)) && self.is_letter_at(pos + 1)) {
+            self.match_word(pos)
+        } else if c.is_numeric() {
+            (TokenKind::Number, self.match_number(pos))
+        } else if c.is_whitespace() {
+            (
+                TokenKind::Whitespace,
+                self.skip_chars_while(pos, char::is_whitespace),
+            )
+        } else if c == '.' {
+            (
+                TokenKind::Punctuation,
+                self.skip_chars_while(pos, |c| c == '.'),
+            )
+        } else if is_punctuation(c) {
+            (TokenKind::Punctuation, pos + 1)
+        } else {
+            (TokenKind::Other, pos + 1)
+        };
+
+        match c {
+            '‘' => self.in_quote = true,
+            '’' if kind == TokenKind::Punctuation => self.in_quote = false,
+            _ => {}
+        }
+
+        self.pos = end;
+        Some(self.make_token(kind, pos, end))
+    }
+}
+
+/// The combining marks that are not alphabetic but are part of words: devanagari nukta and virama,
+/// sinhala al-lakuna, thai phinthu and thanthakhat, myanmar virama and asat, and the zero width
+/// (non) joiner in conjuncts.
+const WORD_MARKS: &[char] = &[
+    '\u{093C}', '\u{094D}', '\u{0DCA}', '\u{0E3A}', '\u{0E4C}', '\u{1039}', '\u{103A}', '\u{200C}',
+    '\u{200D}',
+];
+
+/// Letters of any script, with the combining marks that are part of them, e.g. a + ̄.
+fn is_letter(c: char) -> bool {
+    c.is_alphabetic() || ('\u{0300}'..='\u{036F}').contains(&c) || WORD_MARKS.contains(&c)
+}
+
+fn is_apostrophe(c: Option<char>) -> bool {
+    matches!(c, Some('\'') | Some('’'))
+}
+
+/// Daṇḍa and double daṇḍa, quotes and dashes, sinhala kunddaliya, and myanmar and thai section
+/// marks.
+const PUNCTUATION: &[char] = &[
+    '।', '॥', '…', '–', '—', '‘', '’', '“', '”', '෴', '၊', '။', '๚', '๛',
+];
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || PUNCTUATION.contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn kinds_and_texts(text: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(text)
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    #[test_case("evaṃ me sutaṃ" => vec![
+        (TokenKind::Word, "evaṃ"), (TokenKind::Whitespace, " "), (TokenKind::Word, "me"),
+        (TokenKind::Whitespace, " "), (TokenKind::Word, "sutaṃ")]; "words")]
+    #[test_case("sutan’ti." => vec![
+        (TokenKind::Word, "sutan"), (TokenKind::Elided, "’ti"), (TokenKind::Punctuation, ".")];
+        "elided iti")]
+    #[test_case("so 'pi gacchati ti'" => vec![
+        (TokenKind::Word, "so"), (TokenKind::Whitespace, " "), (TokenKind::Elided, "'pi"),
+        (TokenKind::Whitespace, " "), (TokenKind::Word, "gacchati"), (TokenKind::Whitespace, " "),
+        (TokenKind::Elided, "ti'")]; "leading and trailing apostrophes")]
+    #[test_case("‘evaṃ’ ti’" => vec![
+        (TokenKind::Punctuation, "‘"), (TokenKind::Word, "evaṃ"), (TokenKind::Punctuation, "’"),
+        (TokenKind::Whitespace, " "), (TokenKind::Elided, "ti’")]; "closing quote")]
+    #[test_case("karoti …pe… gacchati" => vec![
+        (TokenKind::Word, "karoti"), (TokenKind::Whitespace, " "), (TokenKind::Peyyala, "…pe…"),
+        (TokenKind::Whitespace, " "), (TokenKind::Word, "gacchati")]; "peyyala")]
+    #[test_case("karoti...pe...gacchati pe. peta" => vec![
+        (TokenKind::Word, "karoti"), (TokenKind::Peyyala, "...pe..."), (TokenKind::Word, "gacchati"),
+        (TokenKind::Whitespace, " "), (TokenKind::Peyyala, "pe."), (TokenKind::Whitespace, " "),
+        (TokenKind::Word, "peta")]; "peyyala with periods")]
+    #[test_case("12. bhikkhave, 1.200 ॥" => vec![
+        (TokenKind::Number, "12"), (TokenKind::Punctuation, "."), (TokenKind::Whitespace, " "),
+        (TokenKind::Word, "bhikkhave"), (TokenKind::Punctuation, ","), (TokenKind::Whitespace, " "),
+        (TokenKind::Number, "1.200"), (TokenKind::Whitespace, " "), (TokenKind::Punctuation, "॥")];
+        "numbers and danda")]
+    #[test_case("धम्मो । ५" => vec![
+        (TokenKind::Word, "धम्मो"), (TokenKind::Whitespace, " "), (TokenKind::Punctuation, "।"),
+        (TokenKind::Whitespace, " "), (TokenKind::Number, "५")]; "devanagari")]
+    #[test_case("ධම්මෝ + x" => vec![
+        (TokenKind::Word, "ධම්මෝ"), (TokenKind::Whitespace, " "), (TokenKind::Punctuation, "+"),
+        (TokenKind::Whitespace, " "), (TokenKind::Word, "x")]; "sinhala")]
+    #[test_case("" => Vec::<(TokenKind, &str)>::new(); "empty")]
+    fn tokenize_tests(text: &str) -> Vec<(TokenKind, &str)> {
+        kinds_and_texts(text)
+    }
+
+    #[test]
+    fn tokens_have_byte_and_char_offsets() {
+        let tokens = tokenize("ā ’ti");
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::Word,
+                    text: "ā",
+                    byte_start: 0,
+                    byte_end: 2,
+                    char_start: 0,
+                    char_end: 1,
+                },
+                Token {
+                    kind: TokenKind::Whitespace,
+                    text: " ",
+                    byte_start: 2,
+                    byte_end: 3,
+                    char_start: 1,
+                    char_end: 2,
+                },
+                Token {
+                    kind: TokenKind::Elided,
+                    text: "’ti",
+                    byte_start: 3,
+                    byte_end: 8,
+                    char_start: 2,
+                    char_end: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn words_skips_everything_else() {
+        let words: Vec<&str> = words("iti’pi so bhagavā …pe… 1").map(|t| t.text).collect();
+
+        assert_eq!(words, vec!["iti", "’pi", "so", "bhagavā"]);
+    }
+
+    #[test_case("Dhammaṃ" => "dhammaṃ")]
+    #[test_case("’pi" => "pi")]
+    #[test_case("tassa'" => "tassa")]
+    fn normalize_word_tests(word: &str) -> String {
+        normalize_word(word)
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn tokens_cover_the_source(text in "[a-zāīūṃṇ ’'.…,।0-9pe]{0,40}") {
+            let tokens = tokenize(&text);
+
+            let joined: String = tokens.iter().map(|t| t.text).collect();
+            assert_eq!(joined, text.clone());
+            for t in tokens {
+                assert_eq!(&text[t.byte_start..t.byte_end], t.text);
+                assert_eq!(t.char_end - t.char_start, t.text.chars().count());
+            }
+        }
+    }
+}
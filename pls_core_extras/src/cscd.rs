@@ -0,0 +1,286 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A <p> or <head> block of a CSCD book with its markup stripped.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Paragraph {
+    pub rend: String,
+    pub paranum: Option<String>,
+    pub text: String,
+}
+
+impl Paragraph {
+    /// gatha1, gatha2, gatha3 and gathalast are the lines of a verse.
+    pub fn is_gatha(&self) -> bool {
+        self.rend.starts_with("gatha")
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CscdBook {
+    pub id: String,
+    pub title: String,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TextTarget {
+    Paragraph,
+    Paranum,
+    Note,
+    Skip,
+}
+
+/// The .xml files at the paths, directories searched recursively, in path order. The .toc.xml
+/// tables of contents are left out.
+pub fn find_xml_files(paths: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_xml_files(Path::new(path), &mut files)?;
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn collect_xml_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .map_err(|e| format!("Unable to read directory '{}': {}", path.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            collect_xml_files(&entry.path(), files)?;
+        }
+    } else if is_book_file(path) {
+        files.push(path.to_path_buf());
+    }
+
+    Ok(())
+}
+
+fn is_book_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".xml") && !name.ends_with(".toc.xml")
+}
+
+pub fn read_book(path: &Path, include_notes: bool) -> Result<CscdBook, String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("Unable to read '{}': {}", path.display(), e))?;
+    let xml = decode_xml(&bytes).map_err(|e| format!("'{}': {}", path.display(), e))?;
+    let id = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.trim_end_matches(".xml"))
+        .unwrap_or_default();
+
+    parse_book(id, &xml, include_notes).map_err(|e| format!("'{}': {}", path.display(), e))
+}
+
+/// The VRI files are UTF-16 with a byte order mark. Files without one are taken to be UTF-8.
+pub fn decode_xml(bytes: &[u8]) -> Result<String, String> {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => decode_utf8(rest),
+        _ => decode_utf8(bytes),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_u16: fn([u8; 2]) -> u16) -> Result<String, String> {
+    let chunks = bytes.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err("Invalid UTF-16: odd number of bytes.".to_string());
+    }
+
+    let units = chunks.map(|c| to_u16([c[0], c[1]]));
+    std::char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| format!("Invalid UTF-16: {}", e))
+}
+
+fn decode_utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| format!("Invalid UTF-8: {}", e))
+}
+
+/// Collects the <p> and <head> blocks. Paragraph numbers come from the n attribute or the
+/// <hi rend="paranum"> markup, the dot after them is dropped, as are the <note> variant readings
+/// unless include_notes. The title is the first block with rend="book".
+pub fn parse_book(id: &str, xml: &str, include_notes: bool) -> Result<CscdBook, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut book = CscdBook {
+        id: id.to_string(),
+        title: String::new(),
+        paragraphs: Vec::new(),
+    };
+    let mut paragraph: Option<Paragraph> = None;
+    let mut targets: Vec<TextTarget> = Vec::new();
+
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.name() {
+                b"p" | b"head" if paragraph.is_none() => {
+                    paragraph = Some(Paragraph {
+                        rend: get_attribute(e, b"rend", &reader)?.unwrap_or_default(),
+                        paranum: get_attribute(e, b"n", &reader)?,
+                        text: String::new(),
+                    });
+                    targets.clear();
+                }
+                name if paragraph.is_some() => {
+                    let parent = targets.last().copied().unwrap_or(TextTarget::Paragraph);
+                    let target = match (name, get_attribute(e, b"rend", &reader)?.as_deref()) {
+                        _ if parent == TextTarget::Skip => TextTarget::Skip,
+                        (b"note", _) if include_notes => TextTarget::Note,
+                        (b"note", _) => TextTarget::Skip,
+                        (b"hi", Some("paranum")) => TextTarget::Paranum,
+                        (b"hi", Some("dot")) => TextTarget::Skip,
+                        _ => parent,
+                    };
+                    targets.push(target);
+                }
+                _ => {}
+            },
+            Ok(Event::End(ref e)) => match e.name() {
+                b"p" | b"head" if targets.is_empty() => {
+                    if let Some(mut p) = paragraph.take() {
+                        p.text = p.text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if book.title.is_empty() && p.rend == "book" {
+                            book.title = p.text.clone();
+                        }
+                        if !p.text.is_empty() {
+                            book.paragraphs.push(p);
+                        }
+                    }
+                }
+                _ => {
+                    targets.pop();
+                }
+            },
+            Ok(Event::Text(ref e)) => {
+                if let Some(p) = paragraph.as_mut() {
+                    let text = e
+                        .unescape_and_decode(&reader)
+                        .map_err(|e| format_xml_error(e, &reader))?;
+                    match targets.last().copied().unwrap_or(TextTarget::Paragraph) {
+                        TextTarget::Paragraph => p.text.push_str(&text),
+                        TextTarget::Paranum => {
+                            let paranum = text.trim();
+                            if p.paranum.is_none() && !paranum.is_empty() {
+                                p.paranum = Some(paranum.to_string());
+                            }
+                            // A separator so the number does not run into the first word.
+                            p.text.push(' ');
+                        }
+                        TextTarget::Note => {
+                            p.text.push(' ');
+                            p.text.push_str(&text);
+                            p.text.push(' ');
+                        }
+                        TextTarget::Skip => {}
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format_xml_error(e, &reader)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(book)
+}
+
+fn get_attribute(
+    e: &BytesStart,
+    name: &[u8],
+    reader: &Reader<&[u8]>,
+) -> Result<Option<String>, String> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| format_xml_error(e.into(), reader))?;
+        if attribute.key == name {
+            return attribute
+                .unescape_and_decode_value(reader)
+                .map(Some)
+                .map_err(|e| format_xml_error(e, reader));
+        }
+    }
+
+    Ok(None)
+}
+
+fn format_xml_error(e: quick_xml::Error, reader: &Reader<&[u8]>) -> String {
+    format!("Invalid XML at byte {}: {}", reader.buffer_position(), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const BOOK_XML: &str = r#"<?xml version="1.0" encoding="UTF-16"?>
+<?xml-stylesheet type="text/xsl" href="tipitaka-latn.xsl"?>
+<TEI.2>
+<teiHeader></teiHeader>
+<text>
+<front></front>
+<body xml:space="preserve">
+<p rend="nikaya">Dīghanikāyo</p>
+<head rend="book">Sīlakkhandhavaggapāḷi</head>
+<div id="dn1_1" n="dn1_1" type="sutta">
+<head rend="chapter">1. Brahmajālasuttaṃ</head>
+<p rend="bodytext" n="1"><hi rend="paranum">1</hi><hi rend="dot">.</hi> Evaṃ me sutaṃ – ekaṃ samayaṃ bhagavā<note>bhagavā sāvatthiyaṃ (syā.)</note> antarā ca rājagahaṃ.<pb ed="M" n="1.0001" /></p>
+<p rend="gatha1">Yo ca pubbe pamajjitvā, pacchā so nappamajjati;</p>
+<p rend="gathalast">Somaṃ lokaṃ pabhāseti, abbhā muttova candimā &amp; sūriyo.</p>
+<p rend="bodytext"><hi rend="paranum">2</hi><hi rend="dot">.</hi> Atha kho <hi rend="bold">bhagavā</hi> …pe… taṃ.</p>
+<p rend="centre"></p>
+</div>
+</body>
+</text>
+</TEI.2>
+"#;
+
+    #[test]
+    fn parse_book_test() {
+        let book = parse_book("s0101m.mul", BOOK_XML, false).unwrap();
+
+        insta::assert_yaml_snapshot!(book);
+    }
+
+    #[test]
+    fn parse_book_with_notes_test() {
+        let book = parse_book("s0101m.mul", BOOK_XML, true).unwrap();
+
+        assert_eq!(
+            book.paragraphs[3].text,
+            "Evaṃ me sutaṃ – ekaṃ samayaṃ bhagavā bhagavā sāvatthiyaṃ (syā.) antarā ca rājagahaṃ."
+        );
+    }
+
+    #[test]
+    fn parse_book_invalid_xml_test() {
+        let ret = parse_book("x", "<p rend='bodytext'>a</q>", false);
+
+        assert!(ret.unwrap_err().starts_with("Invalid XML at byte"));
+    }
+
+    #[test_case(&[0xFF, 0xFE, 0x61, 0x00, 0x01, 0x01] => Ok("aā".to_string()); "utf16 le")]
+    #[test_case(&[0xFE, 0xFF, 0x00, 0x61, 0x01, 0x01] => Ok("aā".to_string()); "utf16 be")]
+    #[test_case(&[0xEF, 0xBB, 0xBF, 0x61, 0xC4, 0x81] => Ok("aā".to_string()); "utf8 bom")]
+    #[test_case(&[0x61, 0xC4, 0x81] => Ok("aā".to_string()); "utf8")]
+    #[test_case(&[0xFF, 0xFE, 0x61] => Err("Invalid UTF-16: odd number of bytes.".to_string()); "odd utf16")]
+    fn decode_xml_tests(bytes: &[u8]) -> Result<String, String> {
+        decode_xml(bytes)
+    }
+
+    #[test_case("s0101m.mul.xml" => true)]
+    #[test_case("s0101m.mul.toc.xml" => false)]
+    #[test_case("tipitaka-latn.xsl" => false)]
+    fn is_book_file_tests(name: &str) -> bool {
+        is_book_file(Path::new(name))
+    }
+}
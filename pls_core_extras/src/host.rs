@@ -1,28 +1,39 @@
 use crate::logger::PlsLogger;
-use crate::sql_access::SqlAccess;
+use crate::sql_access::{SqlAccess, SqlAccessPool};
 use pls_core::inflections::host::PlsInflectionsHost;
+use std::sync::Arc;
 
 pub struct PlsHost<'a> {
     pub locale: &'a str,
     pub version: &'a str,
     pub url: &'a str,
+    pub cache_id: Option<&'a str>,
+    pub table_cache_capacity: usize,
     pub sql_access: SqlAccess,
     pub logger: &'a dyn PlsLogger,
 }
 
-impl<'a> PlsInflectionsHost<'a> for PlsHost<'a> {
-    fn get_locale(&self) -> &'a str {
+impl<'a> PlsInflectionsHost for PlsHost<'a> {
+    fn get_locale(&self) -> &str {
         self.locale
     }
 
-    fn get_version(&self) -> &'a str {
+    fn get_version(&self) -> &str {
         self.version
     }
 
-    fn get_url(&self) -> &'a str {
+    fn get_url(&self) -> &str {
         self.url
     }
 
+    fn get_cache_id(&self) -> Option<&str> {
+        self.cache_id
+    }
+
+    fn get_table_cache_capacity(&self) -> usize {
+        self.table_cache_capacity
+    }
+
     fn transliterate(&self, s: &str) -> Result<String, String> {
         Ok(s.to_string())
     }
@@ -35,4 +46,70 @@ impl<'a> PlsInflectionsHost<'a> for PlsHost<'a> {
     fn log_warning(&self, msg: &str) {
         self.logger.warning(msg)
     }
+
+    fn log_debug(&self, msg: &str) {
+        self.logger.debug(msg)
+    }
+}
+
+/// A host that owns its configuration and takes its connections from a pool, so one instance can
+/// serve many threads.
+#[derive(Clone)]
+pub struct SharedPlsHost {
+    pub locale: String,
+    pub version: String,
+    pub url: String,
+    pub cache_id: Option<String>,
+    pub table_cache_capacity: usize,
+    pub sql_access_pool: Arc<SqlAccessPool>,
+    pub logger: Arc<dyn PlsLogger + Send + Sync>,
+}
+
+impl SharedPlsHost {
+    /// A host sharing the pool and logger of this one but rendering in another locale.
+    pub fn with_locale(&self, locale: &str) -> SharedPlsHost {
+        SharedPlsHost {
+            locale: locale.to_string(),
+            ..self.clone()
+        }
+    }
+}
+
+impl PlsInflectionsHost for SharedPlsHost {
+    fn get_locale(&self) -> &str {
+        &self.locale
+    }
+
+    fn get_version(&self) -> &str {
+        &self.version
+    }
+
+    fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    fn get_cache_id(&self) -> Option<&str> {
+        self.cache_id.as_deref()
+    }
+
+    fn get_table_cache_capacity(&self) -> usize {
+        self.table_cache_capacity
+    }
+
+    fn transliterate(&self, s: &str) -> Result<String, String> {
+        Ok(s.to_string())
+    }
+
+    fn exec_sql_query_core(&self, sql: &str) -> Result<String, String> {
+        let table = self.sql_access_pool.get()?.exec(sql)?;
+        serde_json::to_string(&table).map_err(|x| x.to_string())
+    }
+
+    fn log_warning(&self, msg: &str) {
+        self.logger.warning(msg)
+    }
+
+    fn log_debug(&self, msg: &str) {
+        self.logger.debug(msg)
+    }
 }
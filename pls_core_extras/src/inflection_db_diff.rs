@@ -0,0 +1,310 @@
+use crate::inflection_generator::{get_version_info, PlsInflectionGenerator};
+use pls_core::alphabet::sort_strings;
+use pls_core::inflections::host::PlsInflectionsHost;
+use pls_core::inflections::{generate_inflection_table_data, InflectionCell};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tera::{Context, Tera};
+
+lazy_static! {
+    static ref TEMPLATES: Tera = {
+        let mut tera = Tera::default();
+        tera.add_raw_templates(vec![(
+            "inflection_db_diff.html",
+            include_str!("templates/inflection_db_diff.html"),
+        )])
+        .expect("Unexpected failure adding template");
+        tera.autoescape_on(vec!["html"]);
+        tera
+    };
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub old: String,
+    pub new: String,
+}
+
+/// The forms of one table cell only in the old or only in the new db. cell is the parameter
+/// values, e.g. "nom masc sg", or "in comps".
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CellDiff {
+    pub cell: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HeadWordDiff {
+    pub pali1: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stem: Option<Change>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<Change>,
+    pub added_forms: Vec<String>,
+    pub removed_forms: Vec<String>,
+    pub cells: Vec<CellDiff>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DiffError {
+    pub pali1: String,
+    pub message: String,
+}
+
+/// What changed for users between two versions of inflections.db.
+#[derive(Debug, Default, Serialize)]
+pub struct InflectionDbDiff {
+    pub old_commit_id: String,
+    pub new_commit_id: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<HeadWordDiff>,
+    pub unchanged_count: usize,
+    pub errors: Vec<DiffError>,
+}
+
+struct StemRow {
+    stem: String,
+    pattern: String,
+}
+
+pub fn diff_inflection_dbs(
+    old: &PlsInflectionGenerator,
+    new: &PlsInflectionGenerator,
+) -> Result<InflectionDbDiff, String> {
+    let (old, new) = (&old.inflection_host, &new.inflection_host);
+    let old_stems = get_stem_rows(old)?;
+    let new_stems = get_stem_rows(new)?;
+
+    let mut diff = InflectionDbDiff {
+        old_commit_id: get_version_info(old)?.commit_id,
+        new_commit_id: get_version_info(new)?.commit_id,
+        added: sorted(new_stems.keys().filter(|p| !old_stems.contains_key(*p))),
+        removed: sorted(old_stems.keys().filter(|p| !new_stems.contains_key(*p))),
+        ..InflectionDbDiff::default()
+    };
+
+    let common = sorted(old_stems.keys().filter(|p| new_stems.contains_key(*p)));
+    for pali1 in common {
+        match diff_head_word(&pali1, &old_stems[&pali1], &new_stems[&pali1], old, new) {
+            Ok(Some(hwd)) => diff.changed.push(hwd),
+            Ok(None) => diff.unchanged_count += 1,
+            Err(message) => diff.errors.push(DiffError { pali1, message }),
+        }
+    }
+
+    Ok(diff)
+}
+
+fn get_stem_rows(host: &dyn PlsInflectionsHost) -> Result<HashMap<String, StemRow>, String> {
+    let rows = host
+        .exec_sql_query("select pāli1, stem, pattern from _stems")?
+        .pop()
+        .unwrap_or_default();
+
+    Ok(rows
+        .into_iter()
+        .map(|mut row| {
+            let pattern = row.pop().unwrap_or_default();
+            let stem = row.pop().unwrap_or_default();
+            let pali1 = row.pop().unwrap_or_default();
+            (pali1, StemRow { stem, pattern })
+        })
+        .collect())
+}
+
+/// None if the head word inflects the same in both dbs.
+fn diff_head_word(
+    pali1: &str,
+    old_row: &StemRow,
+    new_row: &StemRow,
+    old: &dyn PlsInflectionsHost,
+    new: &dyn PlsInflectionsHost,
+) -> Result<Option<HeadWordDiff>, String> {
+    let old_cells = get_cells(pali1, old)?;
+    let new_cells = get_cells(pali1, new)?;
+    let old_forms = get_forms(&old_cells);
+    let new_forms = get_forms(&new_cells);
+
+    let mut cell_names: Vec<&String> = old_cells.keys().chain(new_cells.keys()).collect();
+    cell_names.sort();
+    cell_names.dedup();
+    let cells: Vec<CellDiff> = cell_names
+        .into_iter()
+        .filter_map(|cell| {
+            let no_forms = HashSet::new();
+            let old_forms = old_cells.get(cell).unwrap_or(&no_forms);
+            let new_forms = new_cells.get(cell).unwrap_or(&no_forms);
+            let cd = CellDiff {
+                cell: cell.to_owned(),
+                added: sorted(new_forms.difference(old_forms)),
+                removed: sorted(old_forms.difference(new_forms)),
+            };
+            if cd.added.is_empty() && cd.removed.is_empty() {
+                None
+            } else {
+                Some(cd)
+            }
+        })
+        .collect();
+
+    let hwd = HeadWordDiff {
+        pali1: pali1.to_owned(),
+        stem: get_change(&old_row.stem, &new_row.stem),
+        pattern: get_change(&old_row.pattern, &new_row.pattern),
+        added_forms: sorted(new_forms.difference(&old_forms)),
+        removed_forms: sorted(old_forms.difference(&new_forms)),
+        cells,
+    };
+    let unchanged = hwd.stem.is_none()
+        && hwd.pattern.is_none()
+        && hwd.added_forms.is_empty()
+        && hwd.removed_forms.is_empty()
+        && hwd.cells.is_empty();
+
+    Ok(if unchanged { None } else { Some(hwd) })
+}
+
+fn get_cells(
+    pali1: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<BTreeMap<String, HashSet<String>>, String> {
+    let data = generate_inflection_table_data(pali1, host)?;
+    Ok(data
+        .cells
+        .into_iter()
+        .map(|c| (get_cell_name(&c), c.inflections.into_iter().collect()))
+        .collect())
+}
+
+fn get_forms(cells: &BTreeMap<String, HashSet<String>>) -> HashSet<String> {
+    cells.values().flatten().cloned().collect()
+}
+
+fn get_cell_name(cell: &InflectionCell) -> String {
+    let values: Vec<&str> = cell
+        .parameters
+        .values()
+        .map(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if values.is_empty() {
+        "in comps".to_string()
+    } else {
+        values.join(" ")
+    }
+}
+
+fn get_change(old: &str, new: &str) -> Option<Change> {
+    if old == new {
+        None
+    } else {
+        Some(Change {
+            old: old.to_owned(),
+            new: new.to_owned(),
+        })
+    }
+}
+
+fn sorted<'a>(strs: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut strs: Vec<String> = strs.cloned().collect();
+    sort_strings(&mut strs);
+    strs
+}
+
+impl InflectionDbDiff {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// A standalone page for reviewing the release by hand.
+    pub fn to_html(&self) -> Result<String, String> {
+        let mut context = Context::from_serialize(self).map_err(|e| e.to_string())?;
+        context.insert("old_commit_id", short_commit_id(&self.old_commit_id));
+        context.insert("new_commit_id", short_commit_id(&self.new_commit_id));
+
+        TEMPLATES
+            .render("inflection_db_diff.html", &context)
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn short_commit_id(commit_id: &str) -> &str {
+    commit_id.get(0..10).unwrap_or(commit_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::NullLogger;
+    use crate::test_fixtures::create_test_generator;
+
+    const OLD_DB_SQL: &str = r#"
+        UPDATE _version SET commit_id = '1111111111aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa';
+        INSERT INTO _stems VALUES ('dhamma 1', 'dhamm', 'a masc', 'masc', 'nature');
+        INSERT INTO _stems VALUES ('kamma 1', 'kamm', 'a masc', 'nt', 'action');
+    "#;
+
+    const NEW_DB_SQL: &str = r#"
+        UPDATE _version SET commit_id = '2222222222bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb';
+        DELETE FROM _stems WHERE pāli1 = 'ababa 1';
+        INSERT INTO _stems VALUES ('buddha 1', 'buddh', 'a masc', 'masc', 'awakened');
+        UPDATE _stems SET pattern = 'a nt' WHERE pāli1 = 'kamma 1';
+        INSERT INTO _index VALUES ('a nt', '', 'citta');
+        CREATE TABLE a_nt ("case" TEXT, gender TEXT, "number" TEXT, inflections TEXT);
+        INSERT INTO a_nt VALUES ('nom', 'nt', 'sg', 'aṃ');
+        INSERT INTO a_nt VALUES ('nom', 'nt', 'pl', 'ā,āni');
+        INSERT INTO a_nt VALUES ('', '', '', 'a');
+    "#;
+
+    #[test]
+    fn diff_reports_head_word_pattern_and_cell_changes() {
+        let logger = NullLogger {};
+        let old = create_test_generator(OLD_DB_SQL, &logger);
+        let new = create_test_generator(&format!("{}{}", OLD_DB_SQL, NEW_DB_SQL), &logger);
+
+        let diff = diff_inflection_dbs(&old, &new).expect("diff should succeed");
+
+        insta::assert_yaml_snapshot!(diff);
+    }
+
+    #[test]
+    fn same_db_has_no_changes() {
+        let logger = NullLogger {};
+        let old = create_test_generator(OLD_DB_SQL, &logger);
+        let new = create_test_generator(OLD_DB_SQL, &logger);
+
+        let diff = diff_inflection_dbs(&old, &new).expect("diff should succeed");
+
+        assert_eq!(diff.added, Vec::<String>::new());
+        assert_eq!(diff.removed, Vec::<String>::new());
+        assert_eq!(diff.changed, vec![]);
+        assert_eq!(diff.errors, vec![]);
+        assert_eq!(diff.unchanged_count, 4);
+    }
+
+    #[test]
+    fn html_escapes_and_lists_changes() {
+        let diff = InflectionDbDiff {
+            old_commit_id: "1111111111aaaa".to_string(),
+            new_commit_id: "2222222222bbbb".to_string(),
+            added: vec!["buddha 1".to_string()],
+            changed: vec![HeadWordDiff {
+                pali1: "kamma 1".to_string(),
+                stem: None,
+                pattern: get_change("a masc", "a nt"),
+                added_forms: vec!["kammaṃ".to_string()],
+                removed_forms: vec!["kammo".to_string()],
+                cells: vec![CellDiff {
+                    cell: "nom <sg>".to_string(),
+                    added: vec!["kammaṃ".to_string()],
+                    removed: vec!["kammo".to_string()],
+                }],
+            }],
+            ..InflectionDbDiff::default()
+        };
+
+        insta::assert_snapshot!(diff.to_html().expect("render should succeed"));
+    }
+}
@@ -0,0 +1,405 @@
+use crate::inflection_generator::{get_version_info, PlsInflectionGenerator};
+use pls_core::inflections::host::PlsInflectionsHost;
+use pls_core::inflections::pmd::InflectionClass;
+use pls_core::inflections::{
+    generate_inflection_table, get_abbreviations_used_in_templates, get_pattern_table_columns,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationRule {
+    InvalidVersion,
+    MissingTable,
+    UnknownInflectionClass,
+    MissingPatternTable,
+    MissingPatternColumns,
+    EmptySuffix,
+    DuplicateSuffix,
+    MissingPattern,
+    MissingAbbreviation,
+    GenerationError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub rule: ValidationRule,
+    pub severity: Severity,
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub commit_id: String,
+    pub patterns_checked: usize,
+    pub stems_checked: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+pub struct ValidationOptions {
+    /// Render the inflection table of every head word. This is slow but catches everything the
+    /// end user would see as a blank table.
+    pub check_generation: bool,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.error_count == 0
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    fn add_issue(
+        &mut self,
+        rule: ValidationRule,
+        severity: Severity,
+        location: &str,
+        message: &str,
+    ) {
+        match severity {
+            Severity::Error => self.error_count += 1,
+            Severity::Warning => self.warning_count += 1,
+        }
+
+        self.issues.push(ValidationIssue {
+            rule,
+            severity,
+            location: location.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn add_error(&mut self, rule: ValidationRule, location: &str, message: &str) {
+        self.add_issue(rule, Severity::Error, location, message)
+    }
+}
+
+pub fn validate_inflection_db(
+    igen: &PlsInflectionGenerator,
+    options: &ValidationOptions,
+) -> ValidationReport {
+    let host = &igen.inflection_host;
+    let mut report = ValidationReport::default();
+
+    match get_version_info(host) {
+        Ok(vi) => report.commit_id = vi.commit_id,
+        Err(e) => report.add_error(ValidationRule::InvalidVersion, "_version", &e),
+    }
+
+    let patterns = validate_index(host, &mut report);
+    validate_abbreviations(host, &mut report);
+    let pali1s = validate_stems(host, &patterns, &mut report);
+
+    if options.check_generation {
+        for pali1 in pali1s {
+            if let Err(e) = generate_inflection_table(&pali1, true, host) {
+                report.add_error(
+                    ValidationRule::GenerationError,
+                    &format!("_stems:{}", pali1),
+                    &e,
+                );
+            }
+        }
+    }
+
+    report
+}
+
+/// Checks _index and the pattern tables it points to. Returns the names of the patterns that can
+/// be used by _stems.
+fn validate_index(host: &dyn PlsInflectionsHost, report: &mut ValidationReport) -> HashSet<String> {
+    let index_rows = match host.exec_sql_query("select name, inflection_class from _index") {
+        Ok(mut res) => res.pop().unwrap_or_default(),
+        Err(e) => {
+            report.add_error(ValidationRule::MissingTable, "_index", &e);
+            return HashSet::new();
+        }
+    };
+
+    let mut patterns = HashSet::new();
+    for row in index_rows {
+        let (name, inflection_class) = (&row[0], &row[1]);
+        patterns.insert(name.to_owned());
+        report.patterns_checked += 1;
+
+        match InflectionClass::from_str(inflection_class) {
+            Ok(ic) => validate_pattern_table(name, &ic, host, report),
+            Err(e) => report.add_error(
+                ValidationRule::UnknownInflectionClass,
+                &format!("_index:{}", name),
+                &e,
+            ),
+        }
+    }
+
+    patterns
+}
+
+fn validate_pattern_table(
+    pattern: &str,
+    inflection_class: &InflectionClass,
+    host: &dyn PlsInflectionsHost,
+    report: &mut ValidationReport,
+) {
+    let table_name = pattern.replace(" ", "_");
+    let columns: Vec<String> = match host.exec_sql_query(&format!(
+        "select name from pragma_table_info('{}')",
+        table_name
+    )) {
+        Ok(res) => res.into_iter().flatten().flatten().collect(),
+        Err(e) => vec![e],
+    };
+    if columns.is_empty() {
+        report.add_error(
+            ValidationRule::MissingPatternTable,
+            &table_name,
+            &format!("Table for pattern '{}' not found.", pattern),
+        );
+        return;
+    }
+
+    let missing_columns: Vec<&str> = get_pattern_table_columns(inflection_class)
+        .iter()
+        .filter(|&&c| !columns.iter().any(|x| x == c))
+        .cloned()
+        .collect();
+    if !missing_columns.is_empty() {
+        report.add_error(
+            ValidationRule::MissingPatternColumns,
+            &table_name,
+            &format!("Missing columns: {}.", missing_columns.join(", ")),
+        );
+    }
+
+    if !columns.iter().any(|x| x == "inflections") {
+        return;
+    }
+
+    let rows = match host.exec_sql_query(&format!(
+        "select cast(rowid as text), inflections from '{}'",
+        table_name
+    )) {
+        Ok(mut res) => res.pop().unwrap_or_default(),
+        Err(e) => {
+            report.add_error(ValidationRule::MissingPatternTable, &table_name, &e);
+            return;
+        }
+    };
+    for row in rows {
+        let (row_id, inflections) = (&row[0], &row[1]);
+        if inflections.is_empty() {
+            continue;
+        }
+
+        let location = format!("{}:{}", table_name, row_id);
+        let suffixes: Vec<&str> = inflections.split(',').collect();
+        if suffixes.iter().any(|s| s.trim().is_empty()) {
+            report.add_error(
+                ValidationRule::EmptySuffix,
+                &location,
+                &format!("Empty suffix in '{}'.", inflections),
+            );
+        }
+
+        let mut seen = HashSet::new();
+        let duplicates: Vec<&str> = suffixes
+            .into_iter()
+            .filter(|s| !s.is_empty() && !seen.insert(*s))
+            .collect();
+        if !duplicates.is_empty() {
+            report.add_issue(
+                ValidationRule::DuplicateSuffix,
+                Severity::Warning,
+                &location,
+                &format!(
+                    "Duplicate suffixes {} in '{}'.",
+                    duplicates.join(", "),
+                    inflections
+                ),
+            );
+        }
+    }
+}
+
+fn validate_abbreviations(host: &dyn PlsInflectionsHost, report: &mut ValidationReport) {
+    let used = get_abbreviations_used_in_templates(host).and_then(|used| {
+        let defined: HashSet<String> = host
+            .exec_sql_query("select name, description from _abbreviations")?
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+        Ok(used
+            .into_iter()
+            .filter(|x| !defined.contains(x))
+            .collect::<Vec<String>>())
+    });
+
+    match used {
+        Ok(missing) => {
+            for abbrev in missing {
+                report.add_error(
+                    ValidationRule::MissingAbbreviation,
+                    "_abbreviations",
+                    &format!(
+                        "Abbreviation '{}' is used in templates but not defined.",
+                        abbrev
+                    ),
+                );
+            }
+        }
+        Err(e) => report.add_error(ValidationRule::MissingTable, "_abbreviations", &e),
+    }
+}
+
+/// Checks every _stems row refers to a known pattern. Returns the pāli1 of the rows that can be
+/// generated.
+fn validate_stems(
+    host: &dyn PlsInflectionsHost,
+    patterns: &HashSet<String>,
+    report: &mut ValidationReport,
+) -> Vec<String> {
+    let stem_rows = match host.exec_sql_query("select pāli1, stem, pattern from _stems") {
+        Ok(mut res) => res.pop().unwrap_or_default(),
+        Err(e) => {
+            report.add_error(ValidationRule::MissingTable, "_stems", &e);
+            return Vec::new();
+        }
+    };
+
+    let mut pali1s = Vec::new();
+    for row in stem_rows {
+        let (pali1, stem, pattern) = (&row[0], &row[1], &row[2]);
+        report.stems_checked += 1;
+
+        if stem != "!" && stem != "-" && !patterns.contains(pattern) {
+            report.add_error(
+                ValidationRule::MissingPattern,
+                &format!("_stems:{}", pali1),
+                &format!("Pattern '{}' not found in _index.", pattern),
+            );
+            continue;
+        }
+
+        pali1s.push(pali1.to_owned());
+    }
+
+    pali1s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflection_generator::PlsInflectionGenerator;
+    use crate::logger::NullLogger;
+    use crate::test_fixtures;
+
+    const ABBREVIATIONS_SQL: &str = r#"
+        CREATE TABLE _abbreviations (name TEXT, description TEXT);
+        INSERT INTO _abbreviations VALUES ('1st', 'first person'), ('2nd', 'second person'), ('3rd', 'third person');
+        INSERT INTO _abbreviations VALUES ('act', 'active'), ('reflx', 'reflexive'), ('dual', 'dual');
+        INSERT INTO _abbreviations VALUES ('masc', 'masculine'), ('fem', 'feminine'), ('nt', 'neuter');
+        INSERT INTO _abbreviations VALUES ('sg', 'singular'), ('pl', 'plural'), ('in comps', 'in compounds');
+        INSERT INTO _abbreviations VALUES ('nom', 'nominative'), ('pr', 'present');
+        INSERT INTO _abbreviations VALUES ('prp', 'present participle'), ('pp', 'past participle');
+        INSERT INTO _abbreviations VALUES ('ptp', 'potential participle'), ('abs', 'absolutive');
+        INSERT INTO _abbreviations VALUES ('inf', 'infinitive'), ('caus', 'causative'), ('pass', 'passive');
+    "#;
+
+    fn create_test_generator<'a>(sql: &str, logger: &'a NullLogger) -> PlsInflectionGenerator<'a> {
+        test_fixtures::create_test_generator(&format!("{}{}", ABBREVIATIONS_SQL, sql), logger)
+    }
+
+    fn get_rules(report: &ValidationReport) -> Vec<(ValidationRule, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|i| (i.rule, i.location.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_valid_db() {
+        let logger = NullLogger {};
+        let igen = create_test_generator("", &logger);
+
+        let report = validate_inflection_db(
+            &igen,
+            &ValidationOptions {
+                check_generation: true,
+            },
+        );
+
+        assert_eq!(report.issues, vec![]);
+        assert!(report.is_valid());
+        assert_eq!(report.patterns_checked, 1);
+        assert_eq!(report.stems_checked, 2);
+        assert_eq!(report.commit_id, "0123456789012345678901234567890123456789");
+    }
+
+    #[test]
+    fn test_validate_invalid_db() {
+        let logger = NullLogger {};
+        let igen = create_test_generator(
+            r#"
+            UPDATE _version SET commit_id = 'c0ffee';
+            INSERT INTO _stems VALUES ('kamma 1', 'kamm', 'a nt', 'nt', 'action');
+            INSERT INTO _index VALUES ('ī fem', 'adj', 'nadī');
+            INSERT INTO _index VALUES ('u masc', '', 'bhikkhu');
+            CREATE TABLE u_masc ("case" TEXT, "number" TEXT, inflections TEXT);
+            INSERT INTO a_masc VALUES ('nom', 'x', 'sg', 'o,,e');
+            INSERT INTO a_masc VALUES ('nom', 'x', 'pl', 'ā,āse,ā');
+            DELETE FROM _abbreviations WHERE name = 'in comps';
+            "#,
+            &logger,
+        );
+
+        let report = validate_inflection_db(
+            &igen,
+            &ValidationOptions {
+                check_generation: true,
+            },
+        );
+
+        assert_eq!(
+            get_rules(&report),
+            vec![
+                (ValidationRule::InvalidVersion, "_version"),
+                (ValidationRule::EmptySuffix, "a_masc:4"),
+                (ValidationRule::DuplicateSuffix, "a_masc:5"),
+                (ValidationRule::UnknownInflectionClass, "_index:ī fem"),
+                (ValidationRule::MissingPatternColumns, "u_masc"),
+                (ValidationRule::MissingAbbreviation, "_abbreviations"),
+                (ValidationRule::MissingPattern, "_stems:kamma 1"),
+            ]
+        );
+        assert!(!report.is_valid());
+        assert_eq!(report.error_count, 6);
+        assert_eq!(report.warning_count, 1);
+    }
+
+    #[test]
+    fn test_validation_report_to_json() {
+        let mut report = ValidationReport::default();
+        report.add_error(ValidationRule::MissingPattern, "_stems:x", "msg");
+
+        assert_eq!(
+            report.to_json().map(|j| j.replace(char::is_whitespace, "")),
+            Ok(r#"{"commit_id":"","patterns_checked":0,"stems_checked":0,"error_count":1,"warning_count":0,"issues":[{"rule":"missing_pattern","severity":"error","location":"_stems:x","message":"msg"}]}"#.to_string())
+        );
+    }
+}
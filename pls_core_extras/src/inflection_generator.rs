@@ -1,11 +1,13 @@
-use crate::host::PlsHost;
+use crate::host::{PlsHost, SharedPlsHost};
 use crate::logger::PlsLogger;
-use crate::sql_access::SqlAccess;
+use crate::sql_access::{SqlAccess, SqlAccessPool};
 use pls_core::inflections::{
-    generate_all_inflections, generate_inflection_table, host::PlsInflectionsHost,
+    cache, generate_all_inflections, generate_inflection_table, host::PlsInflectionsHost,
 };
 use rusqlite::Connection;
 use std::env;
+use std::path::Path;
+use std::sync::Arc;
 
 lazy_static! {
     static ref PLS_INFLECTION_GENERATOR_PREFIX: String =
@@ -16,6 +18,9 @@ pub trait InflectionGenerator {
     fn check_inflection_db(&self, logger: &dyn PlsLogger) -> Result<(), String>;
     fn generate_inflection_table_html(&self, pali1: &str) -> String;
     fn generate_all_inflections(&self, pali1: &str) -> Vec<String>;
+    /// Same as generate_all_inflections but returns the error instead of logging it, e.g. so the
+    /// word can be retried later.
+    fn try_generate_all_inflections(&self, pali1: &str) -> Result<Vec<String>, String>;
 }
 
 pub struct NullInflectionGenerator {}
@@ -44,6 +49,10 @@ impl InflectionGenerator for NullInflectionGenerator {
     fn generate_all_inflections(&self, _pali1: &str) -> Vec<String> {
         Vec::new()
     }
+
+    fn try_generate_all_inflections(&self, _pali1: &str) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
 }
 
 pub struct PlsInflectionGenerator<'a> {
@@ -70,6 +79,8 @@ impl<'a> PlsInflectionGenerator<'a> {
                 locale,
                 version,
                 url,
+                cache_id: Some(inflections_db_path),
+                table_cache_capacity: 0,
                 sql_access: SqlAccess { connection },
                 logger,
             },
@@ -81,80 +92,188 @@ impl<'a> PlsInflectionGenerator<'a> {
 
 impl<'a> InflectionGenerator for PlsInflectionGenerator<'a> {
     fn check_inflection_db(&self, logger: &dyn PlsLogger) -> Result<(), String> {
-        match self
-            .inflection_host
-            .exec_sql_query("select * from _version")
-        {
-            Ok(ver_info) => {
-                if ver_info.len() != 1 && ver_info[0].len() != 1 && ver_info[0][0].len() != 3 {
-                    let msg =
-                        "Invalid inflection db. Unexpected data in _version table.".to_string();
-                    logger.error(&msg);
-                    return Err(msg);
-                }
-
-                let commit_id = &ver_info[0][0][0];
-                let repository = &ver_info[0][0][2];
-                if commit_id.len() != 40 && repository != "digitalpalitools/inflection-generator" {
-                    let msg = format!(
-                        "Invalid inflection db: commid_id: {}, repository: {}.",
-                        commit_id, repository
-                    );
-                    logger.error(&msg);
-                    return Err(msg);
-                }
-
-                logger.info(&format!(
-                    "... loaded version: https://github.com/{}#{}.",
-                    repository,
-                    &commit_id[0..10]
-                ));
-                Ok(())
-            }
-            Err(e) => {
-                let msg = format!("Unable to load inflection db. Error: {}.", e);
-                logger.error(&msg);
-                Err(e)
-            }
-        }
+        check_inflection_db(&self.inflection_host, logger)
     }
 
     fn generate_inflection_table_html(&self, pali1: &str) -> String {
-        if is_black_listed_word(pali1) {
-            return "".to_string();
-        }
+        generate_inflection_table_html(pali1, &self.inflection_host)
+    }
+
+    fn generate_all_inflections(&self, pali1: &str) -> Vec<String> {
+        generate_all_inflections_or_log(pali1, &self.inflection_host)
+    }
+
+    fn try_generate_all_inflections(&self, pali1: &str) -> Result<Vec<String>, String> {
+        try_generate_all_inflections(pali1, &self.inflection_host)
+    }
+}
+
+/// Same as PlsInflectionGenerator but Send + Sync: the host owns its configuration and takes its
+/// connections from a pool, so one instance can be shared by all the threads of a server.
+pub struct SharedInflectionGenerator {
+    pub inflection_host: SharedPlsHost,
+}
+
+impl SharedInflectionGenerator {
+    pub fn new(
+        locale: &str,
+        version: &str,
+        url: &str,
+        inflections_db_path: &str,
+        max_idle_connections: usize,
+        table_cache_capacity: usize,
+        logger: Arc<dyn PlsLogger + Send + Sync>,
+    ) -> Result<SharedInflectionGenerator, String> {
+        logger.info(&format!(
+            "Open inflections db pool: '{}'...",
+            inflections_db_path
+        ));
+        let sql_access_pool =
+            SqlAccessPool::open(Path::new(inflections_db_path), max_idle_connections)?;
+
+        let igen = SharedInflectionGenerator {
+            inflection_host: SharedPlsHost {
+                locale: locale.to_string(),
+                version: version.to_string(),
+                url: url.to_string(),
+                cache_id: Some(inflections_db_path.to_string()),
+                table_cache_capacity,
+                sql_access_pool: Arc::new(sql_access_pool),
+                logger,
+            },
+        };
+
+        Ok(igen)
+    }
+}
+
+impl InflectionGenerator for SharedInflectionGenerator {
+    fn check_inflection_db(&self, logger: &dyn PlsLogger) -> Result<(), String> {
+        check_inflection_db(&self.inflection_host, logger)
+    }
+
+    fn generate_inflection_table_html(&self, pali1: &str) -> String {
+        generate_inflection_table_html(pali1, &self.inflection_host)
+    }
 
-        match generate_inflection_table(pali1, false, &self.inflection_host) {
-            Ok(t) => t,
-            Err(e) => {
-                self.inflection_host.logger.warning(&format!(
-                    "Unable to generate inflection table '{}'. Error: {}.",
-                    pali1, e
-                ));
-                "".to_string()
+    fn generate_all_inflections(&self, pali1: &str) -> Vec<String> {
+        generate_all_inflections_or_log(pali1, &self.inflection_host)
+    }
+
+    fn try_generate_all_inflections(&self, pali1: &str) -> Result<Vec<String>, String> {
+        try_generate_all_inflections(pali1, &self.inflection_host)
+    }
+}
+
+fn check_inflection_db(
+    host: &dyn PlsInflectionsHost,
+    logger: &dyn PlsLogger,
+) -> Result<(), String> {
+    match get_version_info(host) {
+        Ok(vi) => {
+            logger.info(&format!(
+                "... loaded version: https://github.com/{}#{}.",
+                vi.repository,
+                &vi.commit_id[0..10]
+            ));
+            if cache::invalidate_if_db_version_changed(host)? {
+                logger.info("... inflections db changed, dropped cached lookups.");
             }
+            Ok(())
+        }
+        Err(e) => {
+            logger.error(&e);
+            Err(e)
         }
     }
+}
 
-    fn generate_all_inflections(&self, pali1: &str) -> Vec<String> {
-        if is_black_listed_word(pali1) {
-            return vec![];
+fn generate_inflection_table_html(pali1: &str, host: &dyn PlsInflectionsHost) -> String {
+    if is_black_listed_word(pali1) {
+        return "".to_string();
+    }
+
+    match generate_inflection_table(pali1, false, host) {
+        Ok(t) => t,
+        Err(e) => {
+            host.log_warning(&format!(
+                "Unable to generate inflection table '{}'. Error: {}.",
+                pali1, e
+            ));
+            "".to_string()
         }
+    }
+}
 
-        match generate_all_inflections(pali1, &self.inflection_host) {
-            Ok(inflections) => inflections,
-            Err(e) => {
-                self.inflection_host.logger.warning(&format!(
-                    "Unable to generate inflections for '{}'. Error: {}.",
-                    pali1, e
-                ));
-                Vec::new()
-            }
+fn generate_all_inflections_or_log(pali1: &str, host: &dyn PlsInflectionsHost) -> Vec<String> {
+    match try_generate_all_inflections(pali1, host) {
+        Ok(inflections) => inflections,
+        Err(e) => {
+            host.log_warning(&format!(
+                "Unable to generate inflections for '{}'. Error: {}.",
+                pali1, e
+            ));
+            Vec::new()
         }
     }
 }
 
+fn try_generate_all_inflections(
+    pali1: &str,
+    host: &dyn PlsInflectionsHost,
+) -> Result<Vec<String>, String> {
+    if is_black_listed_word(pali1) {
+        return Ok(vec![]);
+    }
+
+    generate_all_inflections(pali1, host)
+}
+
+pub struct VersionInfo {
+    pub commit_id: String,
+    pub repository: String,
+}
+
+/// Reads the _version row that the inflection-generator repo stamps into inflections.db.
+pub fn get_version_info(host: &dyn PlsInflectionsHost) -> Result<VersionInfo, String> {
+    let ver_info = host
+        .exec_sql_query("select * from _version")
+        .map_err(|e| format!("Unable to load inflection db. Error: {}.", e))?;
+
+    if ver_info.len() != 1 || ver_info[0].len() != 1 || ver_info[0][0].len() != 3 {
+        return Err("Invalid inflection db. Unexpected data in _version table.".to_string());
+    }
+
+    let commit_id = &ver_info[0][0][0];
+    let repository = &ver_info[0][0][2];
+    if commit_id.len() != 40 || repository != "digitalpalitools/inflection-generator" {
+        return Err(format!(
+            "Invalid inflection db: commid_id: {}, repository: {}.",
+            commit_id, repository
+        ));
+    }
+
+    Ok(VersionInfo {
+        commit_id: commit_id.to_owned(),
+        repository: repository.to_owned(),
+    })
+}
+
 fn is_black_listed_word(pali1: &str) -> bool {
     let prefix: &str = &PLS_INFLECTION_GENERATOR_PREFIX;
     !prefix.is_empty() && !pali1.starts_with(prefix)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn shared_inflection_generator_is_send_sync() {
+        assert_send_sync::<SqlAccessPool>();
+        assert_send_sync::<SharedPlsHost>();
+        assert_send_sync::<SharedInflectionGenerator>();
+    }
+}
@@ -1,7 +1,13 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod cscd;
 pub mod host;
+pub mod inflection_db_diff;
+pub mod inflection_db_validator;
 pub mod inflection_generator;
 pub mod logger;
 pub mod sql_access;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod test_fixtures;
+pub mod transliterator;
@@ -1,48 +1,365 @@
 use chrono::Local;
 use colored::*;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
 
 fn get_time_stamp() -> String {
     Local::now().format("%y-%m-%d %H:%M:%S").to_string()
 }
 
-pub trait PlsLogger {
-    fn info(&self, msg: &str);
-    fn error(&self, msg: &str);
-    fn warning(&self, msg: &str);
+/// Most severe first, so a logger's max level lets through the levels less than or equal to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
 }
 
-pub struct ColoredConsoleLogger;
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warning => "warning",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
 
-impl PlsLogger for ColoredConsoleLogger {
-    fn info(&self, msg: &str) {
-        println!(
-            "{} {}",
-            get_time_stamp().white(),
-            format!("info: {}", msg).green(),
-        );
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(LogLevel::Error),
+            "warning" => Ok(LogLevel::Warning),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!(
+                "Unknown log level '{}'. Expected error, warning, info, debug or trace.",
+                s
+            )),
+        }
+    }
+}
+
+/// Loggers implement log; the rest are shorthands for it. fields are key-value pairs logged with
+/// the message, e.g. [("status", "404")].
+pub trait PlsLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]);
+
+    /// Whether messages at the level are logged, so callers can skip building them.
+    fn enabled(&self, _level: LogLevel) -> bool {
+        true
     }
 
     fn error(&self, msg: &str) {
-        println!(
-            "{} {}",
-            get_time_stamp().white(),
-            format!("error: {}", msg).red(),
-        );
+        self.log(LogLevel::Error, msg, &[])
     }
 
     fn warning(&self, msg: &str) {
-        println!(
-            "{} {}",
-            get_time_stamp().white(),
-            format!("warning: {}", msg).yellow(),
-        );
+        self.log(LogLevel::Warning, msg, &[])
+    }
+
+    fn info(&self, msg: &str) {
+        self.log(LogLevel::Info, msg, &[])
+    }
+
+    fn debug(&self, msg: &str) {
+        self.log(LogLevel::Debug, msg, &[])
+    }
+
+    fn trace(&self, msg: &str) {
+        self.log(LogLevel::Trace, msg, &[])
+    }
+}
+
+pub struct ColoredConsoleLogger;
+
+impl PlsLogger for ColoredConsoleLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        println!("{}", format_colored(level, msg, fields));
     }
 }
 
+/// Same as ColoredConsoleLogger but writes to stderr, leaving stdout to the program output.
+pub struct ColoredStderrLogger;
+
+impl PlsLogger for ColoredStderrLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        eprintln!("{}", format_colored(level, msg, fields));
+    }
+}
+
+fn format_colored(level: LogLevel, msg: &str, fields: &[(&str, &str)]) -> String {
+    let line = format!("{}: {}{}", level.as_str(), msg, format_fields(fields));
+    let line = match level {
+        LogLevel::Error => line.red(),
+        LogLevel::Warning => line.yellow(),
+        LogLevel::Info => line.green(),
+        LogLevel::Debug => line.blue(),
+        LogLevel::Trace => line.dimmed(),
+    };
+
+    format!("{} {}", get_time_stamp().white(), line)
+}
+
+/// " key=value" per field, the value quoted if it has spaces or quotes.
+fn format_fields(fields: &[(&str, &str)]) -> String {
+    fields
+        .iter()
+        .map(|(k, v)| {
+            if v.is_empty() || v.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+                format!(" {}={:?}", k, v)
+            } else {
+                format!(" {}={}", k, v)
+            }
+        })
+        .collect()
+}
+
 pub struct NullLogger;
 
 impl PlsLogger for NullLogger {
-    fn info(&self, _msg: &str) {}
-    fn error(&self, _msg: &str) {}
-    fn warning(&self, _msg: &str) {}
+    fn log(&self, _level: LogLevel, _msg: &str, _fields: &[(&str, &str)]) {}
+
+    fn enabled(&self, _level: LogLevel) -> bool {
+        false
+    }
+}
+
+/// Drops the messages less severe than max_level.
+pub struct FilteredLogger<L: PlsLogger> {
+    pub logger: L,
+    pub max_level: LogLevel,
+}
+
+impl<L: PlsLogger> PlsLogger for FilteredLogger<L> {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        if self.enabled(level) {
+            self.logger.log(level, msg, fields);
+        }
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        level <= self.max_level && self.logger.enabled(level)
+    }
+}
+
+/// One JSON object per line, e.g.
+/// {"ts":"2021-03-01T10:00:00+05:30","level":"info","msg":"GET /health 200","fields":{"status":"200"}}
+pub struct JsonLinesLogger {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesLogger {
+    pub fn new(writer: Box<dyn Write + Send>) -> JsonLinesLogger {
+        JsonLinesLogger {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn stdout() -> JsonLinesLogger {
+        JsonLinesLogger::new(Box::new(io::stdout()))
+    }
+
+    /// Appends to the file, creating it if needed.
+    pub fn create(path: &Path) -> Result<JsonLinesLogger, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Unable to open log file '{}': {}", path.display(), e))?;
+
+        Ok(JsonLinesLogger::new(Box::new(file)))
+    }
+}
+
+impl PlsLogger for JsonLinesLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        let mut line = serde_json::json!({
+            "ts": Local::now().to_rfc3339(),
+            "level": level.as_str(),
+            "msg": msg,
+        });
+        if !fields.is_empty() {
+            line["fields"] = fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), serde_json::Value::from(*v)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+
+        // Nowhere to report a failing log sink, and the line is better lost than the request.
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+    }
+}
+
+/// Forwards to the log crate, the fields appended to the message as key=value.
+pub struct LogCrateLogger {
+    pub target: &'static str,
+}
+
+impl Default for LogCrateLogger {
+    fn default() -> Self {
+        LogCrateLogger { target: "pls" }
+    }
+}
+
+impl LogCrateLogger {
+    fn to_log_level(level: LogLevel) -> log::Level {
+        match level {
+            LogLevel::Error => log::Level::Error,
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Trace => log::Level::Trace,
+        }
+    }
+}
+
+impl PlsLogger for LogCrateLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        log::log!(
+            target: self.target,
+            Self::to_log_level(level),
+            "{}{}",
+            msg,
+            format_fields(fields)
+        );
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        log::log_enabled!(target: self.target, Self::to_log_level(level))
+    }
+}
+
+/// Forwards to tracing as events, the fields in one key=value field as tracing field names must be
+/// known at compile time.
+pub struct TracingLogger;
+
+macro_rules! tracing_event {
+    ($level:expr, $msg:expr, $fields:expr) => {
+        if $fields.is_empty() {
+            tracing::event!($level, "{}", $msg)
+        } else {
+            tracing::event!($level, fields = %format_fields($fields).trim_start(), "{}", $msg)
+        }
+    };
+}
+
+impl PlsLogger for TracingLogger {
+    fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+        match level {
+            LogLevel::Error => tracing_event!(tracing::Level::ERROR, msg, fields),
+            LogLevel::Warning => tracing_event!(tracing::Level::WARN, msg, fields),
+            LogLevel::Info => tracing_event!(tracing::Level::INFO, msg, fields),
+            LogLevel::Debug => tracing_event!(tracing::Level::DEBUG, msg, fields),
+            LogLevel::Trace => tracing_event!(tracing::Level::TRACE, msg, fields),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::sync::Arc;
+    use test_case::test_case;
+
+    struct RecordingLogger(RefCell<Vec<String>>);
+
+    impl PlsLogger for RecordingLogger {
+        fn log(&self, level: LogLevel, msg: &str, fields: &[(&str, &str)]) {
+            self.0.borrow_mut().push(format!(
+                "{}: {}{}",
+                level.as_str(),
+                msg,
+                format_fields(fields)
+            ));
+        }
+    }
+
+    #[derive(Clone)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test_case("trace" => Ok(LogLevel::Trace))]
+    #[test_case("warning" => Ok(LogLevel::Warning))]
+    #[test_case("warn" => Err("Unknown log level 'warn'. Expected error, warning, info, debug or trace.".to_string()))]
+    fn log_level_from_str_tests(s: &str) -> Result<LogLevel, String> {
+        s.parse()
+    }
+
+    #[test_case(&[] => "")]
+    #[test_case(&[("status", "404")] => " status=404")]
+    #[test_case(&[("url", "/table/a 1"), ("q", "")] => r#" url="/table/a 1" q="""#)]
+    fn format_fields_tests(fields: &[(&str, &str)]) -> String {
+        format_fields(fields)
+    }
+
+    #[test]
+    fn filtered_logger_drops_less_severe_messages() {
+        let logger = FilteredLogger {
+            logger: RecordingLogger(RefCell::new(Vec::new())),
+            max_level: LogLevel::Info,
+        };
+
+        logger.error("e");
+        logger.info("i");
+        logger.debug("d");
+        logger.log(LogLevel::Trace, "t", &[("k", "v")]);
+
+        assert_eq!(
+            *logger.logger.0.borrow(),
+            vec!["error: e".to_string(), "info: i".to_string()]
+        );
+        assert!(!logger.enabled(LogLevel::Debug));
+    }
+
+    #[test]
+    fn json_lines_logger_writes_one_object_per_line() {
+        let buffer = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+        let logger = JsonLinesLogger::new(Box::new(buffer.clone()));
+
+        logger.warning("no fields");
+        logger.log(
+            LogLevel::Info,
+            "GET /health 200",
+            &[("method", "GET"), ("status", "200")],
+        );
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["ts"].is_string());
+        assert_eq!(lines[0]["level"], "warning");
+        assert_eq!(lines[0]["msg"], "no fields");
+        assert!(lines[0].get("fields").is_none());
+        assert_eq!(lines[1]["level"], "info");
+        assert_eq!(
+            lines[1]["fields"],
+            serde_json::json!({"method": "GET", "status": "200"})
+        );
+    }
 }
@@ -1,7 +1,12 @@
-use rusqlite::{Connection, Row, NO_PARAMS};
+use rusqlite::{Connection, OpenFlags, Row, ToSql, NO_PARAMS};
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+const STATEMENT_CACHE_CAPACITY: usize = 64;
 
 pub struct SqlAccess {
     pub connection: Connection,
@@ -27,7 +32,10 @@ impl SqlAccess {
     pub fn exec(&self, sql: &str) -> Result<Vec<Vec<Vec<String>>>, String> {
         let mut result: Vec<Vec<Vec<String>>> = Vec::new();
         for s in sql.split(';').filter(|s| !s.trim().is_empty()) {
-            let mut stmt = self.connection.prepare(s).map_err(|e| e.to_string())?;
+            let mut stmt = self
+                .connection
+                .prepare_cached(s)
+                .map_err(|e| e.to_string())?;
             let mut rows = stmt.query(NO_PARAMS).map_err(|e| e.to_string())?;
 
             let mut table: Vec<Vec<String>> = Vec::new();
@@ -52,6 +60,125 @@ impl SqlAccess {
             Err("sql query did not return a scalar".to_string())
         }
     }
+
+    /// Executes a single parameterised statement once per row of parameters, all within one
+    /// transaction. The statement is prepared once and reused, so values are never spliced into
+    /// the sql text. Returns the total number of rows changed.
+    pub fn exec_bulk_insert<I, P>(&self, sql: &str, rows: I) -> Result<usize, String>
+    where
+        I: IntoIterator<Item = P>,
+        P: IntoIterator,
+        P::Item: ToSql,
+    {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|e| e.to_string())?;
+
+        let mut rows_changed = 0;
+        {
+            let mut stmt = tx.prepare_cached(sql).map_err(|e| e.to_string())?;
+            for params in rows {
+                rows_changed += stmt.execute(params).map_err(|e| e.to_string())?;
+            }
+        }
+
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(rows_changed)
+    }
+}
+
+/// Read-only connections to one db, shared by many threads. A thread gets back the connection it
+/// used last when that one is idle, so the prepared statement cache of each connection stays warm
+/// for the queries its thread runs.
+pub struct SqlAccessPool {
+    db_path: PathBuf,
+    max_idle: usize,
+    idle: Mutex<Vec<(ThreadId, SqlAccess)>>,
+}
+
+impl SqlAccessPool {
+    /// Opens the first connection right away so a bad path fails here rather than on first use.
+    pub fn open(db_path: &Path, max_idle: usize) -> Result<SqlAccessPool, String> {
+        let pool = SqlAccessPool {
+            db_path: db_path.to_path_buf(),
+            max_idle,
+            idle: Mutex::new(Vec::new()),
+        };
+        let sql_access = pool.open_connection()?;
+        pool.lock_idle().push((thread::current().id(), sql_access));
+
+        Ok(pool)
+    }
+
+    pub fn get(&self) -> Result<PooledSqlAccess<'_>, String> {
+        let thread_id = thread::current().id();
+        let sql_access = {
+            let mut idle = self.lock_idle();
+            let pos = idle.iter().rposition(|(tid, _)| *tid == thread_id);
+            match pos {
+                Some(pos) => Some(idle.swap_remove(pos).1),
+                None => idle.pop().map(|(_, sa)| sa),
+            }
+        };
+
+        let sql_access = match sql_access {
+            Some(sa) => sa,
+            None => self.open_connection()?,
+        };
+
+        Ok(PooledSqlAccess {
+            pool: self,
+            sql_access: Some(sql_access),
+        })
+    }
+
+    pub fn idle_count(&self) -> usize {
+        self.lock_idle().len()
+    }
+
+    fn open_connection(&self) -> Result<SqlAccess, String> {
+        let connection = Connection::open_with_flags(
+            &self.db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| format!("Cannot open db '{}'. Error: {}.", self.db_path.display(), e))?;
+        connection.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+        Ok(SqlAccess { connection })
+    }
+
+    fn lock_idle(&self) -> std::sync::MutexGuard<'_, Vec<(ThreadId, SqlAccess)>> {
+        // The idle list is only pushed to and popped from, so it is consistent even if a holder
+        // of the lock panicked.
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A connection checked out of a SqlAccessPool. It goes back to the pool when dropped, unless the
+/// pool already holds max_idle connections.
+pub struct PooledSqlAccess<'a> {
+    pool: &'a SqlAccessPool,
+    sql_access: Option<SqlAccess>,
+}
+
+impl Deref for PooledSqlAccess<'_> {
+    type Target = SqlAccess;
+
+    fn deref(&self) -> &SqlAccess {
+        self.sql_access.as_ref().expect("only taken on drop")
+    }
+}
+
+impl Drop for PooledSqlAccess<'_> {
+    fn drop(&mut self) {
+        if let Some(sql_access) = self.sql_access.take() {
+            let mut idle = self.pool.lock_idle();
+            if idle.len() < self.pool.max_idle {
+                idle.push((thread::current().id(), sql_access));
+            }
+        }
+    }
 }
 
 pub fn create_sql_access() -> SqlAccess {
@@ -215,4 +342,135 @@ pub mod tests {
 
         assert_eq!(ret, Err("invalid digit found in string".to_string()));
     }
+
+    fn create_in_memory_sql_access() -> SqlAccess {
+        let sa = SqlAccess {
+            connection: Connection::open_in_memory().expect("must be able to open in memory db"),
+        };
+        sa.exec("CREATE TABLE t (word TEXT NOT NULL, id INTEGER NOT NULL)")
+            .expect("unexpected test setup failure");
+
+        sa
+    }
+
+    #[test]
+    fn test_exec_bulk_insert_ok() {
+        let sa = create_in_memory_sql_access();
+        let rows: Vec<(String, i64)> = vec![
+            ("dhamma".to_string(), 1),
+            ("ti'".to_string(), 2),
+            ("a;b".to_string(), 3),
+            ("'); DROP TABLE t; --".to_string(), 4),
+        ];
+
+        let ret = sa.exec_bulk_insert(
+            "INSERT INTO t (word, id) VALUES (?1, ?2)",
+            rows.iter()
+                .map(|(w, id)| vec![w as &dyn ToSql, id as &dyn ToSql]),
+        );
+
+        assert_eq!(ret, Ok(4));
+        assert_eq!(
+            sa.exec("SELECT word, CAST(id AS TEXT) FROM t ORDER BY id"),
+            Ok(vec![vec![
+                vec!["dhamma".to_string(), "1".to_string()],
+                vec!["ti'".to_string(), "2".to_string()],
+                vec!["a;b".to_string(), "3".to_string()],
+                vec!["'); DROP TABLE t; --".to_string(), "4".to_string()],
+            ]])
+        );
+    }
+
+    #[test]
+    fn test_exec_bulk_insert_err_rolls_back() {
+        let sa = create_in_memory_sql_access();
+        let rows: Vec<Vec<Option<&str>>> = vec![vec![Some("dhamma")], vec![None]];
+
+        let ret = sa.exec_bulk_insert("INSERT INTO t (word, id) VALUES (?1, 1)", rows);
+
+        assert_eq!(ret, Err("NOT NULL constraint failed: t.word".to_string()));
+        assert_eq!(
+            sa.exec_scalar::<i32>("SELECT CAST(COUNT(*) as text) FROM t"),
+            Ok(0)
+        );
+    }
+
+    fn create_pool_db(name: &str) -> PathBuf {
+        let db_path =
+            std::env::temp_dir().join(format!("pls_pool_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        let connection = Connection::open(&db_path).expect("unexpected test setup failure");
+        connection
+            .execute_batch("CREATE TABLE t (word TEXT); INSERT INTO t VALUES ('dhamma');")
+            .expect("unexpected test setup failure");
+
+        db_path
+    }
+
+    #[test]
+    fn test_pool_reuses_connections() {
+        let db_path = create_pool_db("reuse");
+        let pool = SqlAccessPool::open(&db_path, 2).expect("unexpected pool failure");
+
+        {
+            let sa1 = pool.get().expect("unexpected pool failure");
+            let sa2 = pool.get().expect("unexpected pool failure");
+            let sa3 = pool.get().expect("unexpected pool failure");
+            assert_eq!(pool.idle_count(), 0);
+            for sa in &[sa1, sa2, sa3] {
+                assert_eq!(
+                    sa.exec("SELECT word FROM t"),
+                    Ok(vec![vec![vec!["dhamma".to_string()]]])
+                );
+            }
+        }
+
+        assert_eq!(pool.idle_count(), 2);
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_pool_connections_are_read_only() {
+        let db_path = create_pool_db("read_only");
+        let pool = SqlAccessPool::open(&db_path, 1).expect("unexpected pool failure");
+
+        let ret = pool
+            .get()
+            .expect("unexpected pool failure")
+            .exec("INSERT INTO t VALUES ('buddha')");
+
+        assert_eq!(ret, Err("attempt to write a readonly database".to_string()));
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_pool_is_shared_across_threads() {
+        let db_path = create_pool_db("threads");
+        let pool =
+            std::sync::Arc::new(SqlAccessPool::open(&db_path, 4).expect("unexpected pool failure"));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    pool.get()?
+                        .exec_scalar::<i32>("SELECT CAST(COUNT(*) AS TEXT) FROM t")
+                })
+            })
+            .collect();
+
+        for h in handles {
+            assert_eq!(h.join().expect("thread panicked"), Ok(1));
+        }
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_pool_open_err() {
+        let ret = SqlAccessPool::open(Path::new("/nonexistent/pls.db"), 1);
+
+        assert!(ret
+            .err()
+            .is_some_and(|e| e.starts_with("Cannot open db '/nonexistent/pls.db'. Error: unable")));
+    }
 }
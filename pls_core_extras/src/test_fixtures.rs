@@ -0,0 +1,63 @@
+use crate::host::PlsHost;
+use crate::inflection_generator::PlsInflectionGenerator;
+use crate::logger::PlsLogger;
+use crate::sql_access::SqlAccess;
+use rusqlite::Connection;
+
+/// A small inflections.db: the declinable ababa 1 and the indeclinable a 1, the a masc pattern,
+/// the parameter values and an empty _all_words. Tests add their own rows on top.
+pub const TEST_DB_SQL: &str = r#"
+    CREATE TABLE _version (commit_id TEXT, date TEXT, repository TEXT);
+    INSERT INTO _version VALUES ('0123456789012345678901234567890123456789', '', 'digitalpalitools/inflection-generator');
+    CREATE TABLE _stems (pāli1 TEXT, stem TEXT, pattern TEXT, pos TEXT, definition TEXT);
+    INSERT INTO _stems VALUES ('ababa 1', 'abab', 'a masc', 'masc', 'bird');
+    INSERT INTO _stems VALUES ('a 1', '-', '', 'ind', 'not');
+    CREATE TABLE _index (name TEXT, inflection_class TEXT, like TEXT);
+    INSERT INTO _index VALUES ('a masc', '', 'dhamma');
+    CREATE TABLE a_masc ("case" TEXT, gender TEXT, "number" TEXT, inflections TEXT);
+    INSERT INTO a_masc VALUES ('nom', 'masc', 'sg', 'o');
+    INSERT INTO a_masc VALUES ('nom', 'masc', 'pl', 'ā');
+    INSERT INTO a_masc VALUES ('', '', '', 'a');
+    CREATE TABLE _case_values (name TEXT);
+    INSERT INTO _case_values VALUES (''), ('nom');
+    CREATE TABLE _gender_values (name TEXT);
+    INSERT INTO _gender_values VALUES (''), ('masc'), ('fem'), ('nt'), ('x');
+    CREATE TABLE _number_values (name TEXT);
+    INSERT INTO _number_values VALUES (''), ('sg'), ('pl'), ('dual');
+    CREATE TABLE _tense_values (name TEXT);
+    INSERT INTO _tense_values VALUES (''), ('pr');
+    CREATE TABLE _all_words (inflectionIndex INTEGER PRIMARY KEY, inflection TEXT NOT NULL, inflection_folded TEXT NOT NULL, stem_id INTEGER NOT NULL);
+"#;
+
+/// An in-memory TEST_DB_SQL with the statements of the test run after it.
+pub fn create_test_sql_access(sql: &str) -> SqlAccess {
+    let connection = Connection::open_in_memory().expect("unexpected test setup failure");
+    let sql_access = SqlAccess { connection };
+    sql_access
+        .exec(TEST_DB_SQL)
+        .and_then(|_| sql_access.exec(sql))
+        .expect("unexpected test setup failure");
+
+    sql_access
+}
+
+pub fn create_test_host<'a>(sql: &str, logger: &'a dyn PlsLogger) -> PlsHost<'a> {
+    PlsHost {
+        locale: "en",
+        version: "v0.1",
+        url: "test case",
+        cache_id: None,
+        table_cache_capacity: 0,
+        sql_access: create_test_sql_access(sql),
+        logger,
+    }
+}
+
+pub fn create_test_generator<'a>(
+    sql: &str,
+    logger: &'a dyn PlsLogger,
+) -> PlsInflectionGenerator<'a> {
+    PlsInflectionGenerator {
+        inflection_host: create_test_host(sql, logger),
+    }
+}
@@ -0,0 +1,151 @@
+use pls_core::alphabet::{Character, CharacterTokenizer, PaliAlphabet};
+use std::str::FromStr;
+
+const VOWEL_COUNT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Roman,
+    Devanagari,
+    Sinhala,
+    Thai,
+}
+
+impl FromStr for Script {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "roman" | "latn" => Ok(Script::Roman),
+            "devanagari" | "deva" => Ok(Script::Devanagari),
+            "sinhala" | "sinh" => Ok(Script::Sinhala),
+            "thai" => Ok(Script::Thai),
+            _ => Err(format!("Unknown script '{}'.", s)),
+        }
+    }
+}
+
+struct ScriptTable {
+    /// Independent vowels followed by consonants, indexed by PaliAlphabet (excluding ṃ).
+    letters: [&'static str; 40],
+    /// Dependent vowel signs, indexed by PaliAlphabet. The inherent 'a' has no sign.
+    vowel_signs: [&'static str; VOWEL_COUNT],
+    virama: &'static str,
+    niggahita: &'static str,
+    /// Vowel signs that are written before the consonant they follow in speech (Thai e, o).
+    preposed_vowels: &'static [PaliAlphabet],
+}
+
+const DEVANAGARI: ScriptTable = ScriptTable {
+    letters: [
+        "अ", "आ", "इ", "ई", "उ", "ऊ", "ए", "ओ", // vowels
+        "क", "ख", "ग", "घ", "ङ", "च", "छ", "ज", "झ", "ञ", "ट", "ठ", "ड", "ढ", "ण", "त", "थ", "द",
+        "ध", "न", "प", "फ", "ब", "भ", "म", "य", "र", "ल", "व", "स", "ह", "ळ",
+    ],
+    vowel_signs: ["", "ा", "ि", "ी", "ु", "ू", "े", "ो"],
+    virama: "्",
+    niggahita: "ं",
+    preposed_vowels: &[],
+};
+
+const SINHALA: ScriptTable = ScriptTable {
+    letters: [
+        "අ", "ආ", "ඉ", "ඊ", "උ", "ඌ", "එ", "ඔ", // vowels
+        "ක", "ඛ", "ග", "ඝ", "ඞ", "ච", "ඡ", "ජ", "ඣ", "ඤ", "ට", "ඨ", "ඩ", "ඪ", "ණ", "ත", "ථ", "ද",
+        "ධ", "න", "ප", "ඵ", "බ", "භ", "ම", "ය", "ර", "ල", "ව", "ස", "හ", "ළ",
+    ],
+    vowel_signs: ["", "ා", "ි", "ී", "ු", "ූ", "ෙ", "ො"],
+    virama: "්",
+    niggahita: "ං",
+    preposed_vowels: &[],
+};
+
+const THAI: ScriptTable = ScriptTable {
+    letters: [
+        "อ", "อา", "อิ", "อี", "อุ", "อู", "เอ", "โอ", // vowels
+        "ก", "ข", "ค", "ฆ", "ง", "จ", "ฉ", "ช", "ฌ", "ญ", "ฏ", "ฐ", "ฑ", "ฒ", "ณ", "ต", "ถ", "ท",
+        "ธ", "น", "ป", "ผ", "พ", "ภ", "ม", "ย", "ร", "ล", "ว", "ส", "ห", "ฬ",
+    ],
+    vowel_signs: ["", "า", "ิ", "ี", "ุ", "ู", "เ", "โ"],
+    virama: "ฺ",
+    niggahita: "ํ",
+    preposed_vowels: &[PaliAlphabet::E, PaliAlphabet::O],
+};
+
+/// Converts Pāli in Roman script to the given script. Characters that are not Pāli letters are
+/// copied as is.
+pub fn transliterate(s: &str, script: Script) -> String {
+    let table = match script {
+        Script::Roman => return s.to_string(),
+        Script::Devanagari => &DEVANAGARI,
+        Script::Sinhala => &SINHALA,
+        Script::Thai => &THAI,
+    };
+
+    let mut output = String::with_capacity(s.len() * 3);
+    // Byte offset in output of the consonant that has not yet received a vowel.
+    let mut pending_consonant: Option<usize> = None;
+    for c in CharacterTokenizer::new(s.chars()) {
+        match c {
+            Character::Pali(PaliAlphabet::DotM) => {
+                pending_consonant = None;
+                output.push_str(table.niggahita);
+            }
+            Character::Pali(p) if (p as usize) < VOWEL_COUNT => match pending_consonant.take() {
+                Some(pos) if table.preposed_vowels.contains(&p) => {
+                    output.insert_str(pos, table.vowel_signs[p as usize])
+                }
+                Some(_) => output.push_str(table.vowel_signs[p as usize]),
+                None => output.push_str(table.letters[p as usize]),
+            },
+            Character::Pali(p) => {
+                if pending_consonant.is_some() {
+                    output.push_str(table.virama);
+                }
+                pending_consonant = Some(output.len());
+                output.push_str(table.letters[p as usize]);
+            }
+            Character::Other(o) => {
+                if pending_consonant.take().is_some() {
+                    output.push_str(table.virama);
+                }
+                output.push(o);
+            }
+        }
+    }
+
+    if pending_consonant.is_some() {
+        output.push_str(table.virama);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("buddho", Script::Roman => "buddho")]
+    #[test_case("buddho", Script::Devanagari => "बुद्धो")]
+    #[test_case("evaṃ me sutaṃ", Script::Devanagari => "एवं मे सुतं")]
+    #[test_case("bhikkhave 1", Script::Devanagari => "भिक्खवे 1")]
+    #[test_case("buddho", Script::Sinhala => "බුද්ධො")]
+    #[test_case("ahesuṃ", Script::Sinhala => "අහෙසුං")]
+    #[test_case("buddho", Script::Thai => "พุทฺโธ")]
+    #[test_case("evaṃ me", Script::Thai => "เอวํ เม")]
+    #[test_case("dhammacakkappavattana", Script::Thai => "ธมฺมจกฺกปฺปวตฺตน")]
+    #[test_case("sakkāt", Script::Thai => "สกฺกาตฺ")]
+    fn transliterate_tests(s: &str, script: Script) -> String {
+        transliterate(s, script)
+    }
+
+    #[test]
+    fn script_from_str_test() {
+        assert_eq!(Script::from_str("deva"), Ok(Script::Devanagari));
+        assert_eq!(
+            Script::from_str("xyz"),
+            Err("Unknown script 'xyz'.".to_string())
+        );
+    }
+}
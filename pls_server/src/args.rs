@@ -0,0 +1,192 @@
+use clap::{App, Arg, ArgMatches};
+use pls_core_extras::logger::LogLevel;
+use std::fmt;
+use std::path::Path;
+
+pub(crate) struct AppArgs<'a> {
+    pub inflections_db_path: &'a str,
+    pub address: &'a str,
+    pub threads: usize,
+    pub cache_size: usize,
+    pub table_cache_size: usize,
+    pub verbose: bool,
+    pub log_format: &'a str,
+    pub log_file: Option<&'a str>,
+    pub log_level: LogLevel,
+}
+
+impl fmt::Display for AppArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "... inflections_db_path: {}", self.inflections_db_path)?;
+        writeln!(f, "... address: {}", self.address)?;
+        writeln!(f, "... threads: {}", self.threads)?;
+        writeln!(f, "... cache_size: {}", self.cache_size)?;
+        writeln!(f, "... table_cache_size: {}", self.table_cache_size)?;
+        writeln!(f, "... log_format: {}", self.log_format)?;
+        if let Some(log_file) = self.log_file {
+            writeln!(f, "... log_file: {}", log_file)?;
+        }
+        writeln!(f, "... log_level: {}", self.log_level.as_str())?;
+        Ok(())
+    }
+}
+
+pub(crate) fn get_args<'a>(args: &'a ArgMatches) -> AppArgs<'a> {
+    AppArgs {
+        inflections_db_path: args
+            .value_of("INFLECTION_DB_PATH")
+            .expect("mandatory argument"),
+        address: args.value_of("ADDRESS").expect("has default value"),
+        threads: args
+            .value_of("THREADS")
+            .expect("has default value")
+            .parse::<usize>()
+            .expect("Valid default configured already."),
+        cache_size: args
+            .value_of("CACHE_SIZE")
+            .expect("has default value")
+            .parse::<usize>()
+            .expect("Valid default configured already."),
+        table_cache_size: args
+            .value_of("TABLE_CACHE_SIZE")
+            .expect("has default value")
+            .parse::<usize>()
+            .expect("Valid default configured already."),
+        verbose: args.is_present("VERBOSE"),
+        log_format: args.value_of("LOG_FORMAT").expect("has default value"),
+        log_file: args.value_of("LOG_FILE"),
+        log_level: args
+            .value_of("LOG_LEVEL")
+            .expect("has default value")
+            .parse()
+            .expect("Valid default configured already."),
+    }
+}
+
+pub(crate) fn parse_args<'a>() -> ArgMatches<'a> {
+    App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .arg(create_inflection_db_path_arg())
+        .arg(create_address_arg())
+        .arg(create_threads_arg())
+        .arg(create_cache_size_arg())
+        .arg(create_table_cache_size_arg())
+        .arg(create_verbose_arg())
+        .arg(create_log_format_arg())
+        .arg(create_log_file_arg())
+        .arg(create_log_level_arg())
+        .get_matches()
+}
+
+fn create_inflection_db_path_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("INFLECTION_DB_PATH")
+        .short("i")
+        .long("inflection-db")
+        .value_name("INFLECTION_DB_PATH")
+        .help("The path to inflections.db.")
+        .required(true)
+        .validator(|s| validate_file_exists(&s))
+        .takes_value(true)
+}
+
+fn create_address_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("ADDRESS")
+        .short("a")
+        .long("address")
+        .value_name("ADDRESS")
+        .help("The address to listen on.")
+        .required(false)
+        .takes_value(true)
+        .default_value("127.0.0.1:8080")
+}
+
+fn create_threads_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("THREADS")
+        .short("t")
+        .long("threads")
+        .value_name("THREADS")
+        .help("The number of worker threads, each with its own connection to inflections.db.")
+        .required(false)
+        .validator(|s| validate_positive_usize(&s))
+        .takes_value(true)
+        .default_value("4")
+}
+
+fn create_cache_size_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("CACHE_SIZE")
+        .short("c")
+        .long("cache-size")
+        .value_name("CACHE_SIZE")
+        .help("The maximum number of responses to cache. 0 disables caching.")
+        .required(false)
+        .validator(|s| s.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+        .takes_value(true)
+        .default_value("10000")
+}
+
+fn create_table_cache_size_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("TABLE_CACHE_SIZE")
+        .long("table-cache-size")
+        .value_name("TABLE_CACHE_SIZE")
+        .help("The maximum number of rendered inflection tables to cache. 0 disables caching.")
+        .required(false)
+        .validator(|s| s.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+        .takes_value(true)
+        .default_value("1000")
+}
+
+fn create_verbose_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("VERBOSE")
+        .short("v")
+        .long("verbose")
+        .help("Log each request to stderr.")
+}
+
+fn create_log_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("LOG_FORMAT")
+        .long("log-format")
+        .value_name("LOG_FORMAT")
+        .help("text for coloured lines, json for one JSON object per line.")
+        .required(false)
+        .possible_values(&["text", "json"])
+        .takes_value(true)
+        .default_value("text")
+}
+
+fn create_log_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("LOG_FILE")
+        .long("log-file")
+        .value_name("LOG_FILE")
+        .help("Append the log to this file as JSON lines instead of writing it to the console.")
+        .required(false)
+        .takes_value(true)
+}
+
+fn create_log_level_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("LOG_LEVEL")
+        .long("log-level")
+        .value_name("LOG_LEVEL")
+        .help("The least severe level logged.")
+        .required(false)
+        .possible_values(&["error", "warning", "info", "debug", "trace"])
+        .takes_value(true)
+        .default_value("info")
+}
+
+fn validate_file_exists(s: &str) -> Result<(), String> {
+    if Path::new(&s).is_file() {
+        Ok(())
+    } else {
+        Err(format!("'{}' does not exist.", s))
+    }
+}
+
+fn validate_positive_usize(s: &str) -> Result<(), String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(_n) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
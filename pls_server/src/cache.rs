@@ -0,0 +1,138 @@
+use crate::routes::Response;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Least recently used cache of rendered responses, shared by all worker threads. The lock is not
+/// held while a response is generated, so two threads may race to fill the same key; both produce
+/// the same response and the later one wins.
+pub(crate) struct ResponseCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    tick: u64,
+    entries: HashMap<String, (u64, Arc<Response>)>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(capacity: usize) -> ResponseCache {
+        ResponseCache {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub(crate) fn get_or_insert_with<F>(&self, key: &str, create_response: F) -> Arc<Response>
+    where
+        F: FnOnce() -> Response,
+    {
+        if let Some(response) = self.get(key) {
+            return response;
+        }
+
+        let response = Arc::new(create_response());
+        if response.cacheable && self.capacity > 0 {
+            self.insert(key, response.clone());
+        }
+
+        response
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    fn get(&self, key: &str) -> Option<Arc<Response>> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.get_mut(key).map(|(last_used, response)| {
+            *last_used = tick;
+            response.clone()
+        })
+    }
+
+    fn insert(&self, key: &str, response: Arc<Response>) {
+        let mut state = self.lock();
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(key) {
+            let lru_key = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(lru_key) = lru_key {
+                state.entries.remove(&lru_key);
+            }
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key.to_string(), (tick, response));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // A worker that panicked mid-update leaves at worst a stale entry, so keep serving.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_response(body: &str, cacheable: bool) -> Response {
+        Response {
+            status: 200,
+            content_type: "text/plain",
+            body: body.to_string(),
+            cacheable,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_does_not_regenerate() {
+        let cache = ResponseCache::new(10);
+        cache.get_or_insert_with("/a", || create_response("a", true));
+
+        let response = cache.get_or_insert_with("/a", || panic!("must be served from the cache"));
+
+        assert_eq!(response.body, "a");
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = ResponseCache::new(2);
+        cache.get_or_insert_with("/a", || create_response("a", true));
+        cache.get_or_insert_with("/b", || create_response("b", true));
+        cache.get_or_insert_with("/a", || create_response("a2", true));
+        cache.get_or_insert_with("/c", || create_response("c", true));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache
+                .get_or_insert_with("/a", || create_response("a3", true))
+                .body,
+            "a"
+        );
+        assert_eq!(
+            cache
+                .get_or_insert_with("/b", || create_response("b2", true))
+                .body,
+            "b2"
+        );
+    }
+
+    #[test]
+    fn test_cache_skips_uncacheable_responses() {
+        let cache = ResponseCache::new(10);
+        cache.get_or_insert_with("/health", || create_response("1", false));
+
+        let response = cache.get_or_insert_with("/health", || create_response("2", false));
+
+        assert_eq!(response.body, "2");
+        assert_eq!(cache.len(), 0);
+    }
+}
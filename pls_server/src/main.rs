@@ -0,0 +1,117 @@
+use crate::cache::ResponseCache;
+use crate::routes::{handle_request, Response};
+use pls_core_extras::inflection_generator::{InflectionGenerator, SharedInflectionGenerator};
+use pls_core_extras::logger::{
+    ColoredConsoleLogger, FilteredLogger, JsonLinesLogger, LogLevel, NullLogger, PlsLogger,
+};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Method, Request, Server};
+
+mod args;
+mod cache;
+mod routes;
+
+fn main() -> Result<(), String> {
+    let arg_matches = args::parse_args();
+    let args = args::get_args(&arg_matches);
+
+    let logger = create_logger(&args)?;
+    logger.info("Starting server with the following parameters:");
+    args.to_string().lines().for_each(|l| logger.info(l));
+
+    // Fail fast on a bad db rather than on the first request.
+    let worker_logger: Arc<dyn PlsLogger + Send + Sync> = if args.verbose {
+        logger.clone()
+    } else {
+        Arc::new(NullLogger {})
+    };
+    let igen = Arc::new(SharedInflectionGenerator::new(
+        "en",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_NAME"),
+        args.inflections_db_path,
+        args.threads,
+        args.table_cache_size,
+        worker_logger,
+    )?);
+    igen.check_inflection_db(&*logger)?;
+
+    let server = Arc::new(Server::http(args.address).map_err(|e| e.to_string())?);
+    let cache = Arc::new(ResponseCache::new(args.cache_size));
+    logger.info(&format!("Listening on http://{}/", args.address));
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..args.threads)
+        .map(|_| {
+            let server = server.clone();
+            let cache = cache.clone();
+            let igen = igen.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    serve_request(request, &igen, &cache);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().map_err(|_| "Worker thread panicked.")?;
+    }
+
+    Ok(())
+}
+
+fn create_logger(args: &args::AppArgs) -> Result<Arc<dyn PlsLogger + Send + Sync>, String> {
+    let max_level = args.log_level;
+    let logger: Arc<dyn PlsLogger + Send + Sync> = match (args.log_file, args.log_format) {
+        (Some(path), _) => Arc::new(FilteredLogger {
+            logger: JsonLinesLogger::create(Path::new(path))?,
+            max_level,
+        }),
+        (None, "json") => Arc::new(FilteredLogger {
+            logger: JsonLinesLogger::stdout(),
+            max_level,
+        }),
+        _ => Arc::new(FilteredLogger {
+            logger: ColoredConsoleLogger {},
+            max_level,
+        }),
+    };
+
+    Ok(logger)
+}
+
+fn serve_request(request: Request, igen: &SharedInflectionGenerator, cache: &ResponseCache) {
+    let logger = &*igen.inflection_host.logger;
+    let url = request.url().to_string();
+    let response = match request.method() {
+        Method::Get => {
+            cache.get_or_insert_with(&url, || handle_request(&url, &igen.inflection_host))
+        }
+        _ => Arc::new(Response::error(405, "Only GET is supported.")),
+    };
+    let method = request.method().to_string();
+    logger.log(
+        LogLevel::Info,
+        &format!("{} {} {}", method, url, response.status),
+        &[
+            ("method", &method),
+            ("url", &url),
+            ("status", &response.status.to_string()),
+        ],
+    );
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], response.content_type.as_bytes())
+        .expect("content types are valid header values");
+    let http_response = tiny_http::Response::from_string(response.body.as_str())
+        .with_status_code(response.status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(http_response) {
+        logger.log(
+            LogLevel::Warning,
+            &format!("Unable to send response for '{}'. Error: {}.", url, e),
+            &[("url", &url)],
+        );
+    }
+}
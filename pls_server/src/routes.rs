@@ -0,0 +1,452 @@
+use pls_core::inflections::{
+    analyze_word, generate_all_inflections, generate_inflection_table,
+    generate_inflection_table_data, generate_verb_derivation_table, generate_verb_derivations,
+    get_abbreviations_for_locale,
+    pmd::{find_pali1_metadata, get_pali1_metadata, Pali1MetadataError},
+};
+use pls_core_extras::host::SharedPlsHost;
+use pls_core_extras::inflection_generator::get_version_info;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+const CONTENT_TYPE_JSON: &str = "application/json; charset=utf-8";
+const CONTENT_TYPE_HTML: &str = "text/html; charset=utf-8";
+
+#[derive(Debug)]
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: String,
+    /// Responses depend only on inflections.db, except for the health check which reports on it.
+    pub(crate) cacheable: bool,
+}
+
+impl Response {
+    fn html(body: String) -> Response {
+        Response {
+            status: 200,
+            content_type: CONTENT_TYPE_HTML,
+            body,
+            cacheable: true,
+        }
+    }
+
+    fn json<T: Serialize>(value: &T) -> Response {
+        match serde_json::to_string(value) {
+            Ok(body) => Response {
+                status: 200,
+                content_type: CONTENT_TYPE_JSON,
+                body,
+                cacheable: true,
+            },
+            Err(e) => Response::error(500, &e.to_string()),
+        }
+    }
+
+    pub(crate) fn error(status: u16, msg: &str) -> Response {
+        Response {
+            status,
+            content_type: CONTENT_TYPE_JSON,
+            body: json!({ "error": msg }).to_string(),
+            cacheable: status < 500,
+        }
+    }
+}
+
+/// Routes a GET request url, e.g. '/table/ababa%201?format=json&locale=en', to the inflection
+/// engine.
+pub(crate) fn handle_request(url: &str, host: &SharedPlsHost) -> Response {
+    let (path, query) = match url.find('?') {
+        Some(i) => (&url[..i], parse_query(&url[i + 1..])),
+        None => (url, HashMap::new()),
+    };
+
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| percent_decode(s, false))
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+
+    let result = match segments.as_slice() {
+        ["health"] => Ok(health(host)),
+        ["table", pali1] => find_word(pali1, host).map(|_| table(pali1, &query, host)),
+        ["derivations", pali1] => find_word(pali1, host).map(|_| derivations(pali1, &query, host)),
+        ["forms", pali1] => find_word(pali1, host).map(|_| forms(pali1, host)),
+        ["metadata", pali1] => find_word(pali1, host).map(|_| metadata(pali1, host)),
+        ["analyze", word] => validate_word(word).map(|_| analyze(word, host)),
+        ["abbreviations", locale] => validate_locale(locale).map(|_| abbreviations(locale, host)),
+        _ => Err(Response::error(404, &format!("Unknown route '{}'.", path))),
+    };
+
+    result.unwrap_or_else(|e| e)
+}
+
+fn health(host: &SharedPlsHost) -> Response {
+    match get_version_info(host) {
+        Ok(vi) => Response {
+            cacheable: false,
+            ..Response::json(&json!({
+                "status": "ok",
+                "version": env!("CARGO_PKG_VERSION"),
+                "commitId": vi.commit_id,
+                "repository": vi.repository,
+            }))
+        },
+        Err(e) => Response {
+            cacheable: false,
+            ..Response::error(503, &e)
+        },
+    }
+}
+
+fn table(pali1: &str, query: &HashMap<String, String>, host: &SharedPlsHost) -> Response {
+    let locale = query.get("locale").map_or("en", |l| l.as_str());
+    if let Err(e) = validate_locale(locale) {
+        return e;
+    }
+
+    let host = host.with_locale(locale);
+    match query.get("format").map(|f| f.as_str()) {
+        Some("json") => match generate_inflection_table_data(pali1, &host) {
+            Ok(data) => Response::json(&data),
+            Err(e) => Response::error(500, &e),
+        },
+        None | Some("html") => {
+            let with_details = query.get("details").is_some_and(|d| d == "true");
+            match generate_inflection_table(pali1, with_details, &host) {
+                Ok(html) => Response::html(html),
+                Err(e) => Response::error(500, &e),
+            }
+        }
+        Some(f) => Response::error(400, &format!("Unknown format '{}'.", f)),
+    }
+}
+
+fn derivations(pali1: &str, query: &HashMap<String, String>, host: &SharedPlsHost) -> Response {
+    let locale = query.get("locale").map_or("en", |l| l.as_str());
+    if let Err(e) = validate_locale(locale) {
+        return e;
+    }
+
+    let host = host.with_locale(locale);
+    match query.get("format").map(|f| f.as_str()) {
+        Some("json") => match generate_verb_derivations(pali1, &host) {
+            Ok(derivations) => Response::json(&derivations),
+            Err(e) => Response::error(500, &e),
+        },
+        None | Some("html") => match generate_verb_derivation_table(pali1, false, &host) {
+            Ok(html) => Response::html(html),
+            Err(e) => Response::error(500, &e),
+        },
+        Some(f) => Response::error(400, &format!("Unknown format '{}'.", f)),
+    }
+}
+
+fn forms(pali1: &str, host: &SharedPlsHost) -> Response {
+    match generate_all_inflections(pali1, host) {
+        Ok(forms) => Response::json(&forms),
+        Err(e) => Response::error(500, &e),
+    }
+}
+
+fn metadata(pali1: &str, host: &SharedPlsHost) -> Response {
+    match get_pali1_metadata(pali1, host) {
+        Ok(pm) => Response::json(&pm),
+        Err(e) => Response::error(500, &e),
+    }
+}
+
+fn analyze(word: &str, host: &SharedPlsHost) -> Response {
+    match analyze_word(word, host) {
+        Ok(analyses) => Response::json(&analyses),
+        Err(e) => Response::error(500, &e),
+    }
+}
+
+fn abbreviations(locale: &str, host: &SharedPlsHost) -> Response {
+    let host = host.with_locale(locale);
+    match get_abbreviations_for_locale(&host) {
+        Ok(abbrevs) => Response::json(&abbrevs.into_iter().collect::<BTreeMap<_, _>>()),
+        Err(e) => Response::error(500, &e),
+    }
+}
+
+/// pls_core escapes the words it splices into its sql, so only refuse what cannot be a word at all.
+/// Elided forms such as ti' are words.
+fn validate_word(word: &str) -> Result<(), Response> {
+    if word.trim().is_empty() || word.chars().any(char::is_control) {
+        Err(Response::error(400, &format!("Invalid word '{}'.", word)))
+    } else {
+        Ok(())
+    }
+}
+
+/// Distinguishes a word missing from inflections.db (404) from a failure to inflect it (500).
+fn find_word(pali1: &str, host: &SharedPlsHost) -> Result<(), Response> {
+    validate_word(pali1)?;
+    match find_pali1_metadata(pali1, host) {
+        Ok(_) => Ok(()),
+        Err(Pali1MetadataError::NotFound(e)) => Err(Response::error(404, &e)),
+        Err(Pali1MetadataError::Failed(e)) => Err(Response::error(500, &e)),
+    }
+}
+
+/// Locales are tags such as si or si-LK, looked up among the columns of _abbreviations.
+fn validate_locale(locale: &str) -> Result<(), Response> {
+    if !locale.is_empty()
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(Response::error(
+            400,
+            &format!("Invalid locale '{}'.", locale),
+        ))
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| match kv.find('=') {
+            Some(i) => (
+                percent_decode(&kv[..i], true),
+                percent_decode(&kv[i + 1..], true),
+            ),
+            None => (percent_decode(kv, true), "".to_string()),
+        })
+        .collect()
+}
+
+/// Decodes %XX escapes as utf-8. Malformed escapes are kept as is.
+fn percent_decode(s: &str, plus_as_space: bool) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        decoded.push(b);
+                        i += 3;
+                        continue;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            b'+' if plus_as_space => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pls_core_extras::logger::NullLogger;
+    use pls_core_extras::sql_access::SqlAccessPool;
+    use pls_core_extras::test_fixtures::TEST_DB_SQL;
+    use rusqlite::Connection;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use test_case::test_case;
+
+    const ROUTES_DB_SQL: &str = r#"
+        INSERT INTO _stems VALUES ('bhavati 1', 'bhav', 'ati pr', 'pr', 'is');
+        INSERT INTO _stems VALUES ('phala 1', 'phal', 'a nt', 'nt', 'fruit');
+        INSERT INTO _stems VALUES ('iti', '-', '', 'ind', 'thus');
+        INSERT INTO _index VALUES ('ati pr', 'verb', 'bhavati');
+        INSERT INTO _index VALUES ('a adj', '', 'piya');
+        CREATE TABLE a_adj ("case" TEXT, gender TEXT, "number" TEXT, inflections TEXT);
+        INSERT INTO a_adj VALUES ('nom', 'masc', 'sg', 'o');
+        CREATE TABLE ati_pr (tense TEXT, person TEXT, actreflx TEXT, "number" TEXT, inflections TEXT);
+        INSERT INTO ati_pr VALUES ('pr', '3rd', 'act', 'sg', 'ati');
+        CREATE TABLE _person_values (name TEXT);
+        INSERT INTO _person_values VALUES (''), ('3rd'), ('2nd'), ('1st');
+        CREATE TABLE _actreflx_values (name TEXT);
+        INSERT INTO _actreflx_values VALUES (''), ('act'), ('reflx');
+        INSERT INTO _all_words (inflection, inflection_folded, stem_id) VALUES
+            ('ababo', 'ababo', 1), ('ababā', 'ababa', 1), ('ababa', 'ababa', 1), ('ti''', 'ti''', 5),
+            ('bhavitvā', 'bhavitva', 3);
+        CREATE TABLE _abbreviations (name TEXT, description TEXT, si TEXT);
+        INSERT INTO _abbreviations VALUES ('masc', 'masculine', 'පු'), ('sg', 'singular', 'ඒක');
+        INSERT INTO _abbreviations VALUES ('pl', 'plural', 'බහු'), ('nom', 'nominative', 'පඨ');
+        INSERT INTO _abbreviations VALUES ('in comps', 'in compounds', 'සමාස');
+        INSERT INTO _abbreviations VALUES ('prp', 'present participle', 'වර්'), ('pp', 'past participle', 'අතී');
+        INSERT INTO _abbreviations VALUES ('ptp', 'potential participle', 'කිච්'), ('abs', 'absolutive', 'පුබ්');
+        INSERT INTO _abbreviations VALUES ('inf', 'infinitive', 'තුං'), ('caus', 'causative', 'කාරි'), ('pass', 'passive', 'කම්');
+        INSERT INTO _abbreviations VALUES ('pr', 'present', 'වත්'), ('act', 'active', 'පරස්'), ('reflx', 'reflexive', 'අත්');
+        INSERT INTO _abbreviations VALUES ('1st', 'first person', 'උත්'), ('2nd', 'second person', 'මජ්'), ('3rd', 'third person', 'පඨ')
+    "#;
+
+    static TEST_DB_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn get(url: &str) -> Response {
+        let db_path = std::env::temp_dir().join(format!(
+            "pls_server_routes_{}_{}.db",
+            std::process::id(),
+            TEST_DB_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        Connection::open(&db_path)
+            .and_then(|c| {
+                c.execute_batch(TEST_DB_SQL)?;
+                c.execute_batch(ROUTES_DB_SQL)
+            })
+            .expect("unexpected test setup failure");
+
+        let host = SharedPlsHost {
+            locale: "en".to_string(),
+            version: "v0.1".to_string(),
+            url: "test case".to_string(),
+            cache_id: None,
+            table_cache_capacity: 0,
+            sql_access_pool: Arc::new(
+                SqlAccessPool::open(&db_path, 1).expect("unexpected test setup failure"),
+            ),
+            logger: Arc::new(NullLogger {}),
+        };
+        let response = handle_request(url, &host);
+
+        drop(host);
+        let _ = std::fs::remove_file(&db_path);
+        response
+    }
+
+    #[test_case("/health" => (200, false); "health")]
+    #[test_case("/table/ababa%201?details=true" => (200, true); "table html with details")]
+    #[test_case("/forms/ababa%201" => (200, true); "forms")]
+    #[test_case("/derivations/bhavati%201" => (200, true); "derivations html")]
+    #[test_case("/derivations/bhavati%201?format=json" => (200, true); "derivations json")]
+    #[test_case("/table/ababa%201" => (200, true); "table html")]
+    #[test_case("/table/ababa%201?format=json&locale=si" => (200, true); "table json")]
+    #[test_case("/table/ababa%201?format=xml" => (400, true); "table unknown format")]
+    #[test_case("/table/ababa%201?locale=x%27y" => (400, true); "table invalid locale")]
+    #[test_case("/metadata/kamma%201" => (404, true); "metadata unknown word")]
+    #[test_case("/metadata/a%22%20or%20%221" => (404, true); "metadata quoted word")]
+    #[test_case("/metadata/ti%27" => (404, true); "metadata elided form")]
+    #[test_case("/metadata/%01" => (400, true); "metadata control character")]
+    #[test_case("/analyze/ti%27" => (200, true); "analyze elided form")]
+    #[test_case("/analyze/%20" => (400, true); "analyze blank word")]
+    #[test_case("/table/phala%201" => (500, false); "table pattern not in db")]
+    #[test_case("/abbreviations/de" => (200, true); "abbreviations unknown locale")]
+    #[test_case("/unknown" => (404, true); "unknown route")]
+    fn handle_request_status_tests(url: &str) -> (u16, bool) {
+        let response = get(url);
+        (response.status, response.cacheable)
+    }
+
+    #[test]
+    fn test_forms() {
+        let response = get("/forms/ababa%201");
+
+        assert_eq!(response.content_type, CONTENT_TYPE_JSON);
+        assert_eq!(response.body, r#"["ababo","ababā","ababa"]"#);
+    }
+
+    #[test]
+    fn test_table_json_is_localised_by_query() {
+        let response = get("/table/ababa%201?format=json&locale=si");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(data["metadata"]["pali1"], "ababa 1");
+        assert_eq!(data["cells"][0]["inflections"], json!(["ababo"]));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let response = get("/analyze/abab%C4%81");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(response.status, 200);
+        assert_eq!(data[0]["metadata"]["pali1"], "ababa 1");
+        assert_eq!(
+            data[0]["parameters"],
+            json!([{ "case": "nom", "gender": "masc", "number": "pl" }])
+        );
+    }
+
+    #[test]
+    fn test_analyze_elided_form() {
+        let response = get("/analyze/ti%27");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(response.status, 200);
+        assert_eq!(data[0]["metadata"]["pali1"], "iti");
+    }
+
+    #[test]
+    fn test_derivations_render_tables_for_patterns_in_db() {
+        let response = get("/derivations/bhavati%201?format=json");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(data[1]["word"], "bhavamāna");
+        assert_eq!(data[1]["stem"], "bhavamān");
+        assert_eq!(data[1]["pattern"], "a adj");
+        assert_eq!(data[0]["word"], "bhavant");
+        assert_eq!(data[0]["pattern"], serde_json::Value::Null);
+        assert_eq!(data[5]["word"], "bhavitvā");
+        assert_eq!(data[5]["attested"], true);
+        assert_eq!(data[11]["word"], "bhūyati");
+        assert_eq!(data[11]["attested"], false);
+
+        let response = get("/derivations/bhavati%201");
+        assert!(response
+            .body
+            .contains(r#"<div class="pls-inflection-inflected-word">bhavitvā</div>"#));
+        assert!(response.body.contains(r#"bhavamāna "a adj""#));
+        assert!(response.body.contains("bhavamāno"));
+        assert!(response.body.contains("bhāveti*"));
+        assert!(response.body.contains(r#"bhūyati "ati pr""#));
+        assert!(response.body.contains("not found in the dictionary"));
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let response = get("/abbreviations/si");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(data["masc"], "පු");
+        assert_eq!(data["masculine"], "පු");
+        assert_eq!(data["in comps"], "සමාස");
+    }
+
+    #[test]
+    fn test_abbreviations_fall_back_along_locale_chain() {
+        let response = get("/abbreviations/si-LK");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+        assert_eq!(data["masc"], "පු");
+
+        let response = get("/abbreviations/de");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+        assert_eq!(data["masc"], "masc");
+    }
+
+    #[test]
+    fn test_health() {
+        let response = get("/health");
+        let data: serde_json::Value = serde_json::from_str(&response.body).expect("valid json");
+
+        assert_eq!(data["status"], "ok");
+        assert_eq!(data["commitId"], "0123456789012345678901234567890123456789");
+    }
+
+    #[test_case("ababa%201", false => "ababa 1")]
+    #[test_case("ababa+1", false => "ababa+1")]
+    #[test_case("ababa+1", true => "ababa 1")]
+    #[test_case("%E1%B9%83", false => "ṃ")]
+    #[test_case("100%", false => "100%")]
+    #[test_case("%zz%", false => "%zz%")]
+    fn percent_decode_tests(s: &str, plus_as_space: bool) -> String {
+        percent_decode(s, plus_as_space)
+    }
+}
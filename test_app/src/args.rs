@@ -0,0 +1,371 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use pls_core_extras::transliterator::Script;
+use std::path::Path;
+use std::str::FromStr;
+
+pub(crate) fn parse_args<'a>() -> ArgMatches<'a> {
+    App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(create_inflection_db_path_arg())
+        .arg(create_verbose_arg())
+        .subcommand(
+            SubCommand::with_name("table")
+                .about("Generate the inflection table for a head word.")
+                .arg(create_pali1_arg())
+                .arg(create_locale_arg())
+                .arg(create_format_arg())
+                .arg(
+                    Arg::with_name("DETAILS")
+                        .short("d")
+                        .long("details")
+                        .help("Include the part of speech and meaning in the html."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("derivations")
+                .about("Generate the participles, absolutives, infinitives, causatives and passives of a verb.")
+                .arg(create_pali1_arg())
+                .arg(create_locale_arg())
+                .arg(create_format_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("forms")
+                .about("List all inflected forms of a head word.")
+                .arg(create_pali1_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("analyze")
+                .about("List the head words and table cells an inflected word comes from. Needs _all_words.")
+                .arg(
+                    Arg::with_name("WORD")
+                        .help("The inflected word.")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("Find the head words and inflected forms that match a query ignoring diacritics, printed as json. Needs _all_words.")
+                .arg(
+                    Arg::with_name("QUERY")
+                        .help("The query, e.g. panna for paññā.")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("MAX_RESULTS")
+                        .short("n")
+                        .long("max-results")
+                        .value_name("MAX_RESULTS")
+                        .help("The maximum number of results.")
+                        .default_value("50")
+                        .validator(|s| validate_u32(&s))
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("suggest")
+                .about("Suggest corrections for each misspelled word (or line of stdin) with their head words, printed as json. Needs _all_words.")
+                .arg(create_words_arg())
+                .arg(
+                    Arg::with_name("MAX_RESULTS")
+                        .short("n")
+                        .long("max-results")
+                        .value_name("MAX_RESULTS")
+                        .help("The maximum number of suggestions per word.")
+                        .default_value("10")
+                        .validator(|s| validate_u32(&s))
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("MAX_COST")
+                        .short("c")
+                        .long("max-cost")
+                        .value_name("MAX_COST")
+                        .help("The maximum edit cost of a suggestion: 10 per edit, 2 or 3 for a/ā, k/kh, ṭ/t, ṃ/n or t/tt.")
+                        .default_value("20")
+                        .validator(|s| validate_u32(&s))
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("restore-diacritics")
+                .about("Restore the diacritics of the words of stdin, typed without them, using _all_words and the word frequencies of a corpus.")
+                .arg(
+                    Arg::with_name("CORPUS_PATHS")
+                        .short("c")
+                        .long("corpus")
+                        .value_name("CORPUS_PATHS")
+                        .help("Text files, CSCD .xml books, or directories of CSCD books to count the words of.")
+                        .multiple(true)
+                        .validator(|s| validate_path_exists(&s))
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT_FORMAT")
+                        .short("f")
+                        .long("format")
+                        .value_name("OUTPUT_FORMAT")
+                        .help("The restored text, or json with the alternatives of each word.")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("ocr-correct")
+                .about("Correct the OCR errors of the text of stdin, e.g. rn read for m or ṁ for ṃ, against _all_words.")
+                .arg(
+                    Arg::with_name("CONFUSIONS_PATH")
+                        .short("m")
+                        .long("confusions")
+                        .value_name("CONFUSIONS_PATH")
+                        .help("A tsv file of 'read<TAB>printed<TAB>cost' lines to use instead of the built-in confusions.")
+                        .validator(|s| validate_file_exists(&s))
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT_FORMAT")
+                        .short("f")
+                        .long("format")
+                        .value_name("OUTPUT_FORMAT")
+                        .help("The corrected text, with the edits to review logged with -v, or json with the edits and the unknown words.")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sort")
+                .about("Sort the lines of stdin in Pāli alphabetical order."),
+        )
+        .subcommand(
+            SubCommand::with_name("length")
+                .about("Print the number of Pāli letters in each word (or line of stdin).")
+                .arg(create_words_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("tokenize-text")
+                .about("Split stdin into words, numbers, punctuation and whitespace, printed as json with their offsets."),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("Check the words of texts in Roman script against Pāli phonotactics, failing on errors.")
+                .arg(
+                    Arg::with_name("FILE_PATHS")
+                        .help("The text files. stdin is used if none are given.")
+                        .multiple(true)
+                        .validator(|s| validate_file_exists(&s)),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT_FORMAT")
+                        .short("f")
+                        .long("format")
+                        .value_name("OUTPUT_FORMAT")
+                        .help("One 'path:line:column: severity[rule]: message' line per diagnostic, or json.")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("transliterate")
+                .about("Transliterate each word (or line of stdin) from Roman script.")
+                .arg(create_words_arg())
+                .arg(
+                    Arg::with_name("SCRIPT")
+                        .short("s")
+                        .long("script")
+                        .value_name("SCRIPT")
+                        .help("The target script.")
+                        .possible_values(&["roman", "devanagari", "sinhala", "thai"])
+                        .default_value("devanagari")
+                        .validator(|s| Script::from_str(&s).map(|_| ()))
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("ipa")
+                .about("Transcribe the words (or lines of stdin) to IPA as a recitation tradition says them.")
+                .arg(create_words_arg())
+                .arg(
+                    Arg::with_name("PROFILE")
+                        .short("p")
+                        .long("profile")
+                        .value_name("PROFILE")
+                        .help("The recitation tradition.")
+                        .possible_values(&["reconstructed", "sri-lankan", "burmese", "thai"])
+                        .default_value("reconstructed")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("SYLLABLES")
+                        .short("s")
+                        .long("syllables")
+                        .help("Separate the syllables with '.'."),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT_FORMAT")
+                        .short("f")
+                        .long("format")
+                        .value_name("OUTPUT_FORMAT")
+                        .help("The transcription, or json with the syllables of each word in Roman script and IPA.")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("numeral")
+                .about("Spell a number as a Pāli cardinal or ordinal, declined in a case with -c.")
+                .arg(
+                    Arg::with_name("NUMBER")
+                        .help("The number, from 1 to 999999999.")
+                        .required(true)
+                        .validator(|s| s.parse::<u32>().map(|_| ()).map_err(|e| e.to_string()))
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("ORDINAL")
+                        .short("o")
+                        .long("ordinal")
+                        .help("Spell the ordinal, e.g. dutiya for 2."),
+                )
+                .arg(
+                    Arg::with_name("CASE")
+                        .short("c")
+                        .long("case")
+                        .value_name("CASE")
+                        .help("Decline the numeral in the case, with the forms of inflections.db.")
+                        .possible_values(&["nom", "acc", "instr", "dat", "abl", "gen", "loc", "voc"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("GENDER")
+                        .short("g")
+                        .long("gender")
+                        .value_name("GENDER")
+                        .help("The gender of the declined numeral.")
+                        .possible_values(&["masc", "fem", "nt"])
+                        .default_value("masc")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("parse-numeral")
+                .about("Read Pāli numeral phrases as numbers, printed as 'number<TAB>cardinal|ordinal'.")
+                .arg(
+                    Arg::with_name("PHRASES")
+                        .help("The phrases, e.g. 'pañca satāni'. Lines of stdin are used if none are given.")
+                        .multiple(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-db")
+                .about("Report the head words, patterns and forms that changed between two inflections.db.")
+                .arg(
+                    Arg::with_name("OLD_DB_PATH")
+                        .help("The path to the previous inflections.db.")
+                        .required(true)
+                        .validator(|s| validate_file_exists(&s))
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("NEW_DB_PATH")
+                        .help("The path to the new inflections.db.")
+                        .required(true)
+                        .validator(|s| validate_file_exists(&s))
+                        .index(2),
+                )
+                .arg(create_format_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("check-db")
+                .about("Validate inflections.db and print the report as json.")
+                .arg(
+                    Arg::with_name("GENERATION")
+                        .short("g")
+                        .long("generation")
+                        .help("Also generate the inflection table of every head word (slow)."),
+                ),
+        )
+        .get_matches()
+}
+
+fn create_inflection_db_path_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("INFLECTION_DB_PATH")
+        .short("i")
+        .long("inflection-db")
+        .value_name("INFLECTION_DB_PATH")
+        .help("The path to inflections.db. Defaults to inflections.db in the workspace.")
+        .required(false)
+        .validator(|s| validate_file_exists(&s))
+        .takes_value(true)
+        .global(true)
+}
+
+fn create_verbose_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("VERBOSE")
+        .short("v")
+        .long("verbose")
+        .help("Log progress and warnings to stderr.")
+        .global(true)
+}
+
+fn create_pali1_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("PALI1")
+        .help("The head word, e.g. 'ababa 1'.")
+        .required(true)
+        .index(1)
+}
+
+fn create_locale_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("LOCALE")
+        .short("l")
+        .long("locale")
+        .value_name("LOCALE")
+        .help("The locale for the abbreviations in the table.")
+        .default_value("en")
+        .takes_value(true)
+}
+
+fn create_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("FORMAT")
+        .short("f")
+        .long("format")
+        .value_name("FORMAT")
+        .help("The output format.")
+        .possible_values(&["html", "json"])
+        .default_value("html")
+        .takes_value(true)
+}
+
+fn create_words_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("WORDS")
+        .help("The words. Lines of stdin are used if none are given.")
+        .multiple(true)
+}
+
+fn validate_file_exists(s: &str) -> Result<(), String> {
+    if Path::new(&s).is_file() {
+        Ok(())
+    } else {
+        Err(format!("'{}' does not exist.", s))
+    }
+}
+
+fn validate_path_exists(s: &str) -> Result<(), String> {
+    if Path::new(&s).exists() {
+        Ok(())
+    } else {
+        Err(format!("'{}' does not exist.", s))
+    }
+}
+
+fn validate_u32(s: &str) -> Result<(), String> {
+    s.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,434 @@
+use clap::Values;
+use pls_core::alphabet::{string_compare, string_length};
+use pls_core::diacritics::{count_words, DiacriticRestorer};
+use pls_core::inflections::{
+    self, analyze_word, create_spelling_suggester, generate_all_inflections,
+    generate_inflection_table, generate_inflection_table_data, generate_verb_derivation_table,
+    generate_verb_derivations, load_word_inventory, search_words, suggest_spellings,
+};
+use pls_core::ipa::{transcribe, transcribe_word, Profile};
+use pls_core::numerals::{self, NumeralKind};
+use pls_core::ocr::{ConfusionMatrix, OcrCorrector};
+use pls_core::phonotactics::{lint_text, Severity};
+use pls_core::tokenizer::{tokenize, words as text_words};
+use pls_core_extras::cscd;
+use pls_core_extras::inflection_db_diff::diff_inflection_dbs;
+use pls_core_extras::inflection_db_validator::{validate_inflection_db, ValidationOptions};
+use pls_core_extras::inflection_generator::PlsInflectionGenerator;
+use pls_core_extras::logger::PlsLogger;
+use pls_core_extras::transliterator::{self, Script};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+pub(crate) fn table(
+    igen: &PlsInflectionGenerator,
+    pali1: &str,
+    format: &str,
+    with_details: bool,
+) -> Result<(), String> {
+    let output = match format {
+        "json" => {
+            let data = generate_inflection_table_data(pali1, &igen.inflection_host)?;
+            serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?
+        }
+        _ => generate_inflection_table(pali1, with_details, &igen.inflection_host)?,
+    };
+
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn derivations(
+    igen: &PlsInflectionGenerator,
+    pali1: &str,
+    format: &str,
+) -> Result<(), String> {
+    let output = match format {
+        "json" => {
+            let derivations = generate_verb_derivations(pali1, &igen.inflection_host)?;
+            serde_json::to_string_pretty(&derivations).map_err(|e| e.to_string())?
+        }
+        _ => generate_verb_derivation_table(pali1, false, &igen.inflection_host)?,
+    };
+
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn forms(igen: &PlsInflectionGenerator, pali1: &str) -> Result<(), String> {
+    for form in generate_all_inflections(pali1, &igen.inflection_host)? {
+        println!("{}", form);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn analyze(igen: &PlsInflectionGenerator, word: &str) -> Result<(), String> {
+    let analyses = analyze_word(word, &igen.inflection_host)?;
+    if analyses.is_empty() {
+        return Err(format!("No head word found for '{}'.", word));
+    }
+
+    let output = serde_json::to_string_pretty(&analyses).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn search(
+    igen: &PlsInflectionGenerator,
+    query: &str,
+    max_results: usize,
+) -> Result<(), String> {
+    let results = search_words(query, max_results, &igen.inflection_host)?;
+    let output = serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn restore_diacritics(
+    igen: &PlsInflectionGenerator,
+    corpus_paths: Option<Values>,
+    format: &str,
+) -> Result<(), String> {
+    let logger = igen.inflection_host.logger;
+    let mut frequencies = HashMap::new();
+    for path in corpus_paths.into_iter().flatten() {
+        logger.info(&format!("Counting the words of '{}'...", path));
+        count_corpus_words(Path::new(path), &mut frequencies)?;
+    }
+
+    let restorer =
+        DiacriticRestorer::new(load_word_inventory(&igen.inflection_host)?, &frequencies);
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+
+    let restoration = restorer.restore(&text);
+    match format {
+        "json" => {
+            let output = serde_json::to_string_pretty(&restoration).map_err(|e| e.to_string())?;
+            println!("{}", output);
+        }
+        _ => print!("{}", restoration.text),
+    }
+
+    Ok(())
+}
+
+pub(crate) fn ocr_correct(
+    igen: &PlsInflectionGenerator,
+    confusions_path: Option<&str>,
+    format: &str,
+) -> Result<(), String> {
+    let confusions = match confusions_path {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read '{}': {}", path, e))?
+            .parse::<ConfusionMatrix>()?,
+        None => ConfusionMatrix::default(),
+    };
+
+    let corrector = OcrCorrector::new(load_word_inventory(&igen.inflection_host)?, confusions);
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+
+    let correction = corrector.correct(&text);
+    match format {
+        "json" => {
+            let output = serde_json::to_string_pretty(&correction).map_err(|e| e.to_string())?;
+            println!("{}", output);
+        }
+        _ => {
+            let logger = igen.inflection_host.logger;
+            for edit in correction.edits_to_review() {
+                let alternatives = if edit.alternatives.is_empty() {
+                    String::new()
+                } else {
+                    format!(", or {}", edit.alternatives.join(", "))
+                };
+                logger.warning(&format!(
+                    "Review '{}' → '{}' at byte {} (cost {}{}).",
+                    edit.original, edit.corrected, edit.byte_start, edit.cost, alternatives
+                ));
+            }
+            print!("{}", correction.text);
+        }
+    }
+
+    Ok(())
+}
+
+/// CSCD books are read without their paragraph numbers and notes, other files as plain text.
+fn count_corpus_words(path: &Path, counts: &mut HashMap<String, u64>) -> Result<(), String> {
+    let path_str = path.to_str().ok_or("Invalid corpus path.")?;
+    if path.is_dir() || path_str.ends_with(".xml") {
+        for file in cscd::find_xml_files(&[path_str])? {
+            for paragraph in cscd::read_book(&file, false)?.paragraphs {
+                count_words(&paragraph.text, counts);
+            }
+        }
+    } else {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read '{}': {}", path.display(), e))?;
+        count_words(&text, counts);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn suggest(
+    igen: &PlsInflectionGenerator,
+    words: Option<Values>,
+    max_cost: u32,
+    max_results: usize,
+) -> Result<(), String> {
+    let words = get_words(words)?;
+    let suggester = create_spelling_suggester(&igen.inflection_host)?;
+    let suggestions = words
+        .into_iter()
+        .map(|word| {
+            let suggestions = suggest_spellings(
+                &word,
+                &suggester,
+                max_cost,
+                max_results,
+                &igen.inflection_host,
+            )?;
+            Ok(serde_json::json!({ "word": word, "suggestions": suggestions }))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let output = serde_json::to_string_pretty(&suggestions).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn check_db(
+    igen: &PlsInflectionGenerator,
+    check_generation: bool,
+) -> Result<(), String> {
+    let report = validate_inflection_db(igen, &ValidationOptions { check_generation });
+    println!("{}", report.to_json()?);
+
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(format!(
+            "inflections.db has {} errors and {} warnings.",
+            report.error_count, report.warning_count
+        ))
+    }
+}
+
+pub(crate) fn diff_db(
+    old_db_path: &str,
+    new_db_path: &str,
+    format: &str,
+    logger: &dyn PlsLogger,
+) -> Result<(), String> {
+    let version = env!("CARGO_PKG_VERSION");
+    let url = env!("CARGO_PKG_NAME");
+    let old = PlsInflectionGenerator::new("en", version, url, old_db_path, logger)?;
+    let new = PlsInflectionGenerator::new("en", version, url, new_db_path, logger)?;
+
+    let diff = diff_inflection_dbs(&old, &new)?;
+    let output = match format {
+        "json" => diff.to_json()?,
+        _ => diff.to_html()?,
+    };
+
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn sort() -> Result<(), String> {
+    let mut lines = read_stdin_lines()?;
+    lines.sort_by(|a, b| Ord::cmp(&string_compare(a, b), &0));
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn length(words: Option<Values>) -> Result<(), String> {
+    for word in get_words(words)? {
+        println!("{}\t{}", string_length(&word), word);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn tokenize_text() -> Result<(), String> {
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+
+    let tokens = tokenize(&text);
+    let output = serde_json::to_string_pretty(&tokens).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
+
+pub(crate) fn lint(file_paths: Option<Values>, format: &str) -> Result<(), String> {
+    let texts = match file_paths {
+        Some(paths) => paths
+            .map(|path| {
+                fs::read_to_string(path)
+                    .map(|text| (path.to_string(), text))
+                    .map_err(|e| format!("Unable to read '{}': {}", path, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| e.to_string())?;
+            vec![("<stdin>".to_string(), text)]
+        }
+    };
+
+    let mut error_count = 0;
+    let mut results = Vec::new();
+    for (path, text) in &texts {
+        for d in lint_text(text) {
+            if d.severity == Severity::Error {
+                error_count += 1;
+            }
+            // Lines and columns count from 1, columns in chars.
+            let line_start = text[..d.byte_start].rfind('\n').map_or(0, |i| i + 1);
+            let line = text[..line_start].matches('\n').count() + 1;
+            let column = text[line_start..d.byte_start].chars().count() + 1;
+            match format {
+                "json" => results.push(serde_json::json!({
+                    "path": path,
+                    "line": line,
+                    "column": column,
+                    "diagnostic": d,
+                })),
+                _ => println!("{}:{}:{}: {} ('{}')", path, line, column, d, d.word),
+            }
+        }
+    }
+
+    if format == "json" {
+        let output = serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?;
+        println!("{}", output);
+    }
+
+    if error_count > 0 {
+        Err(format!("{} phonotactic error(s) found.", error_count))
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn ipa(
+    words: Option<Values>,
+    profile: &str,
+    syllable_boundaries: bool,
+    format: &str,
+) -> Result<(), String> {
+    let profile = Profile::from_str(profile)?;
+    let lines = get_words(words)?;
+    match format {
+        "json" => {
+            let transcriptions: Vec<_> = lines
+                .iter()
+                .flat_map(|line| text_words(line))
+                .map(|word| {
+                    serde_json::json!({
+                        "word": word.text,
+                        "syllables": transcribe_word(word.text, profile),
+                    })
+                })
+                .collect();
+            let output =
+                serde_json::to_string_pretty(&transcriptions).map_err(|e| e.to_string())?;
+            println!("{}", output);
+        }
+        _ => {
+            for line in lines {
+                println!("{}", transcribe(&line, profile, syllable_boundaries));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn numeral(number: u32, ordinal: bool) -> Result<(), String> {
+    let spelling = if ordinal {
+        numerals::ordinal(number as u64)?
+    } else {
+        numerals::cardinal(number as u64)?
+    };
+
+    println!("{}", spelling);
+    Ok(())
+}
+
+pub(crate) fn inflect_numeral(
+    igen: &PlsInflectionGenerator,
+    number: u32,
+    ordinal: bool,
+    case: &str,
+    gender: &str,
+) -> Result<(), String> {
+    let kind = if ordinal {
+        NumeralKind::Ordinal
+    } else {
+        NumeralKind::Cardinal
+    };
+
+    let forms =
+        inflections::inflect_numeral(number as u64, kind, case, gender, &igen.inflection_host)?;
+    for form in forms {
+        println!("{}", form);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn parse_numeral(phrases: Option<Values>) -> Result<(), String> {
+    for phrase in get_words(phrases)? {
+        let parsed = numerals::parse_numeral(&phrase)?;
+        let kind = match parsed.kind {
+            NumeralKind::Cardinal => "cardinal",
+            NumeralKind::Ordinal => "ordinal",
+        };
+        println!("{}\t{}", parsed.value, kind);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn transliterate(words: Option<Values>, script: &str) -> Result<(), String> {
+    let script = Script::from_str(script)?;
+    for word in get_words(words)? {
+        println!("{}", transliterator::transliterate(&word, script));
+    }
+
+    Ok(())
+}
+
+fn get_words(words: Option<Values>) -> Result<Vec<String>, String> {
+    match words {
+        Some(words) => Ok(words.map(|w| w.to_string()).collect()),
+        None => read_stdin_lines(),
+    }
+}
+
+fn read_stdin_lines() -> Result<Vec<String>, String> {
+    io::stdin()
+        .lock()
+        .lines()
+        .collect::<Result<Vec<String>, io::Error>>()
+        .map_err(|e| e.to_string())
+}
@@ -1,35 +1,119 @@
-use pls_core_extras::host::PlsHost;
-use pls_core_extras::logger::ColoredConsoleLogger;
-use pls_core_extras::sql_access::SqlAccess;
-use rusqlite::Connection;
+use pls_core_extras::inflection_generator::PlsInflectionGenerator;
+use pls_core_extras::logger::{ColoredStderrLogger, NullLogger, PlsLogger};
 use std::path::PathBuf;
 
+mod args;
+mod commands;
+
 fn main() -> Result<(), String> {
-    println!("{:?}", pls_core::alphabet::PALI_ALPHABET_ROMAN);
-    let x = pls_core::alphabet::PaliAlphabet::Aa;
-    println!("ā > bh? {:#?}", x > pls_core::alphabet::PaliAlphabet::Bh);
+    let arg_matches = args::parse_args();
+    let logger: &dyn PlsLogger = if arg_matches.is_present("VERBOSE") {
+        &ColoredStderrLogger {}
+    } else {
+        &NullLogger {}
+    };
 
-    let inflections_db_path = resolve_file_in_manifest_dir("inflections.db")
-        .expect("must exist")
-        .as_path()
+    let default_inflections_db_path = resolve_file_in_manifest_dir("inflections.db")?
         .to_str()
-        .expect("must exist")
+        .ok_or("Invalid inflections.db path.")?
         .to_owned();
-    let connection = Connection::open(&inflections_db_path)
-        .map_err(|e| format!("Cannot open db '{}'. Error: {}.", &inflections_db_path, e))?;
-
-    let pls_host = PlsHost {
-        locale: "en",
-        version: "host version v0.0.1",
-        url: "the table is hosted here",
-        sql_access: SqlAccess { connection },
-        logger: &ColoredConsoleLogger {},
-    };
 
-    let html = pls_core::inflections::generate_all_inflections("ababa 1", &pls_host)?;
-    println!("{:#?}", html);
+    match arg_matches.subcommand() {
+        ("sort", Some(_)) => commands::sort(),
+        ("length", Some(m)) => commands::length(m.values_of("WORDS")),
+        ("tokenize-text", Some(_)) => commands::tokenize_text(),
+        ("lint", Some(m)) => commands::lint(
+            m.values_of("FILE_PATHS"),
+            m.value_of("OUTPUT_FORMAT").expect("has default value"),
+        ),
+        ("transliterate", Some(m)) => commands::transliterate(
+            m.values_of("WORDS"),
+            m.value_of("SCRIPT").expect("has default value"),
+        ),
+        ("ipa", Some(m)) => commands::ipa(
+            m.values_of("WORDS"),
+            m.value_of("PROFILE").expect("has default value"),
+            m.is_present("SYLLABLES"),
+            m.value_of("OUTPUT_FORMAT").expect("has default value"),
+        ),
+        ("numeral", Some(m)) if !m.is_present("CASE") => commands::numeral(
+            parse_u32(m.value_of("NUMBER").expect("mandatory argument"))?,
+            m.is_present("ORDINAL"),
+        ),
+        ("parse-numeral", Some(m)) => commands::parse_numeral(m.values_of("PHRASES")),
+        ("diff-db", Some(m)) => commands::diff_db(
+            m.value_of("OLD_DB_PATH").expect("mandatory argument"),
+            m.value_of("NEW_DB_PATH").expect("mandatory argument"),
+            m.value_of("FORMAT").expect("has default value"),
+            logger,
+        ),
+        (name, Some(m)) => {
+            let inflections_db_path = m
+                .value_of("INFLECTION_DB_PATH")
+                .unwrap_or(&default_inflections_db_path);
+            let igen = PlsInflectionGenerator::new(
+                m.value_of("LOCALE").unwrap_or("en"),
+                env!("CARGO_PKG_VERSION"),
+                env!("CARGO_PKG_NAME"),
+                inflections_db_path,
+                logger,
+            )?;
+
+            match name {
+                "table" => commands::table(
+                    &igen,
+                    m.value_of("PALI1").expect("mandatory argument"),
+                    m.value_of("FORMAT").expect("has default value"),
+                    m.is_present("DETAILS"),
+                ),
+                "derivations" => commands::derivations(
+                    &igen,
+                    m.value_of("PALI1").expect("mandatory argument"),
+                    m.value_of("FORMAT").expect("has default value"),
+                ),
+                "forms" => commands::forms(&igen, m.value_of("PALI1").expect("mandatory argument")),
+                "analyze" => {
+                    commands::analyze(&igen, m.value_of("WORD").expect("mandatory argument"))
+                }
+                "search" => commands::search(
+                    &igen,
+                    m.value_of("QUERY").expect("mandatory argument"),
+                    parse_u32(m.value_of("MAX_RESULTS").expect("has default value"))? as usize,
+                ),
+                "restore-diacritics" => commands::restore_diacritics(
+                    &igen,
+                    m.values_of("CORPUS_PATHS"),
+                    m.value_of("OUTPUT_FORMAT").expect("has default value"),
+                ),
+                "ocr-correct" => commands::ocr_correct(
+                    &igen,
+                    m.value_of("CONFUSIONS_PATH"),
+                    m.value_of("OUTPUT_FORMAT").expect("has default value"),
+                ),
+                "suggest" => commands::suggest(
+                    &igen,
+                    m.values_of("WORDS"),
+                    parse_u32(m.value_of("MAX_COST").expect("has default value"))?,
+                    parse_u32(m.value_of("MAX_RESULTS").expect("has default value"))? as usize,
+                ),
+                "numeral" => commands::inflect_numeral(
+                    &igen,
+                    parse_u32(m.value_of("NUMBER").expect("mandatory argument"))?,
+                    m.is_present("ORDINAL"),
+                    m.value_of("CASE")
+                        .expect("numeral without a case is handled above"),
+                    m.value_of("GENDER").expect("has default value"),
+                ),
+                "check-db" => commands::check_db(&igen, m.is_present("GENERATION")),
+                _ => Err(format!("Unknown command '{}'.", name)),
+            }
+        }
+        _ => Err("No command given.".to_string()),
+    }
+}
 
-    Ok(())
+fn parse_u32(s: &str) -> Result<u32, String> {
+    s.parse::<u32>().map_err(|e| e.to_string())
 }
 
 fn resolve_file_in_manifest_dir(file_name: &str) -> Result<PathBuf, String> {